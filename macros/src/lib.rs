@@ -0,0 +1,350 @@
+//! Macros for [axum-inertia](https://crates.io/crates/axum-inertia).
+//!
+//! These are re-exported by `axum-inertia` behind feature flags and
+//! shouldn't be depended on directly.
+
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, FnArg, ItemFn, LitStr, ReturnType, Type};
+
+/// Derives `axum_inertia::typegen::TypeScript` for a struct with named
+/// fields.
+///
+/// Respects the serde attributes `rename` (on the struct and its
+/// fields), `rename_all = "camelCase"` and `skip`. Fields with
+/// `skip_serializing_if`, and `Option` fields, are optional (`name?:`).
+/// `flatten` isn't supported, and is a compile error.
+#[proc_macro_derive(TypeScript, attributes(serde))]
+pub fn derive_typescript(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_typescript(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_typescript(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let ident = &input.ident;
+    let attrs = SerdeAttrs::parse(&input.attrs)?;
+    let name = attrs.rename.unwrap_or_else(|| ident.to_string());
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "TypeScript can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "TypeScript can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut members = Vec::new();
+    for field in fields {
+        let field_attrs = SerdeAttrs::parse(&field.attrs)?;
+        if field_attrs.skip {
+            continue;
+        }
+        if field_attrs.flatten {
+            return Err(syn::Error::new_spanned(
+                field,
+                "TypeScript can't be derived for #[serde(flatten)] fields",
+            ));
+        }
+        let rust_name = field.ident.as_ref().unwrap().to_string();
+        let ts_name = match (field_attrs.rename, &attrs.rename_all) {
+            (Some(rename), _) => rename,
+            (None, Some(rule)) if rule == "camelCase" => to_camel_case(&rust_name),
+            (None, Some(rule)) => {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    format!("unsupported rename_all rule for TypeScript: {}", rule),
+                ))
+            }
+            (None, None) => rust_name,
+        };
+        let ty = &field.ty;
+        let separator = if field_attrs.optional || is_option(ty) {
+            "?: "
+        } else {
+            ": "
+        };
+        members.push(quote! {
+            out.push_str("  ");
+            out.push_str(#ts_name);
+            out.push_str(#separator);
+            out.push_str(&<#ty as ::axum_inertia::typegen::TypeScript>::ts_type());
+            out.push_str(";\n");
+        });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::axum_inertia::typegen::TypeScript for #ident #ty_generics #where_clause {
+            fn ts_type() -> ::std::string::String {
+                ::std::string::String::from(#name)
+            }
+
+            fn ts_declaration() -> ::std::option::Option<::std::string::String> {
+                let mut out = ::std::string::String::new();
+                out.push_str("export interface ");
+                out.push_str(#name);
+                out.push_str(" {\n");
+                #(#members)*
+                out.push_str("}\n");
+                ::std::option::Option::Some(out)
+            }
+        }
+    })
+}
+
+/// Derives `axum_inertia::partial::PropKey` for an enum of unit
+/// variants, one per prop of a component.
+///
+/// Keys are the variant names, respecting the serde attributes
+/// `rename` (on variants) and `rename_all = "camelCase"` or
+/// `"snake_case"` (on the enum).
+#[proc_macro_derive(PropKey, attributes(serde))]
+pub fn derive_prop_key(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_prop_key(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_prop_key(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let ident = &input.ident;
+    let attrs = SerdeAttrs::parse(&input.attrs)?;
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "PropKey can only be derived for enums",
+            ))
+        }
+    };
+
+    let mut names = Vec::new();
+    let mut keys = Vec::new();
+    for variant in variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "PropKey can only be derived for enums with unit variants",
+            ));
+        }
+        let variant_attrs = SerdeAttrs::parse(&variant.attrs)?;
+        let rust_name = variant.ident.to_string();
+        let key = match (variant_attrs.rename, &attrs.rename_all) {
+            (Some(rename), _) => rename,
+            (None, Some(rule)) if rule == "camelCase" => lowercase_first(&rust_name),
+            (None, Some(rule)) if rule == "snake_case" => to_snake_case(&rust_name),
+            (None, Some(rule)) => {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    format!("unsupported rename_all rule for PropKey: {}", rule),
+                ))
+            }
+            (None, None) => rust_name,
+        };
+        names.push(&variant.ident);
+        keys.push(key);
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::axum_inertia::partial::PropKey for #ident #ty_generics #where_clause {
+            const KEYS: &'static [&'static str] = &[#(#keys),*];
+
+            fn from_key(key: &str) -> ::std::option::Option<Self> {
+                match key {
+                    #(#keys => ::std::option::Option::Some(Self::#names),)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+
+            fn key(&self) -> &'static str {
+                match self {
+                    #(Self::#names => #keys,)*
+                }
+            }
+        }
+    })
+}
+
+/// Turns an async fn returning props, or a `Result` of props, into an
+/// Inertia handler rendering `component`. See
+/// `axum_inertia::inertia`.
+#[proc_macro_attribute]
+pub fn inertia(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let component = parse_macro_input!(attr as LitStr);
+    let function = parse_macro_input!(item as ItemFn);
+    match expand_inertia(component, function) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_inertia(component: LitStr, function: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = function;
+    if sig.asyncness.is_none() {
+        return Err(syn::Error::new_spanned(
+            sig.fn_token,
+            "#[inertia] handlers must be async",
+        ));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "#[inertia] handlers can't be generic",
+        ));
+    }
+
+    let mut params = Vec::new();
+    let mut args = Vec::new();
+    for (i, input) in sig.inputs.iter().enumerate() {
+        let FnArg::Typed(input) = input else {
+            return Err(syn::Error::new_spanned(
+                input,
+                "#[inertia] handlers can't take self",
+            ));
+        };
+        let arg = format_ident!("__arg{}", i);
+        let ty = &input.ty;
+        params.push(quote! { #arg: #ty });
+        args.push(arg);
+    }
+
+    let returns_result = match &sig.output {
+        ReturnType::Type(_, ty) => match &**ty {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Result"),
+            _ => false,
+        },
+        ReturnType::Default => false,
+    };
+    let result = if returns_result {
+        quote! { __inertia_handler(#(#args),*).await }
+    } else {
+        quote! {
+            ::std::result::Result::<_, ::std::convert::Infallible>::Ok(
+                __inertia_handler(#(#args),*).await,
+            )
+        }
+    };
+
+    let ident = &sig.ident;
+    let inputs = &sig.inputs;
+    let output = &sig.output;
+    Ok(quote! {
+        #(#attrs)*
+        #vis async fn #ident(
+            __inertia: ::axum_inertia::Inertia,
+            #(#params),*
+        ) -> ::axum_inertia::__private::Response {
+            async fn __inertia_handler(#inputs) #output #block
+
+            __inertia.render_result(#component, #result)
+        }
+    })
+}
+
+#[derive(Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    skip: bool,
+    optional: bool,
+    flatten: bool,
+}
+
+impl SerdeAttrs {
+    fn parse(attrs: &[syn::Attribute]) -> syn::Result<SerdeAttrs> {
+        let mut out = SerdeAttrs::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    out.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("rename_all") {
+                    out.rename_all = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                    out.skip = true;
+                } else if meta.path.is_ident("skip_serializing_if") {
+                    out.optional = true;
+                    meta.value()?.parse::<LitStr>()?;
+                } else if meta.path.is_ident("flatten") {
+                    out.flatten = true;
+                } else if meta.input.peek(syn::Token![=]) {
+                    // Ignore other serde attributes and their values.
+                    meta.value()?.parse::<syn::Expr>()?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(out)
+    }
+}
+
+/// Whether `ty` is an `Option`, which may be missing in TypeScript.
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+fn to_camel_case(s: &str) -> String {
+    let mut out = String::new();
+    let mut upper = false;
+    for c in s.chars() {
+        if c == '_' {
+            upper = !out.is_empty();
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn lowercase_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn to_snake_case(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        if c.is_uppercase() && !out.is_empty() {
+            out.push('_');
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
@@ -0,0 +1,104 @@
+//! The `auth` shared prop for apps using [axum-login].
+//!
+//! Inertia apps conventionally share the current user as the
+//! `auth.user` prop. [InertiaConfig::auth_user](crate::InertiaConfig::auth_user)
+//! reads the user from the `AuthSession` that axum-login's
+//! `AuthManagerLayer` adds to each request, and shares the fields
+//! picked by a closure, or `null` for guests:
+//!
+//! ```rust,ignore
+//! use axum_inertia::vite;
+//! use serde_json::json;
+//!
+//! // Pages receive `{ "auth": { "user": { "id": 1, "name": "mikey" } } }`
+//! let inertia = vite::Development::default()
+//!     .into_config()
+//!     .auth_user::<Backend, _, _>(|user| json!({ "id": user.id, "name": user.name }));
+//! ```
+//!
+//! Only pick fields that are safe to send to the browser: the prop is
+//! visible in the page source.
+//!
+//! Requires the `axum-login` feature.
+//!
+//! [axum-login]: https://crates.io/crates/axum-login
+
+use axum_login::{AuthSession, AuthnBackend};
+use http::request::Parts;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub(crate) type AuthUserResolver = dyn Fn(&Parts) -> Value + Send + Sync;
+
+/// Returns a resolver of the `auth` prop for requests authenticated
+/// with backend `B`.
+pub(crate) fn resolver<B, F, T>(user: F) -> Arc<AuthUserResolver>
+where
+    B: AuthnBackend + 'static,
+    F: Fn(&B::User) -> T + Send + Sync + 'static,
+    T: Serialize,
+{
+    Arc::new(move |parts: &Parts| {
+        let session = parts.extensions.get::<AuthSession<B>>();
+        if session.is_none() {
+            tracing::warn!("no auth session found, is the AuthManagerLayer installed?");
+        }
+        let user = session
+            .and_then(|session| session.user.as_ref())
+            .map(|current| serde_json::to_value(user(current)))
+            .transpose()
+            .unwrap_or_else(|err| {
+                tracing::error!(%err, "failed to serialize the auth user");
+                None
+            });
+        json!({ "user": user })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum_login::{AuthUser, UserId};
+    use std::convert::Infallible;
+
+    #[derive(Clone, Debug)]
+    struct User;
+
+    impl AuthUser for User {
+        type Id = i64;
+
+        fn id(&self) -> i64 {
+            1
+        }
+
+        fn session_auth_hash(&self) -> &[u8] {
+            b""
+        }
+    }
+
+    #[derive(Clone)]
+    struct Backend;
+
+    #[async_trait::async_trait]
+    impl AuthnBackend for Backend {
+        type User = User;
+        type Credentials = ();
+        type Error = Infallible;
+
+        async fn authenticate(&self, _: ()) -> Result<Option<User>, Infallible> {
+            Ok(None)
+        }
+
+        async fn get_user(&self, _: &UserId<Self>) -> Result<Option<User>, Infallible> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_resolver_for_guests() {
+        let resolve = resolver::<Backend, _, _>(|user| user.id());
+        let (parts, _) = http::Request::new(()).into_parts();
+        assert_eq!(resolve(&parts), json!({ "user": null }));
+    }
+}
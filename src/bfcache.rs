@@ -0,0 +1,89 @@
+//! Headers for the browser's back-forward cache (bfcache).
+//!
+//! Inertia serves html and json at the same urls. Without `Vary:
+//! X-Inertia`, going back to a page after a full reload can show the
+//! cached json instead of the page. And pages sent with `Cache-Control:
+//! no-store` are kept out of the bfcache by most browsers, so going
+//! back reloads them.
+//!
+//! [InertiaConfig::bfcache_headers(true)](crate::InertiaConfig::bfcache_headers)
+//! sends `Vary: X-Inertia` and `Cache-Control: private, no-cache` with
+//! every Inertia response, so pages can be restored from the bfcache,
+//! and are revalidated otherwise. Pages that must not be restored, e.g.
+//! with one-time secrets, can opt out with [NoBfcache]:
+//!
+//! ```rust
+//! use axum::response::IntoResponse;
+//! use axum_inertia::{bfcache::NoBfcache, Inertia};
+//! use serde_json::json;
+//!
+//! async fn recovery_codes(i: Inertia) -> impl IntoResponse {
+//!     NoBfcache(i.render("RecoveryCodes", json!({ "codes": ["..."] })))
+//! }
+//! ```
+
+use axum::response::{IntoResponse, Response};
+use http::header::CACHE_CONTROL;
+use http::HeaderValue;
+
+/// The `Cache-Control` value of the bfcache preset.
+pub const CACHE_CONTROL_VALUE: HeaderValue = HeaderValue::from_static("private, no-cache");
+
+/// Keeps a response out of the bfcache and other caches, with
+/// `Cache-Control: no-store`.
+#[derive(Clone, Debug)]
+pub struct NoBfcache<R>(pub R);
+
+impl<R: IntoResponse> IntoResponse for NoBfcache<R> {
+    fn into_response(self) -> Response {
+        let mut response = self.0.into_response();
+        response
+            .headers_mut()
+            .insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Inertia, InertiaConfig};
+    use axum::{routing::get, Router};
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn it_sends_bfcache_headers() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|i: Inertia| async move { i.render("Home", json!({})) }),
+            )
+            .route(
+                "/secret",
+                get(|i: Inertia| async move { NoBfcache(i.render("Secret", json!({}))) }),
+            )
+            .with_state(
+                InertiaConfig::new(None, Box::new(|props| props.to_string())).bfcache_headers(true),
+            );
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let res = reqwest::get(format!("http://{}/", &addr)).await.unwrap();
+        assert_eq!(res.headers()["vary"], "x-inertia");
+        assert_eq!(res.headers()["cache-control"], "private, no-cache");
+
+        let res = reqwest::get(format!("http://{}/secret", &addr))
+            .await
+            .unwrap();
+        assert_eq!(res.headers()["vary"], "x-inertia");
+        assert_eq!(res.headers()["cache-control"], "no-store");
+    }
+}
@@ -0,0 +1,363 @@
+//! Scaffolds a new axum + vite project using axum-inertia. Available
+//! with the `cli` feature:
+//!
+//! ```sh
+//! cargo install axum-inertia --features cli
+//! cargo axum-inertia new my-app --react
+//! ```
+
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Scaffolds a new axum + vite project using axum-inertia.
+
+Usage: cargo axum-inertia new <NAME> [--react | --vue | --svelte]
+
+Options:
+  --react   Use React (default)
+  --vue     Use Vue
+  --svelte  Use Svelte
+";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Framework {
+    React,
+    Vue,
+    Svelte,
+}
+
+impl Framework {
+    /// The entry point of the client, relative to `client/`.
+    fn main(self) -> &'static str {
+        match self {
+            Framework::React => "src/main.jsx",
+            Framework::Vue | Framework::Svelte => "src/main.js",
+        }
+    }
+}
+
+/// Parses `new <NAME> [FLAG]`, ignoring the `axum-inertia` argument
+/// passed by cargo when run as `cargo axum-inertia`.
+fn parse_args(args: &[String]) -> Result<(String, Framework), String> {
+    let mut args = args.iter().map(String::as_str).peekable();
+    args.next_if_eq(&"axum-inertia");
+    if args.next() != Some("new") {
+        return Err("expected the `new` command".to_string());
+    }
+
+    let mut name = None;
+    let mut framework = Framework::React;
+    for arg in args {
+        match arg {
+            "--react" => framework = Framework::React,
+            "--vue" => framework = Framework::Vue,
+            "--svelte" => framework = Framework::Svelte,
+            flag if flag.starts_with('-') => return Err(format!("unknown option `{}`", flag)),
+            arg if name.is_none() => name = Some(arg.to_string()),
+            arg => return Err(format!("unexpected argument `{}`", arg)),
+        }
+    }
+    let name = name.ok_or("missing project name")?;
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("invalid project name `{}`", name));
+    }
+    Ok((name, framework))
+}
+
+/// Returns the paths and contents of the project files.
+fn files(name: &str, framework: Framework) -> Vec<(String, String)> {
+    let main_rs = MAIN_RS.replace("__MAIN__", framework.main()).replace(
+        "__REACT__",
+        if framework == Framework::React {
+            "\n            .react()"
+        } else {
+            ""
+        },
+    );
+    let (package_deps, plugin_import, plugin, entry, page_path, page) = match framework {
+        Framework::React => (
+            REACT_DEPS,
+            "import react from '@vitejs/plugin-react'",
+            "react()",
+            REACT_MAIN,
+            "src/Pages/Home.jsx",
+            REACT_PAGE,
+        ),
+        Framework::Vue => (
+            VUE_DEPS,
+            "import vue from '@vitejs/plugin-vue'",
+            "vue()",
+            VUE_MAIN,
+            "src/Pages/Home.vue",
+            VUE_PAGE,
+        ),
+        Framework::Svelte => (
+            SVELTE_DEPS,
+            "import { svelte } from '@sveltejs/vite-plugin-svelte'",
+            "svelte()",
+            SVELTE_MAIN,
+            "src/Pages/Home.svelte",
+            SVELTE_PAGE,
+        ),
+    };
+    let vite_config = VITE_CONFIG
+        .replace("__PLUGIN_IMPORT__", plugin_import)
+        .replace("__PLUGIN__", plugin)
+        .replace("__MAIN__", framework.main());
+    let package_json = PACKAGE_JSON.replace("__DEPS__", package_deps);
+
+    [
+        ("Cargo.toml".to_string(), CARGO_TOML.to_string()),
+        ("src/main.rs".to_string(), main_rs),
+        (".gitignore".to_string(), GITIGNORE.to_string()),
+        ("client/package.json".to_string(), package_json),
+        ("client/vite.config.js".to_string(), vite_config),
+        (format!("client/{}", framework.main()), entry.to_string()),
+        (format!("client/{}", page_path), page.to_string()),
+    ]
+    .into_iter()
+    .map(|(path, contents)| {
+        let contents = contents
+            .replace("__NAME__", name)
+            .replace("__VERSION__", env!("CARGO_PKG_VERSION"));
+        (path, contents)
+    })
+    .collect()
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (name, framework) = match parse_args(&args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let root = Path::new(&name);
+    if root.exists() {
+        eprintln!("error: `{}` already exists", name);
+        return ExitCode::FAILURE;
+    }
+    for (path, contents) in files(&name, framework) {
+        let path = root.join(path);
+        let written =
+            fs::create_dir_all(path.parent().unwrap()).and_then(|_| fs::write(&path, contents));
+        if let Err(err) = written {
+            eprintln!("error: could not write {}: {}", path.display(), err);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    println!(
+        "Created {name}. To start developing, run:
+
+    cd {name}/client && npm install && npm run dev
+    cd {name} && cargo run
+
+and open http://localhost:3000. For production, run `npm run build`
+in {name}/client, then `APP_ENV=production cargo run`."
+    );
+    ExitCode::SUCCESS
+}
+
+const CARGO_TOML: &str = r#"[package]
+name = "__NAME__"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+axum = "0.7"
+axum-inertia = "__VERSION__"
+serde_json = "1"
+tokio = { version = "1", features = ["full"] }
+tower-http = { version = "0.5", features = ["fs"] }
+"#;
+
+const MAIN_RS: &str = r#"use axum::{response::IntoResponse, routing::get, Router};
+use axum_inertia::{vite, Inertia};
+use serde_json::json;
+use tower_http::services::ServeDir;
+
+async fn home(i: Inertia) -> impl IntoResponse {
+    i.render("Home", json!({ "name": "__NAME__" }))
+}
+
+#[tokio::main]
+async fn main() {
+    let inertia = if std::env::var("APP_ENV").as_deref() == Ok("production") {
+        vite::Production::new("client/dist/.vite/manifest.json", "__MAIN__")
+            .expect("run `npm run build` in client/ first")
+            .title("__NAME__")
+            .into_config()
+    } else {
+        vite::Development::default()
+            .main("__MAIN__")
+            .title("__NAME__")__REACT__
+            .into_config()
+    };
+
+    let app = Router::new()
+        .route("/", get(home))
+        .nest_service("/assets", ServeDir::new("client/dist/assets"))
+        .with_state(inertia);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000")
+        .await
+        .unwrap();
+    axum::serve(listener, app).await.unwrap();
+}
+"#;
+
+const GITIGNORE: &str = "/target
+/client/node_modules
+/client/dist
+";
+
+const PACKAGE_JSON: &str = r#"{
+  "name": "__NAME__",
+  "private": true,
+  "type": "module",
+  "scripts": {
+    "dev": "vite",
+    "build": "vite build"
+  },
+  "dependencies": {
+__DEPS__
+  }
+}
+"#;
+
+const VITE_CONFIG: &str = r#"import { defineConfig } from 'vite'
+__PLUGIN_IMPORT__
+
+export default defineConfig({
+  plugins: [__PLUGIN__],
+  build: {
+    manifest: true,
+    rollupOptions: { input: '__MAIN__' },
+  },
+})
+"#;
+
+const REACT_DEPS: &str = r#"    "@inertiajs/react": "^2.0.0",
+    "@vitejs/plugin-react": "^4.3.0",
+    "react": "^18.3.0",
+    "react-dom": "^18.3.0",
+    "vite": "^5.4.0""#;
+
+const REACT_MAIN: &str = r#"import { createInertiaApp } from '@inertiajs/react'
+import { createRoot } from 'react-dom/client'
+
+createInertiaApp({
+  resolve: (name) => {
+    const pages = import.meta.glob('./Pages/**/*.jsx', { eager: true })
+    return pages[`./Pages/${name}.jsx`]
+  },
+  setup({ el, App, props }) {
+    createRoot(el).render(<App {...props} />)
+  },
+})
+"#;
+
+const REACT_PAGE: &str = r#"export default function Home({ name }) {
+  return <h1>Welcome to {name}</h1>
+}
+"#;
+
+const VUE_DEPS: &str = r#"    "@inertiajs/vue3": "^2.0.0",
+    "@vitejs/plugin-vue": "^5.1.0",
+    "vite": "^5.4.0",
+    "vue": "^3.4.0""#;
+
+const VUE_MAIN: &str = r#"import { createApp, h } from 'vue'
+import { createInertiaApp } from '@inertiajs/vue3'
+
+createInertiaApp({
+  resolve: (name) => {
+    const pages = import.meta.glob('./Pages/**/*.vue', { eager: true })
+    return pages[`./Pages/${name}.vue`]
+  },
+  setup({ el, App, props, plugin }) {
+    createApp({ render: () => h(App, props) }).use(plugin).mount(el)
+  },
+})
+"#;
+
+const VUE_PAGE: &str = r#"<script setup>
+defineProps({ name: String })
+</script>
+
+<template>
+  <h1>Welcome to {{ name }}</h1>
+</template>
+"#;
+
+const SVELTE_DEPS: &str = r#"    "@inertiajs/svelte": "^2.0.0",
+    "@sveltejs/vite-plugin-svelte": "^4.0.0",
+    "svelte": "^5.0.0",
+    "vite": "^5.4.0""#;
+
+const SVELTE_MAIN: &str = r#"import { createInertiaApp } from '@inertiajs/svelte'
+import { mount } from 'svelte'
+
+createInertiaApp({
+  resolve: (name) => {
+    const pages = import.meta.glob('./Pages/**/*.svelte', { eager: true })
+    return pages[`./Pages/${name}.svelte`]
+  },
+  setup({ el, App, props }) {
+    mount(App, { target: el, props })
+  },
+})
+"#;
+
+const SVELTE_PAGE: &str = r#"<script>
+  let { name } = $props()
+</script>
+
+<h1>Welcome to {name}</h1>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(&args(&["axum-inertia", "new", "my-app"])),
+            Ok(("my-app".to_string(), Framework::React))
+        );
+        assert_eq!(
+            parse_args(&args(&["new", "--vue", "my-app"])),
+            Ok(("my-app".to_string(), Framework::Vue))
+        );
+        assert!(parse_args(&args(&["new"])).is_err());
+        assert!(parse_args(&args(&["new", "../escape"])).is_err());
+        assert!(parse_args(&args(&["generate", "my-app"])).is_err());
+    }
+
+    #[test]
+    fn test_files() {
+        let files = files("my-app", Framework::Svelte);
+        let file = |path: &str| files.iter().find(|(p, _)| p == path).unwrap().1.clone();
+
+        assert!(file("Cargo.toml").contains(r#"name = "my-app""#));
+        assert!(file("src/main.rs").contains(r#".main("src/main.js")"#));
+        assert!(!file("src/main.rs").contains(".react()"));
+        assert!(file("client/vite.config.js").contains("svelte()"));
+        assert!(file("client/src/Pages/Home.svelte").contains("{name}"));
+        assert!(!files.iter().any(|(_, contents)| contents.contains("__")));
+    }
+}
@@ -0,0 +1,138 @@
+//! Sources of time and randomness, replaceable for deterministic
+//! tests.
+//!
+//! The parts of the crate that expire entries or generate tokens read
+//! a [Clock] or an [Rng], set with their `clock` and `rng` builders:
+//!
+//! - [MemoryCache](crate::props::MemoryCache) and
+//!   [PageStore](crate::page_store::PageStore) expire entries with a
+//!   clock,
+//! - [DeferStreams](crate::defer::DeferStreams) tokens and the
+//!   [PageStore](crate::page_store::PageStore) key come from an rng.
+//!
+//! They default to the system clock and random tokens. Tests can use a
+//! [MockClock] that only moves when advanced, instead of sleeping, and
+//! a [SequentialRng] for predictable urls:
+//!
+//! ```rust
+//! use axum_inertia::clock::{MockClock, SequentialRng};
+//! use axum_inertia::page_store::PageStore;
+//! use std::time::Duration;
+//!
+//! let clock = MockClock::new();
+//! let store = PageStore::new()
+//!     .clock(clock.clone())
+//!     .rng(SequentialRng::new());
+//! let url = store.insert("{}").unwrap();
+//! clock.advance(Duration::from_secs(120));
+//! // The page has expired.
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A source of the current time.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// A source of tokens, e.g. for urls that must not be guessed.
+pub trait Rng: Send + Sync {
+    /// Returns a token of 32 lowercase hex digits.
+    fn token(&self) -> String;
+}
+
+/// The system's monotonic clock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that stands still until [advance](MockClock::advance)d.
+/// Clones share their time.
+#[derive(Clone, Debug)]
+pub struct MockClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        MockClock {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+}
+
+impl MockClock {
+    pub fn new() -> MockClock {
+        Self::default()
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Tokens of 128 bits read from the operating system's secure random
+/// number generator.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemRng;
+
+impl Rng for SystemRng {
+    fn token(&self) -> String {
+        let mut bytes = [0; 16];
+        getrandom::getrandom(&mut bytes).expect("operating system random number generator");
+        hex::encode(bytes)
+    }
+}
+
+/// Tokens counting up from `...0001`. They are predictable, so only use
+/// them in tests. Clones share their counter.
+#[derive(Clone, Debug, Default)]
+pub struct SequentialRng {
+    count: Arc<AtomicU64>,
+}
+
+impl SequentialRng {
+    pub fn new() -> SequentialRng {
+        Self::default()
+    }
+}
+
+impl Rng for SequentialRng {
+    fn token(&self) -> String {
+        let count = self.count.fetch_add(1, Ordering::Relaxed) + 1;
+        format!("{:032x}", count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_and_sequential_rng() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        clock.clone().advance(Duration::from_secs(5));
+        assert_eq!(clock.now() - start, Duration::from_secs(5));
+
+        let rng = SequentialRng::new();
+        assert_eq!(rng.token(), "00000000000000000000000000000001");
+        assert_eq!(rng.clone().token(), "00000000000000000000000000000002");
+        assert_eq!(SystemRng.token().len(), 32);
+        assert_ne!(SystemRng.token(), SystemRng.token());
+    }
+}
@@ -1,10 +1,287 @@
+use crate::hints::{ClientHints, ACCEPT_CH, ACCEPT_CH_VALUE};
+use crate::page::Page;
+use crate::partial::Partial;
+use crate::props::{html, When};
+use crate::protocol::{HeaderNames, ProtocolVersion};
+use crate::provider::{PropProvider, ServiceProvider};
+use crate::request::Request;
+use crate::serializer::{PageSerializer, SerdeJson, SerdeJsonPretty};
+use crate::shell::ShellCache;
+use crate::strict::StrictMode;
+use http::header::VARY;
+use http::{HeaderMap, HeaderValue};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::error::Error;
 use std::sync::Arc;
+use tokio::sync::watch;
+use tower_service::Service;
+
+#[cfg(feature = "axum-login")]
+use crate::auth::AuthUserResolver;
+#[cfg(feature = "debug")]
+use crate::debug::{PageLog, PropsDiff};
+#[cfg(feature = "schema")]
+use crate::schema::PropSchemas;
 
 type LayoutResolver = Box<dyn Fn(String) -> String + Send + Sync>;
+type ComponentLayoutResolver = dyn Fn(&str, String) -> String + Send + Sync;
+type ResponseHook = dyn Fn(&Request, &mut Page, &mut HeaderMap) + Send + Sync;
+type PartialReloadHook = dyn Fn(&str, &Partial) + Send + Sync;
+type VersionConflictHook = dyn Fn(&Request) -> ConflictAction + Send + Sync;
+type ConflictPredicate = dyn Fn(&Request) -> bool + Send + Sync;
+type LayoutPropsProvider = dyn Fn(&Request) -> Value + Send + Sync;
+type HtmlSanitizer = dyn Fn(&str) -> String + Send + Sync;
+
+/// The body of `409 Conflict` responses sent on asset version
+/// mismatches.
+///
+/// The Inertia client ignores the body and reloads the page, but
+/// other clients (or developers poking at the endpoint) may find an
+/// explanation useful.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConflictBody {
+    /// An empty body.
+    #[default]
+    Empty,
+    /// A small html page linking to the new location.
+    Html,
+    /// A json object with `message` and `location` fields.
+    Json,
+}
+
+/// What to do with a request whose asset version doesn't match. See
+/// [InertiaConfig::on_version_conflict].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConflictAction {
+    /// Respond with `409 Conflict`, so the client reloads the page.
+    #[default]
+    Conflict,
+    /// Handle the request anyway, e.g. because the client's assets are
+    /// still being served.
+    Bypass,
+}
+
+/// Which requests with a mismatching asset version get a `409
+/// Conflict` response. See [InertiaConfig::conflict_policy].
+#[derive(Clone, Default)]
+pub enum ConflictPolicy {
+    /// Only `GET` requests, as in the Inertia protocol.
+    #[default]
+    GetOnly,
+    /// All requests, so that e.g. form submissions from outdated
+    /// bundles are not executed.
+    AllMethods,
+    /// `GET` requests. Other requests are handled, but logged with a
+    /// warning.
+    LogOthers,
+    /// Requests for which the function returns true.
+    Custom(Arc<ConflictPredicate>),
+}
+
+impl ConflictPolicy {
+    /// Returns true if a version mismatch of `request` is a conflict.
+    pub(crate) fn conflicts(&self, request: &Request) -> bool {
+        let is_get = request.method() == http::Method::GET;
+        match self {
+            ConflictPolicy::GetOnly => is_get,
+            ConflictPolicy::AllMethods => true,
+            ConflictPolicy::LogOthers => {
+                if !is_get {
+                    tracing::warn!(
+                        method = %request.method(),
+                        url = request.url(),
+                        version = request.version(),
+                        "handling inertia request with a mismatching asset version"
+                    );
+                }
+                is_get
+            }
+            ConflictPolicy::Custom(predicate) => predicate(request),
+        }
+    }
+}
+
+/// A case convention for prop keys. See
+/// [InertiaConfig::props_case].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Case {
+    /// `camelCase`, e.g. `current_user` becomes `currentUser`.
+    Camel,
+}
+
+impl Case {
+    /// Converts the keys of all objects in `value`, recursively.
+    pub(crate) fn convert_keys(self, value: Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| (self.convert(&key), self.convert_keys(value)))
+                    .collect(),
+            ),
+            Value::Array(values) => {
+                Value::Array(values.into_iter().map(|v| self.convert_keys(v)).collect())
+            }
+            value => value,
+        }
+    }
+
+    fn convert(self, key: &str) -> String {
+        match self {
+            Case::Camel => {
+                let mut out = String::with_capacity(key.len());
+                let mut upper = false;
+                for c in key.chars() {
+                    if c == '_' && !out.is_empty() {
+                        upper = true;
+                    } else if upper {
+                        out.extend(c.to_uppercase());
+                        upper = false;
+                    } else {
+                        out.push(c);
+                    }
+                }
+                out
+            }
+        }
+    }
+}
+
+/// What to do with Inertia request headers whose values aren't
+/// visible ascii, e.g. when mangled by a proxy. See
+/// [InertiaConfig::invalid_headers].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InvalidHeaders {
+    /// Reject the request with `400 Bad Request`. See
+    /// [InertiaRejection::BadHeader](crate::rejection::InertiaRejection::BadHeader).
+    #[default]
+    Reject,
+    /// Log a warning and treat the header as missing.
+    Strip,
+}
+
+/// A page object extra field whose fields are added to the props
+/// envelope. See [InertiaConfig::props_envelope].
+pub const ENVELOPE_META: &str = "$envelope";
+
+/// How failures of individual props are handled when resolving a
+/// [props::Map](crate::props::Map). See
+/// [InertiaConfig::prop_errors].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PropErrors {
+    /// Fail the whole render.
+    #[default]
+    Strict,
+    /// Log the error with `tracing` and leave the prop out.
+    Lenient,
+}
+
+/// What to do with partial reloads whose
+/// `X-Inertia-Partial-Component` isn't the rendered component, e.g.
+/// when the handler renders another component than the page the
+/// client reloads. See [InertiaConfig::partial_mismatch].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PartialMismatch {
+    /// Respond with all props, like a full visit. If props were
+    /// already resolved for the partial reload, with
+    /// [resolve_map](crate::Inertia::resolve_map) or
+    /// [resolve_timed](crate::Inertia::resolve_timed), respond like
+    /// [Conflict](PartialMismatch::Conflict) instead, since some would
+    /// be missing.
+    #[default]
+    FullProps,
+    /// Respond with `409 Conflict` and the request url in
+    /// `X-Inertia-Location`, so the client makes a fresh visit.
+    Conflict,
+}
+
+/// The order of keys in serialized props. See
+/// [InertiaConfig::prop_order].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PropOrder {
+    /// The order of `serde_json` maps: sorted, or insertion order if
+    /// its `preserve_order` feature is enabled, e.g. with this crate's
+    /// `preserve-order` feature or by another dependency.
+    #[default]
+    Serde,
+    /// Keys sorted at every level, whichever `serde_json` features are
+    /// enabled.
+    Sorted,
+}
+
+impl PropOrder {
+    /// Reorders the keys of `value`.
+    pub(crate) fn apply(self, value: &mut Value) {
+        if self == PropOrder::Sorted {
+            sort_keys(value);
+        }
+    }
+}
+
+fn sort_keys(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = std::mem::take(map).into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (key, mut value) in entries {
+                sort_keys(&mut value);
+                map.insert(key, value);
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(sort_keys),
+        _ => {}
+    }
+}
 
+#[derive(Clone)]
 struct Inner {
     version: Option<String>,
-    layout: LayoutResolver,
+    version_watch: Option<watch::Receiver<String>>,
+    layout: Arc<LayoutResolver>,
+    layout_fallback: Option<Arc<LayoutResolver>>,
+    component_layout: Option<Arc<ComponentLayoutResolver>>,
+    response_hooks: Vec<Arc<ResponseHook>>,
+    partial_reload_hooks: Vec<Arc<PartialReloadHook>>,
+    version_conflict_hooks: Vec<Arc<VersionConflictHook>>,
+    conflict_full_url: bool,
+    conflict_body: ConflictBody,
+    conflict_policy: ConflictPolicy,
+    absolute_urls: bool,
+    trust_proxy: bool,
+    origin: Option<String>,
+    query_partials: bool,
+    share_params: bool,
+    bfcache_headers: bool,
+    shell_fingerprint: bool,
+    default_props: Map<String, Value>,
+    component_defaults: HashMap<String, Map<String, Value>>,
+    prop_providers: Vec<Arc<dyn PropProvider>>,
+    layout_props: Option<Arc<LayoutPropsProvider>>,
+    layout_props_key: Option<&'static str>,
+    props_case: Option<Case>,
+    protocol_version: ProtocolVersion,
+    header_names: HeaderNames,
+    invalid_headers: InvalidHeaders,
+    serializer: Arc<dyn PageSerializer>,
+    pretty_json: bool,
+    prop_order: PropOrder,
+    partial_mismatch: PartialMismatch,
+    prop_errors: PropErrors,
+    props_key: &'static str,
+    props_envelope: Option<&'static str>,
+    html_sanitizer: Option<Arc<HtmlSanitizer>>,
+    strict: Option<StrictMode>,
+    blocking_serialization: Option<usize>,
+    shell_cache: Option<ShellCache>,
+    #[cfg(feature = "axum-login")]
+    auth_user: Option<Arc<AuthUserResolver>>,
+    #[cfg(feature = "debug")]
+    page_log: Option<PageLog>,
+    #[cfg(feature = "debug")]
+    props_diff: Option<PropsDiff>,
+    #[cfg(feature = "schema")]
+    prop_schemas: Option<PropSchemas>,
 }
 
 #[derive(Clone)]
@@ -19,7 +296,54 @@ impl InertiaConfig {
     /// page load. See the [crate::vite] module for an implementation
     /// of this for vite.
     pub fn new(version: Option<String>, layout: LayoutResolver) -> InertiaConfig {
-        let inner = Inner { version, layout };
+        let inner = Inner {
+            version,
+            version_watch: None,
+            layout: Arc::new(layout),
+            layout_fallback: None,
+            component_layout: None,
+            response_hooks: Vec::new(),
+            partial_reload_hooks: Vec::new(),
+            version_conflict_hooks: Vec::new(),
+            conflict_full_url: false,
+            conflict_body: ConflictBody::default(),
+            conflict_policy: ConflictPolicy::default(),
+            absolute_urls: false,
+            trust_proxy: false,
+            origin: None,
+            query_partials: false,
+            share_params: false,
+            bfcache_headers: false,
+            shell_fingerprint: false,
+            default_props: Map::new(),
+            component_defaults: HashMap::new(),
+            prop_providers: Vec::new(),
+            layout_props: None,
+            layout_props_key: None,
+            props_case: None,
+            protocol_version: ProtocolVersion::default(),
+            header_names: HeaderNames::default(),
+            invalid_headers: InvalidHeaders::default(),
+            serializer: Arc::new(SerdeJson),
+            pretty_json: false,
+            prop_order: PropOrder::default(),
+            partial_mismatch: PartialMismatch::default(),
+            prop_errors: PropErrors::default(),
+            props_key: "props",
+            props_envelope: None,
+            html_sanitizer: None,
+            strict: None,
+            blocking_serialization: None,
+            shell_cache: None,
+            #[cfg(feature = "axum-login")]
+            auth_user: None,
+            #[cfg(feature = "debug")]
+            page_log: None,
+            #[cfg(feature = "debug")]
+            props_diff: None,
+            #[cfg(feature = "schema")]
+            prop_schemas: None,
+        };
         InertiaConfig {
             inner: Arc::new(inner),
         }
@@ -27,11 +351,988 @@ impl InertiaConfig {
 
     /// Returns a cloned optional version string.
     pub fn version(&self) -> Option<String> {
-        self.inner.version.clone()
+        match &self.inner.version_watch {
+            Some(version) => Some(version.borrow().clone()),
+            None => self.inner.version.clone(),
+        }
+    }
+
+    /// Reads the asset version from a watch channel, so it can be
+    /// changed while the app runs, e.g. by deploy tooling after new
+    /// assets are synced. Takes precedence over a fixed version.
+    ///
+    /// ```rust
+    /// use axum_inertia::vite;
+    /// use tokio::sync::watch;
+    ///
+    /// let (tx, rx) = watch::channel("v1".to_string());
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .version_watch(rx);
+    ///
+    /// // later, after a deploy:
+    /// tx.send("v2".to_string()).unwrap();
+    /// assert_eq!(inertia.version().as_deref(), Some("v2"));
+    /// ```
+    pub fn version_watch(mut self, version: watch::Receiver<String>) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).version_watch = Some(version);
+        self
+    }
+
+    /// Sets the asset version, e.g. one derived from build metadata.
+    /// See the [crate::version] module.
+    pub fn with_version(mut self, version: impl Into<String>) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).version = Some(version.into());
+        self
     }
 
     /// Returns a reference to the layout function.
     pub fn layout(&self) -> &LayoutResolver {
         &self.inner.layout
     }
+
+    /// Sets the html rendered, with a `500 Internal Server Error`
+    /// status, when the layout function panics. It receives the
+    /// serialized page object, like the layout.
+    ///
+    /// Without a fallback, the response body is empty.
+    ///
+    /// ```rust
+    /// use axum_inertia::vite;
+    ///
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .layout_fallback(Box::new(|_page| {
+    ///         "<!DOCTYPE html><p>Something went wrong.</p>".to_string()
+    ///     }));
+    /// ```
+    pub fn layout_fallback(mut self, fallback: LayoutResolver) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).layout_fallback = Some(Arc::new(fallback));
+        self
+    }
+
+    /// Returns the layout fallback function, if any.
+    pub(crate) fn layout_fallback_fn(&self) -> Option<&LayoutResolver> {
+        self.inner.layout_fallback.as_deref()
+    }
+
+    /// Replaces the layout with one that also receives the rendered
+    /// component, e.g. to load only the assets of that page. See
+    /// [vite::Production::page_entry](crate::vite::Production::page_entry).
+    ///
+    /// ```rust
+    /// use axum_inertia::InertiaConfig;
+    ///
+    /// let inertia = InertiaConfig::new(None, Box::new(|page| page)).component_layout(
+    ///     |component, page| format!(r#"<body class="{}">{}</body>"#, component, page),
+    /// );
+    /// ```
+    pub fn component_layout<F>(mut self, layout: F) -> InertiaConfig
+    where
+        F: Fn(&str, String) -> String + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.inner).component_layout = Some(Arc::new(layout));
+        self
+    }
+
+    /// Renders the layout of `component` with the serialized page
+    /// object.
+    pub(crate) fn render_layout(&self, component: &str, page: String) -> String {
+        match &self.inner.component_layout {
+            Some(layout) => layout(component, page),
+            None => (self.inner.layout)(page),
+        }
+    }
+
+    /// Adds a default prop, included in every rendered page.
+    ///
+    /// Default props are merged beneath the props passed to
+    /// [render](crate::Inertia::render), so a handler prop with the
+    /// same key takes precedence. During partial reloads, they are
+    /// only included if requested.
+    ///
+    /// ```rust
+    /// use axum_inertia::vite;
+    ///
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .share("appName", "My app")
+    ///     .share("environment", "development");
+    /// ```
+    pub fn share(mut self, key: &str, value: impl Into<Value>) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner)
+            .default_props
+            .insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Adds conditional default props, included in every rendered page
+    /// whose request header matches. See [When].
+    ///
+    /// Like [share](InertiaConfig::share)d props, handler props take
+    /// precedence, and during partial reloads only requested props are
+    /// included. Responses vary on the header.
+    ///
+    /// ```rust
+    /// use axum_inertia::props::{contains, When};
+    /// use axum_inertia::vite;
+    /// use serde_json::json;
+    ///
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .share_when(When::header(
+    ///         "X-Feature-Flags",
+    ///         contains("beta"),
+    ///         json!({ "betaBanner": true }),
+    ///     ));
+    /// ```
+    pub fn share_when(self, when: When) -> InertiaConfig {
+        self.on_response(move |request, page, headers| {
+            headers.append(VARY, HeaderValue::from(when.header_name().clone()));
+            let shared = match when.resolve(request) {
+                Ok(Value::Object(shared)) => shared,
+                Ok(_) => return,
+                Err(err) => {
+                    tracing::error!(%err, "failed to serialize conditional props");
+                    return;
+                }
+            };
+            if let Value::Object(props) = &mut page.props {
+                for (key, value) in shared {
+                    let requested = request
+                        .partial()
+                        .is_none_or(|partial| partial.props.contains(&key));
+                    if requested && !props.contains_key(&key) {
+                        props.insert(key, value);
+                    }
+                }
+            }
+        })
+    }
+
+    /// Returns the default props.
+    pub fn default_props(&self) -> &Map<String, Value> {
+        &self.inner.default_props
+    }
+
+    /// Adds default props for pages rendering `component`, e.g. for
+    /// configuration every handler of the component would otherwise
+    /// pass.
+    ///
+    /// The keys of `props` are merged beneath the handler's props, like
+    /// [share](InertiaConfig::share)d props, and take precedence over
+    /// them. `component` is the full name, with any
+    /// [ComponentPrefix](crate::prefix::ComponentPrefix) applied.
+    /// Values that aren't json objects are ignored.
+    ///
+    /// ```rust
+    /// use axum_inertia::vite;
+    /// use serde_json::json;
+    ///
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .defaults("Pages/Dashboard", json!({ "refreshInterval": 30 }));
+    /// ```
+    pub fn defaults(mut self, component: &str, props: impl Into<Value>) -> InertiaConfig {
+        let Value::Object(props) = props.into() else {
+            tracing::warn!(
+                component,
+                "ignoring component defaults that aren't a json object"
+            );
+            return self;
+        };
+        Arc::make_mut(&mut self.inner)
+            .component_defaults
+            .entry(component.to_string())
+            .or_default()
+            .extend(props);
+        self
+    }
+
+    /// Returns the default props of `component`, if any.
+    pub(crate) fn component_defaults(&self, component: &str) -> Option<&Map<String, Value>> {
+        self.inner.component_defaults.get(component)
+    }
+
+    /// Registers a provider of a shared prop, resolved for each
+    /// request when the [Inertia](crate::Inertia) extractor runs. See
+    /// the [crate::provider] module.
+    pub fn prop_provider(mut self, provider: impl PropProvider + 'static) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner)
+            .prop_providers
+            .push(Arc::new(provider));
+        self
+    }
+
+    /// Registers a tower service as a [prop
+    /// provider](InertiaConfig::prop_provider). The service is called
+    /// with the Inertia request, and responds with the key and value
+    /// of the prop.
+    pub fn prop_service<S>(self, service: S) -> InertiaConfig
+    where
+        S: Service<Request, Response = (String, Value)> + Clone + Send + Sync + 'static,
+        S::Future: Send,
+        S::Error: Into<Box<dyn Error + Send + Sync>>,
+    {
+        self.prop_provider(ServiceProvider::new(service))
+    }
+
+    pub(crate) fn prop_providers(&self) -> &[Arc<dyn PropProvider>] {
+        &self.inner.prop_providers
+    }
+
+    /// Registers a provider of layout props, for persistent layouts
+    /// on the client (e.g. navigation or the current user).
+    ///
+    /// The provider is called on every response, and its value is
+    /// added to the page object as a `layoutProps` field, separate
+    /// from the page props:
+    ///
+    /// ```rust
+    /// use axum_inertia::vite;
+    /// use serde_json::json;
+    ///
+    /// // {"component": ..., "props": {...}, "layoutProps": {"nav": [...]}}
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .layout_props(|request| {
+    ///         json!({ "nav": ["/", "/posts"], "current": request.url() })
+    ///     });
+    /// ```
+    ///
+    /// Use [nest_layout_props](InertiaConfig::nest_layout_props) to
+    /// send them as a prop instead.
+    pub fn layout_props<F>(mut self, provider: F) -> InertiaConfig
+    where
+        F: Fn(&Request) -> Value + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.inner).layout_props = Some(Arc::new(provider));
+        self
+    }
+
+    /// Sends layout props as the prop `key` instead of the `layoutProps`
+    /// page object field, for clients that only read props. Like
+    /// shared props, they are then only included in partial reloads
+    /// that request `key`, and a handler prop named `key` takes
+    /// precedence.
+    pub fn nest_layout_props(mut self, key: &'static str) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).layout_props_key = Some(key);
+        self
+    }
+
+    /// Adds the layout props for `request`, if any, to `page`.
+    pub(crate) fn add_layout_props(&self, request: &Request, page: &mut Page) {
+        let Some(provider) = &self.inner.layout_props else {
+            return;
+        };
+        match self.inner.layout_props_key {
+            None => {
+                page.extra
+                    .insert("layoutProps".to_string(), provider(request));
+            }
+            Some(key) => {
+                let requested = request
+                    .partial()
+                    .is_none_or(|partial| partial.props.iter().any(|p| p == key));
+                if let Value::Object(props) = &mut page.props {
+                    if requested && !props.contains_key(key) {
+                        props.insert(key.to_string(), provider(request));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Converts the keys of all props, including nested objects and
+    /// shared props, to the given case, e.g. for Rust structs without
+    /// `#[serde(rename_all = "camelCase")]`.
+    ///
+    /// Keys requested in partial reloads are passed to [Props] as sent
+    /// by the client, i.e. in the converted case.
+    ///
+    /// ```rust
+    /// use axum_inertia::{config::Case, vite};
+    ///
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .props_case(Case::Camel);
+    /// ```
+    ///
+    /// [Props]: crate::props::Props
+    pub fn props_case(mut self, case: Case) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).props_case = Some(case);
+        self
+    }
+
+    /// Returns the configured case for prop keys.
+    pub(crate) fn props_case_kind(&self) -> Option<Case> {
+        self.inner.props_case
+    }
+
+    /// Sets the page object version sent to clients that don't announce
+    /// one with the `X-Inertia-Protocol` header. Defaults to
+    /// [ProtocolVersion::V2].
+    ///
+    /// Setting this to [ProtocolVersion::V1] keeps older clients
+    /// working while the frontend is upgraded.
+    pub fn protocol_version(mut self, version: ProtocolVersion) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).protocol_version = version;
+        self
+    }
+
+    /// Returns the page object version for clients that don't announce
+    /// one.
+    pub(crate) fn default_protocol_version(&self) -> ProtocolVersion {
+        self.inner.protocol_version
+    }
+
+    /// Sets the names of the Inertia headers read from requests and
+    /// written to responses, e.g. for proxies that strip `X-Inertia-*`
+    /// headers. Defaults to the standard names.
+    ///
+    /// ```rust
+    /// use axum_inertia::{protocol::HeaderNames, vite};
+    /// use http::HeaderName;
+    ///
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .header_names(HeaderNames::default().version(HeaderName::from_static("x-app-version")));
+    /// ```
+    pub fn header_names(mut self, names: HeaderNames) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).header_names = names;
+        self
+    }
+
+    /// Returns the names of the Inertia headers.
+    pub(crate) fn header_names_ref(&self) -> &HeaderNames {
+        &self.inner.header_names
+    }
+
+    /// Sets how failures of individual props are handled by
+    /// [Inertia::resolve_map](crate::Inertia::resolve_map). Defaults
+    /// to [PropErrors::Strict].
+    pub fn prop_errors(mut self, mode: PropErrors) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).prop_errors = mode;
+        self
+    }
+
+    /// Returns how failures of individual props are handled.
+    pub(crate) fn prop_errors_mode(&self) -> PropErrors {
+        self.inner.prop_errors
+    }
+
+    /// Sets the page object key holding the props, for custom client
+    /// adapters. Defaults to `props`.
+    ///
+    /// ```rust
+    /// use axum_inertia::vite;
+    ///
+    /// // {"component": ..., "pageProps": {...}, "url": ...}
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .props_key("pageProps");
+    /// ```
+    pub fn props_key(mut self, key: &'static str) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).props_key = key;
+        self
+    }
+
+    /// Wraps the props in an envelope object, under `key`. Applies to
+    /// both Inertia responses and the initial html page.
+    ///
+    /// Response hooks run before the props are wrapped, and may add
+    /// other envelope fields with the [ENVELOPE_META] key:
+    ///
+    /// ```rust
+    /// use axum_inertia::{config::ENVELOPE_META, vite};
+    /// use serde_json::json;
+    ///
+    /// // "props": {"data": {...}, "meta": {"requestId": "abc"}}
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .props_envelope("data")
+    ///     .on_response(|_request, page, _headers| {
+    ///         page.extra.insert(ENVELOPE_META.to_string(), json!({ "meta": { "requestId": "abc" } }));
+    ///     });
+    /// ```
+    pub fn props_envelope(mut self, key: &'static str) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).props_envelope = Some(key);
+        self
+    }
+
+    /// Moves the props of `page` under the configured envelope and
+    /// key.
+    pub(crate) fn wrap_props(&self, page: &mut Page) {
+        let meta = page.extra.remove(ENVELOPE_META);
+        if let Some(key) = self.inner.props_envelope {
+            let mut envelope = match meta {
+                Some(Value::Object(fields)) => fields,
+                _ => Map::new(),
+            };
+            envelope.insert(key.to_string(), page.props.take());
+            page.props = Value::Object(envelope);
+        }
+        if self.inner.props_key != "props" {
+            let props = page.props.take();
+            page.extra.insert(self.inner.props_key.to_string(), props);
+        }
+    }
+
+    /// Sets the sanitizer for [Html](crate::props::Html) props, e.g.
+    /// [ammonia](https://docs.rs/ammonia). It runs on every `Html`
+    /// value in the page's props, including shared and hook-added
+    /// props, just before the page is serialized, and on the props of
+    /// [negotiated](crate::Inertia::negotiate) json responses and
+    /// [streamed](crate::defer) deferred groups.
+    ///
+    /// ```rust,ignore
+    /// use axum_inertia::vite;
+    ///
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .html_sanitizer(|html| ammonia::clean(html));
+    /// ```
+    pub fn html_sanitizer<F>(mut self, sanitizer: F) -> InertiaConfig
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.inner).html_sanitizer = Some(Arc::new(sanitizer));
+        self
+    }
+
+    /// Replaces the [Html](crate::props::Html) props in `props` with
+    /// their sanitized strings, or their plain strings without a
+    /// sanitizer. Every path that sends props to a client runs it.
+    pub(crate) fn sanitize_html(&self, props: &mut Value) {
+        match &self.inner.html_sanitizer {
+            Some(sanitizer) => html::sanitize(props, &**sanitizer),
+            None => html::sanitize(props, &str::to_string),
+        }
+    }
+
+    /// Sets the json serializer for page objects. Defaults to
+    /// [SerdeJson]. See the [crate::serializer] module.
+    pub fn serializer(mut self, serializer: impl PageSerializer + 'static) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).serializer = Arc::new(serializer);
+        self
+    }
+
+    /// Pretty-prints page objects in debug builds, in the initial
+    /// html page and in Inertia responses, so props are readable in
+    /// the browser's devtools. Page objects are always compact in
+    /// release builds.
+    ///
+    /// In debug builds, this takes precedence over a custom
+    /// [serializer](InertiaConfig::serializer).
+    ///
+    /// ```rust
+    /// use axum_inertia::vite;
+    ///
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .pretty_json(true);
+    /// ```
+    pub fn pretty_json(mut self, pretty_json: bool) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).pretty_json = pretty_json;
+        self
+    }
+
+    /// Sets the order of keys in serialized props, e.g. sorted for
+    /// deterministic payloads in snapshot tests or html diff caching.
+    /// See [PropOrder].
+    ///
+    /// ```rust
+    /// use axum_inertia::{config::PropOrder, vite};
+    ///
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .prop_order(PropOrder::Sorted);
+    /// ```
+    pub fn prop_order(mut self, order: PropOrder) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).prop_order = order;
+        self
+    }
+
+    pub(crate) fn prop_order_kind(&self) -> PropOrder {
+        self.inner.prop_order
+    }
+
+    /// Returns true if page objects are pretty-printed.
+    pub(crate) fn uses_pretty_json(&self) -> bool {
+        cfg!(debug_assertions) && self.inner.pretty_json
+    }
+
+    /// Returns the page object serializer.
+    pub(crate) fn page_serializer(&self) -> &dyn PageSerializer {
+        if self.uses_pretty_json() {
+            &SerdeJsonPretty
+        } else {
+            &*self.inner.serializer
+        }
+    }
+
+    /// Registers a hook that is run on every Inertia response before
+    /// it is finalized.
+    ///
+    /// Hooks receive the Inertia request, the page object and the
+    /// response headers, and may modify the latter two. They run in
+    /// the order they were registered.
+    ///
+    /// ```rust
+    /// use axum_inertia::vite;
+    ///
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .on_response(|_request, page, headers| {
+    ///         page.props["experiment"] = "b".into();
+    ///         headers.insert("X-Experiment", "b".parse().unwrap());
+    ///     });
+    /// ```
+    pub fn on_response<F>(mut self, hook: F) -> InertiaConfig
+    where
+        F: Fn(&Request, &mut Page, &mut HeaderMap) + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.inner)
+            .response_hooks
+            .push(Arc::new(hook));
+        self
+    }
+
+    /// Shares the request's [ClientHints](crate::hints::ClientHints) as
+    /// the `key` prop, and asks browsers to send them with an
+    /// `Accept-CH` header. See the [crate::hints] module.
+    ///
+    /// On partial reloads, the prop is only included when requested.
+    pub fn client_hints(self, key: &'static str) -> InertiaConfig {
+        self.on_response(move |request, page, headers| {
+            headers.insert(ACCEPT_CH, ACCEPT_CH_VALUE);
+            headers.append(VARY, ACCEPT_CH_VALUE);
+            if request
+                .partial()
+                .is_some_and(|partial| !partial.props.iter().any(|p| p == key))
+            {
+                return;
+            }
+            if let Value::Object(props) = &mut page.props {
+                let hints = ClientHints::from_headers(request.headers());
+                props.insert(key.to_string(), serde_json::to_value(hints).unwrap());
+            }
+        })
+    }
+
+    /// Returns the registered response hooks.
+    pub(crate) fn response_hooks(&self) -> impl Iterator<Item = &Arc<ResponseHook>> {
+        self.inner.response_hooks.iter()
+    }
+
+    /// Sets what to do with partial reloads of a component other than
+    /// the rendered one. Either way, a `tracing` event is logged. See
+    /// [PartialMismatch].
+    ///
+    /// ```rust
+    /// use axum_inertia::{config::PartialMismatch, vite};
+    ///
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .partial_mismatch(PartialMismatch::Conflict);
+    /// ```
+    pub fn partial_mismatch(mut self, mismatch: PartialMismatch) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).partial_mismatch = mismatch;
+        self
+    }
+
+    pub(crate) fn partial_mismatch_kind(&self) -> PartialMismatch {
+        self.inner.partial_mismatch
+    }
+
+    /// Registers a hook that is run when a partial reload is rendered,
+    /// e.g. for recording metrics.
+    ///
+    /// Hooks receive the rendered component and the partial reload
+    /// data. Counting which props are requested per component can
+    /// show which props are worth making lazy:
+    ///
+    /// ```rust
+    /// use axum_inertia::vite;
+    /// use std::collections::HashMap;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let usage: Arc<Mutex<HashMap<(String, String), u64>>> = Default::default();
+    /// let recorder = usage.clone();
+    ///
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .on_partial_reload(move |component, partial| {
+    ///         let mut usage = recorder.lock().unwrap();
+    ///         for prop in &partial.props {
+    ///             *usage.entry((component.to_string(), prop.clone())).or_default() += 1;
+    ///         }
+    ///     });
+    /// ```
+    pub fn on_partial_reload<F>(mut self, hook: F) -> InertiaConfig
+    where
+        F: Fn(&str, &Partial) + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.inner)
+            .partial_reload_hooks
+            .push(Arc::new(hook));
+        self
+    }
+
+    /// Returns the registered partial reload hooks.
+    pub(crate) fn partial_reload_hooks(&self) -> impl Iterator<Item = &Arc<PartialReloadHook>> {
+        self.inner.partial_reload_hooks.iter()
+    }
+
+    /// Registers a hook that is run on every asset version mismatch,
+    /// before the `409 Conflict` response is sent.
+    ///
+    /// Hooks receive the Inertia request, whose
+    /// [version](Request::version) is the client's asset version. If
+    /// any hook returns [ConflictAction::Bypass], the request is
+    /// handled normally instead. During blue/green deployments, this
+    /// lets clients of the previous release keep working while its
+    /// assets are still served:
+    ///
+    /// ```rust
+    /// use axum_inertia::config::ConflictAction;
+    /// use axum_inertia::vite;
+    ///
+    /// let previous = std::env::var("PREVIOUS_ASSET_VERSION").ok();
+    ///
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .on_version_conflict(move |request| {
+    ///         if request.version().is_some() && request.version() == previous.as_deref() {
+    ///             ConflictAction::Bypass
+    ///         } else {
+    ///             ConflictAction::Conflict
+    ///         }
+    ///     });
+    /// ```
+    pub fn on_version_conflict<F>(mut self, hook: F) -> InertiaConfig
+    where
+        F: Fn(&Request) -> ConflictAction + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.inner)
+            .version_conflict_hooks
+            .push(Arc::new(hook));
+        self
+    }
+
+    /// Runs the version conflict hooks for `request`. All hooks run,
+    /// and the conflict is bypassed if any of them bypasses it.
+    pub(crate) fn resolve_version_conflict(&self, request: &Request) -> ConflictAction {
+        self.inner
+            .version_conflict_hooks
+            .iter()
+            .fold(ConflictAction::Conflict, |action, hook| {
+                match hook(request) {
+                    ConflictAction::Bypass => ConflictAction::Bypass,
+                    ConflictAction::Conflict => action,
+                }
+            })
+    }
+
+    /// Uses the full url, including scheme and host, for the
+    /// `X-Inertia-Location` header of version conflict responses.
+    /// Defaults to false, i.e. only the path is sent.
+    ///
+    /// The scheme and host come from the
+    /// [origin](InertiaConfig::origin), or from the request's headers
+    /// with [trust_proxy](InertiaConfig::trust_proxy). Otherwise the
+    /// path is sent, since a forged `Host` header would redirect the
+    /// client elsewhere.
+    pub fn conflict_full_url(mut self, full_url: bool) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).conflict_full_url = full_url;
+        self
+    }
+
+    /// Returns true if conflict responses use the full url.
+    pub(crate) fn uses_conflict_full_url(&self) -> bool {
+        self.inner.conflict_full_url
+    }
+
+    /// Sets the body of version conflict responses. Defaults to
+    /// [ConflictBody::Empty].
+    pub fn conflict_body(mut self, body: ConflictBody) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).conflict_body = body;
+        self
+    }
+
+    /// Returns the configured conflict response body.
+    pub(crate) fn conflict_body_kind(&self) -> ConflictBody {
+        self.inner.conflict_body
+    }
+
+    /// Sets which requests with a mismatching asset version get a `409
+    /// Conflict` response. Defaults to [ConflictPolicy::GetOnly].
+    ///
+    /// ```rust
+    /// use axum_inertia::config::ConflictPolicy;
+    /// use axum_inertia::vite;
+    /// use std::sync::Arc;
+    ///
+    /// // Reject stale form submissions, except for logging out.
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .conflict_policy(ConflictPolicy::Custom(Arc::new(|request| {
+    ///         request.url() != "/logout"
+    ///     })));
+    /// ```
+    pub fn conflict_policy(mut self, policy: ConflictPolicy) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).conflict_policy = policy;
+        self
+    }
+
+    /// Returns the configured conflict policy.
+    pub(crate) fn conflict_policy_kind(&self) -> &ConflictPolicy {
+        &self.inner.conflict_policy
+    }
+
+    /// Uses absolute urls, including scheme and host, for the page
+    /// object's `url`. Defaults to false, i.e. only the path is sent.
+    ///
+    /// This is useful for apps using subdomain routing, e.g. for
+    /// multi-tenancy. The scheme and host are taken from the
+    /// `X-Forwarded-Proto` and `Host` headers, so urls stay relative
+    /// unless [trust_proxy](InertiaConfig::trust_proxy) is set, or
+    /// come from the [origin](InertiaConfig::origin).
+    pub fn absolute_urls(mut self, absolute_urls: bool) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).absolute_urls = absolute_urls;
+        self
+    }
+
+    /// Returns true if page urls are absolute.
+    pub(crate) fn uses_absolute_urls(&self) -> bool {
+        self.inner.absolute_urls
+    }
+
+    /// Trusts the `X-Forwarded-Proto` and `Host` headers of requests
+    /// when building absolute urls. Defaults to false.
+    ///
+    /// Clients can send any value in these headers, so only set this
+    /// behind a reverse proxy that overwrites them.
+    pub fn trust_proxy(mut self, trust_proxy: bool) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).trust_proxy = trust_proxy;
+        self
+    }
+
+    /// Returns true if proxy headers are trusted.
+    pub(crate) fn trusts_proxy(&self) -> bool {
+        self.inner.trust_proxy
+    }
+
+    /// Sets the scheme and host of absolute urls, e.g.
+    /// `https://example.com`, instead of reading them from request
+    /// headers.
+    pub fn origin(mut self, origin: impl Into<String>) -> InertiaConfig {
+        let origin = origin.into().trim_end_matches('/').to_string();
+        Arc::make_mut(&mut self.inner).origin = Some(origin);
+        self
+    }
+
+    /// Returns the configured origin.
+    pub(crate) fn origin_ref(&self) -> Option<&str> {
+        self.inner.origin.as_deref()
+    }
+
+    /// Serializes page objects whose props are estimated to be at
+    /// least `threshold` bytes of json on a blocking thread, when
+    /// rendered with [Inertia::render_async](crate::Inertia::render_async).
+    /// Defaults to never.
+    ///
+    /// Serializing a large page can take milliseconds, during which
+    /// the runtime worker can't make progress on other requests.
+    /// Moving it to a blocking thread costs a thread hop, so the
+    /// threshold should be well above the size of a typical page.
+    pub fn blocking_serialization(mut self, threshold: usize) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).blocking_serialization = Some(threshold);
+        self
+    }
+
+    /// Returns the blocking serialization threshold, if any.
+    pub(crate) fn blocking_serialization_threshold(&self) -> Option<usize> {
+        self.inner.blocking_serialization
+    }
+
+    /// Caches the initial html page of the components registered with
+    /// `cache`. See the [crate::shell] module.
+    pub fn shell_cache(mut self, cache: ShellCache) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).shell_cache = Some(cache);
+        self
+    }
+
+    /// Returns the html page cache, if any.
+    pub(crate) fn shell_cache_ref(&self) -> Option<&ShellCache> {
+        self.inner.shell_cache.as_ref()
+    }
+
+    /// Sets what to do with Inertia request headers with invalid
+    /// values. Defaults to [InvalidHeaders::Reject].
+    pub fn invalid_headers(mut self, invalid: InvalidHeaders) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).invalid_headers = invalid;
+        self
+    }
+
+    /// Returns what to do with invalid Inertia request headers.
+    pub(crate) fn invalid_headers_kind(&self) -> InvalidHeaders {
+        self.inner.invalid_headers
+    }
+
+    /// Checks every rendered response against the Inertia protocol,
+    /// reporting violations as `mode` says. See the [crate::strict]
+    /// module.
+    pub fn strict(mut self, mode: StrictMode) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).strict = Some(mode);
+        self
+    }
+
+    /// Returns the strict mode, if enabled.
+    pub(crate) fn strict_mode(&self) -> Option<StrictMode> {
+        self.inner.strict
+    }
+
+    /// Treats a `partial` query parameter on non-Inertia requests as a
+    /// partial reload of the listed props, e.g. `?partial=users,filters`.
+    /// Defaults to false.
+    ///
+    /// This lets pre-rendering tools and crawlers request the initial
+    /// html with a reduced set of props. The parameter is parsed like
+    /// the `X-Inertia-Partial-Data` header, and is ignored on Inertia
+    /// requests. Unlike partial reloads, it also drops the unrequested
+    /// top-level keys of plain serializable props, e.g. `json!` values.
+    pub fn query_partials(mut self, query_partials: bool) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).query_partials = query_partials;
+        self
+    }
+
+    /// Returns true if the `partial` query parameter is used.
+    pub(crate) fn uses_query_partials(&self) -> bool {
+        self.inner.query_partials
+    }
+
+    /// Shares the matched path parameters and the query string as the
+    /// `params` prop, so pages can read routing parameters without
+    /// each handler passing them. Defaults to false.
+    ///
+    /// ```rust
+    /// use axum_inertia::vite;
+    ///
+    /// // `/users/1?tab=posts` on the route `/users/:id` gets
+    /// // `{ "params": { "id": "1", "tab": "posts" } }`
+    /// let inertia = vite::Development::default()
+    ///     .into_config()
+    ///     .share_params(true);
+    /// ```
+    ///
+    /// Values are strings. Path parameters take precedence over query
+    /// parameters of the same name, and of repeated query parameters,
+    /// the last one is used. Pages rendered by an
+    /// [InertiaLayer](crate::layer::InertiaLayer) only get the query
+    /// parameters, as the layer runs outside the router.
+    pub fn share_params(mut self, share_params: bool) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).share_params = share_params;
+        self
+    }
+
+    /// Returns true if the `params` prop is shared.
+    pub(crate) fn shares_params(&self) -> bool {
+        self.inner.share_params
+    }
+
+    /// If enabled, sends the headers recommended for the browser's
+    /// back-forward cache with every Inertia response: `Vary` on the
+    /// Inertia header, and `Cache-Control: private, no-cache`. Response
+    /// hooks can still change them. Disabled by default. See the
+    /// [crate::bfcache] module.
+    pub fn bfcache_headers(mut self, enabled: bool) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).bfcache_headers = enabled;
+        self
+    }
+
+    /// Returns true if the bfcache headers are sent.
+    pub(crate) fn sends_bfcache_headers(&self) -> bool {
+        self.inner.bfcache_headers
+    }
+
+    /// Remembers the asset version of the html shells sent to each
+    /// browser in a cookie, and sends the next shell with headers
+    /// forbidding caching when the version changed, so browsers and
+    /// proxies don't keep mounting old javascript against new props
+    /// after a deploy. Defaults to false.
+    ///
+    /// Inertia requests are covered by the protocol's version check,
+    /// and only html shells get the cookie. Without a version, nothing
+    /// is sent.
+    pub fn shell_fingerprint(mut self, shell_fingerprint: bool) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).shell_fingerprint = shell_fingerprint;
+        self
+    }
+
+    /// Returns true if html shells are fingerprinted.
+    pub(crate) fn uses_shell_fingerprint(&self) -> bool {
+        self.inner.shell_fingerprint
+    }
+
+    /// Shares the current user of axum-login backend `B` as the
+    /// `auth.user` prop, with the fields returned by `user`, or `null`
+    /// for guests. See the [crate::auth] module.
+    #[cfg(feature = "axum-login")]
+    pub fn auth_user<B, F, T>(mut self, user: F) -> InertiaConfig
+    where
+        B: axum_login::AuthnBackend + 'static,
+        F: Fn(&B::User) -> T + Send + Sync + 'static,
+        T: serde::Serialize,
+    {
+        Arc::make_mut(&mut self.inner).auth_user = Some(crate::auth::resolver::<B, F, T>(user));
+        self
+    }
+
+    /// Returns the `auth` prop resolver, if any.
+    #[cfg(feature = "axum-login")]
+    pub(crate) fn auth_user_resolver(&self) -> Option<&AuthUserResolver> {
+        self.inner.auth_user.as_deref()
+    }
+
+    /// Records every rendered page object in `page_log`. See the
+    /// [crate::debug] module.
+    #[cfg(feature = "debug")]
+    pub fn with_page_log(mut self, page_log: PageLog) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).page_log = Some(page_log);
+        self
+    }
+
+    /// Returns a reference to the page log, if any.
+    #[cfg(feature = "debug")]
+    pub fn page_log(&self) -> Option<&PageLog> {
+        self.inner.page_log.as_ref()
+    }
+
+    /// Logs which props changed on each render, in debug builds. See
+    /// the [crate::debug] module.
+    #[cfg(feature = "debug")]
+    pub fn with_props_diff(mut self, props_diff: PropsDiff) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).props_diff = Some(props_diff);
+        self
+    }
+
+    #[cfg(feature = "debug")]
+    pub(crate) fn props_diff(&self) -> Option<&PropsDiff> {
+        self.inner.props_diff.as_ref()
+    }
+
+    /// Validates rendered props against `schemas`, in debug builds.
+    /// See the [crate::schema] module.
+    #[cfg(feature = "schema")]
+    pub fn with_prop_schemas(mut self, schemas: PropSchemas) -> InertiaConfig {
+        Arc::make_mut(&mut self.inner).prop_schemas = Some(schemas);
+        self
+    }
+
+    /// Returns a reference to the prop schemas, if any.
+    #[cfg(feature = "schema")]
+    pub(crate) fn prop_schemas(&self) -> Option<&PropSchemas> {
+        self.inner.prop_schemas.as_ref()
+    }
 }
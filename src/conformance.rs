@@ -0,0 +1,143 @@
+//! Wire-format conformance tests, run with `cargo test --features
+//! conformance`.
+//!
+//! Each json file in the `conformance` directory describes one
+//! exchange of the protocol: the handler to run (render a component,
+//! or redirect), the request, and the expected status, headers and
+//! page object. The fixtures are written for this crate from the
+//! protocol documentation, not recorded from another adapter. Headers not listed in a fixture aren't
+//! checked. The layout sends the page object as is, so initial page
+//! loads are compared like Inertia responses.
+
+use crate::{props, Inertia, InertiaConfig};
+use axum::response::IntoResponse;
+use axum::Router;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+#[derive(Deserialize)]
+struct Fixture {
+    description: String,
+    version: Option<String>,
+    handler: Handler,
+    request: FixtureRequest,
+    response: FixtureResponse,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Handler {
+    Render {
+        component: String,
+        props: Map<String, Value>,
+    },
+    Redirect {
+        location: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct FixtureRequest {
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct FixtureResponse {
+    status: u16,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    /// The page object, or `None` for an empty body.
+    page: Option<Value>,
+}
+
+async fn respond(i: Inertia, handler: Arc<Handler>) -> axum::response::Response {
+    match &*handler {
+        Handler::Render { component, props } => {
+            // Resolved as a props map, so that partial reloads only get
+            // the requested props.
+            let map = props
+                .iter()
+                .fold(props::Map::new(), |map, (key, value)| map.value(key, value));
+            let props = map.resolve(i.request()).await.unwrap();
+            i.render(component, props).into_response()
+        }
+        Handler::Redirect { location } => i.redirect(location),
+    }
+}
+
+async fn check(name: &str, fixture: Fixture) {
+    let handler = Arc::new(fixture.handler);
+    let config = InertiaConfig::new(fixture.version, Box::new(|page| page));
+    let app = Router::new()
+        .fallback(move |i: Inertia| respond(i, handler.clone()))
+        .with_state(config);
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Could not bind ephemeral socket");
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("server error");
+    });
+
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+    let method = reqwest::Method::from_bytes(fixture.request.method.as_bytes()).unwrap();
+    let mut request = client.request(method, format!("http://{}{}", &addr, fixture.request.url));
+    for (name, value) in &fixture.request.headers {
+        request = request.header(name, value);
+    }
+    let res = request.send().await.unwrap();
+
+    let context = format!("{}: {}", name, fixture.description);
+    assert_eq!(
+        res.status().as_u16(),
+        fixture.response.status,
+        "{}",
+        context
+    );
+    for (header, expected) in &fixture.response.headers {
+        let value = res
+            .headers()
+            .get(header)
+            .and_then(|value| value.to_str().ok());
+        assert_eq!(value, Some(expected.as_str()), "{} ({})", context, header);
+    }
+    let body = res.text().await.unwrap();
+    match fixture.response.page {
+        Some(expected) => {
+            let page: Value = serde_json::from_str(&body).unwrap();
+            assert_eq!(page, expected, "{}", context);
+        }
+        None => assert_eq!(body, "", "{}", context),
+    }
+}
+
+#[tokio::test]
+async fn it_conforms_to_fixtures() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("conformance");
+    let mut paths: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no fixtures in {}", dir.display());
+
+    for path in paths {
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let fixture: Fixture = serde_json::from_str(&std::fs::read_to_string(&path).unwrap())
+            .unwrap_or_else(|err| panic!("invalid fixture {}: {}", name, err));
+        check(&name, fixture).await;
+    }
+}
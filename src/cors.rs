@@ -0,0 +1,296 @@
+//! CORS for Inertia requests from another origin.
+//!
+//! When the frontend dev server runs on a different origin than axum,
+//! browsers send preflight `OPTIONS` requests before Inertia
+//! requests, since they carry custom headers. [InertiaCorsLayer]
+//! answers these preflights, allowing the Inertia headers, and adds
+//! CORS headers to responses for the allowed origins:
+//!
+//! ```rust
+//! use axum::{routing::get, Router};
+//! use axum_inertia::cors::InertiaCorsLayer;
+//!
+//! let app: Router = Router::new()
+//!     .route("/", get(|| async { "hello" }))
+//!     .layer(InertiaCorsLayer::new(["http://localhost:5173"]));
+//! ```
+//!
+//! For anything more involved, use a general-purpose CORS layer such
+//! as `tower_http::cors::CorsLayer` with the header names from
+//! [crate::protocol].
+
+use crate::protocol::HeaderNames;
+use axum::response::{IntoResponse, Response};
+use http::header::{
+    ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+    ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS, ACCESS_CONTROL_MAX_AGE,
+    ACCESS_CONTROL_REQUEST_METHOD, CONTENT_TYPE, ORIGIN, VARY,
+};
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+const ALLOW_METHODS: &str = "GET, POST, PUT, PATCH, DELETE";
+const MAX_AGE: &str = "600";
+
+/// Layer that applies [InertiaCors] to a service.
+#[derive(Clone, Debug)]
+pub struct InertiaCorsLayer {
+    origins: Arc<Vec<HeaderValue>>,
+    credentials: bool,
+    names: HeaderNames,
+}
+
+impl InertiaCorsLayer {
+    /// Allows requests from the given origins, e.g.
+    /// `http://localhost:5173`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an origin is not a valid header value.
+    pub fn new<I, O>(origins: I) -> InertiaCorsLayer
+    where
+        I: IntoIterator<Item = O>,
+        O: AsRef<str>,
+    {
+        let origins = origins
+            .into_iter()
+            .map(|origin| HeaderValue::from_str(origin.as_ref()).expect("valid origin"))
+            .collect();
+        InertiaCorsLayer {
+            origins: Arc::new(origins),
+            credentials: false,
+            names: HeaderNames::default(),
+        }
+    }
+
+    /// Allows credentials, i.e. cookies, on cross-origin requests.
+    pub fn allow_credentials(mut self, credentials: bool) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Allows and exposes the Inertia headers with the given names.
+    /// See [InertiaConfig::header_names](crate::InertiaConfig::header_names).
+    pub fn header_names(mut self, names: HeaderNames) -> Self {
+        self.names = names;
+        self
+    }
+}
+
+impl<S> Layer<S> for InertiaCorsLayer {
+    type Service = InertiaCors<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InertiaCors {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Service answering CORS preflights for Inertia requests. See the
+/// [module documentation](self) for more.
+#[derive(Clone, Debug)]
+pub struct InertiaCors<S> {
+    inner: S,
+    layer: InertiaCorsLayer,
+}
+
+impl<S> InertiaCors<S> {
+    /// Returns the request origin, if it is allowed.
+    fn allowed_origin(&self, headers: &HeaderMap) -> Option<HeaderValue> {
+        let origin = headers.get(ORIGIN)?;
+        self.layer
+            .origins
+            .iter()
+            .any(|allowed| allowed == origin)
+            .then(|| origin.clone())
+    }
+
+    fn cors_headers(&self, origin: HeaderValue) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        if self.layer.credentials {
+            headers.insert(
+                ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+        headers
+    }
+}
+
+fn allow_headers(names: &HeaderNames) -> HeaderValue {
+    header_list(&[
+        &names.inertia,
+        &names.version,
+        &names.partial_data,
+        &names.partial_component,
+        &names.protocol,
+        &CONTENT_TYPE,
+    ])
+}
+
+fn expose_headers(names: &HeaderNames) -> HeaderValue {
+    header_list(&[&names.inertia, &names.location])
+}
+
+fn header_list(names: &[&HeaderName]) -> HeaderValue {
+    let names: Vec<&str> = names.iter().map(|name| name.as_str()).collect();
+    HeaderValue::from_str(&names.join(", ")).unwrap()
+}
+
+impl<S, B> Service<http::Request<B>> for InertiaCors<S>
+where
+    S: Service<http::Request<B>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let origin = self.allowed_origin(req.headers());
+        let is_preflight = req.method() == Method::OPTIONS
+            && req.headers().contains_key(ACCESS_CONTROL_REQUEST_METHOD);
+
+        if is_preflight {
+            let mut headers = HeaderMap::new();
+            if let Some(origin) = origin {
+                headers = self.cors_headers(origin);
+                headers.insert(
+                    ACCESS_CONTROL_ALLOW_METHODS,
+                    HeaderValue::from_static(ALLOW_METHODS),
+                );
+                headers.insert(
+                    ACCESS_CONTROL_ALLOW_HEADERS,
+                    allow_headers(&self.layer.names),
+                );
+                headers.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from_static(MAX_AGE));
+            }
+            headers.insert(VARY, HeaderValue::from_static("origin"));
+            let response = (StatusCode::NO_CONTENT, headers).into_response();
+            return Box::pin(async move { Ok(response) });
+        }
+
+        let cors_headers = origin.map(|origin| self.cors_headers(origin));
+        let expose = expose_headers(&self.layer.names);
+
+        // Take the service that was driven to readiness, leaving a
+        // clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let mut response = inner.call(req).await?;
+            let headers = response.headers_mut();
+            headers.append(VARY, HeaderValue::from_static("origin"));
+            if let Some(cors_headers) = cors_headers {
+                headers.extend(cors_headers);
+                headers.insert(ACCESS_CONTROL_EXPOSE_HEADERS, expose);
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn it_answers_preflights_for_allowed_origins() {
+        let app = Router::new()
+            .route("/test", get(|| async { "ok" }))
+            .layer(InertiaCorsLayer::new(["http://localhost:5173"]).allow_credentials(true));
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+        let url = format!("http://{}/test", &addr);
+
+        let res = client
+            .request(reqwest::Method::OPTIONS, &url)
+            .header("Origin", "http://localhost:5173")
+            .header("Access-Control-Request-Method", "GET")
+            .header("Access-Control-Request-Headers", "x-inertia")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::NO_CONTENT);
+        let headers = res.headers();
+        assert_eq!(
+            headers.get("Access-Control-Allow-Origin").unwrap(),
+            "http://localhost:5173"
+        );
+        assert_eq!(
+            headers.get("Access-Control-Allow-Credentials").unwrap(),
+            "true"
+        );
+        let allowed = headers
+            .get("Access-Control-Allow-Headers")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(allowed.contains("x-inertia-partial-data"));
+
+        let res = client
+            .request(reqwest::Method::OPTIONS, &url)
+            .header("Origin", "http://evil.example")
+            .header("Access-Control-Request-Method", "GET")
+            .send()
+            .await
+            .unwrap();
+        assert!(res.headers().get("Access-Control-Allow-Origin").is_none());
+
+        let res = client
+            .get(&url)
+            .header("Origin", "http://localhost:5173")
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::OK);
+        assert_eq!(
+            res.headers().get("Access-Control-Allow-Origin").unwrap(),
+            "http://localhost:5173"
+        );
+        assert_eq!(
+            res.headers().get("Access-Control-Expose-Headers").unwrap(),
+            "x-inertia, x-inertia-location"
+        );
+    }
+
+    #[test]
+    fn test_custom_header_names() {
+        let names = HeaderNames {
+            inertia: HeaderName::from_static("x-app-inertia"),
+            location: HeaderName::from_static("x-app-location"),
+            ..HeaderNames::default()
+        };
+        assert!(allow_headers(&names)
+            .to_str()
+            .unwrap()
+            .starts_with("x-app-inertia, x-inertia-version"));
+        assert_eq!(expose_headers(&names), "x-app-inertia, x-app-location");
+    }
+}
@@ -0,0 +1,370 @@
+//! Introspection of rendered page objects, available with the
+//! `debug` feature.
+//!
+//! A [PageLog] keeps the most recently rendered page objects in
+//! memory, with sensitive prop keys redacted. Register it with
+//! [with_page_log](crate::InertiaConfig::with_page_log) and mount its
+//! [router](PageLog::router) to inspect exactly what props were sent
+//! without browser devtools:
+//!
+//! ```rust
+//! use axum::Router;
+//! use axum_inertia::{debug::PageLog, vite};
+//!
+//! let page_log = PageLog::new(10).redact("password").redact("token");
+//! let inertia = vite::Development::default()
+//!     .into_config()
+//!     .with_page_log(page_log.clone());
+//!
+//! let app: Router = Router::new()
+//!     .merge(page_log.router())
+//!     .with_state(inertia);
+//! ```
+//!
+//! The router serves the latest page object at
+//! `/_inertia/last-page`, and all recorded page objects (newest
+//! first) at `/_inertia/pages`. It should not be mounted in
+//! production.
+//!
+//! A [PropsDiff] remembers the last props of each component per
+//! session, and logs which props changed on each render, to spot props
+//! that are sent again without changing:
+//!
+//! ```rust
+//! use axum_inertia::{debug::PropsDiff, vite};
+//!
+//! let inertia = vite::Development::default()
+//!     .into_config()
+//!     .with_props_diff(PropsDiff::new().session(|request| {
+//!         request.headers().get("cookie")?.to_str().ok().map(str::to_string)
+//!     }));
+//! ```
+//!
+//! Diffs are logged with `tracing` at the `debug` level, and the most
+//! recent ones are served at `/_inertia/props-diff` by its
+//! [router](PropsDiff::router). It only records in debug builds.
+
+use crate::page::Page;
+use crate::request::Request;
+use axum::{routing::get, Json, Router};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+const REDACTED: &str = "[REDACTED]";
+
+const DIFF_MAX_ENTRIES: usize = 1000;
+const DIFF_CAPACITY: usize = 50;
+
+type SessionResolver = dyn Fn(&Request) -> Option<String> + Send + Sync;
+
+/// A bounded, in-memory log of recently rendered page objects.
+#[derive(Clone)]
+pub struct PageLog {
+    capacity: usize,
+    redacted_keys: Vec<String>,
+    pages: Arc<Mutex<VecDeque<Value>>>,
+}
+
+impl PageLog {
+    /// Constructs a new log that keeps at most `capacity` pages.
+    pub fn new(capacity: usize) -> PageLog {
+        PageLog {
+            capacity,
+            redacted_keys: Vec::new(),
+            pages: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    /// Redacts the value of any prop with the given key, at any
+    /// depth. Keys are matched case-insensitively.
+    pub fn redact(mut self, key: &str) -> PageLog {
+        self.redacted_keys.push(key.to_lowercase());
+        self
+    }
+
+    /// Returns the recorded page objects, newest first.
+    pub fn pages(&self) -> Vec<Value> {
+        self.pages.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Returns the most recently recorded page object.
+    pub fn last_page(&self) -> Option<Value> {
+        self.pages.lock().unwrap().front().cloned()
+    }
+
+    /// Returns a router serving the recorded page objects as json.
+    pub fn router<S>(&self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let last = self.clone();
+        let all = self.clone();
+        Router::new()
+            .route(
+                "/_inertia/last-page",
+                get(move || async move { Json(last.last_page()) }),
+            )
+            .route(
+                "/_inertia/pages",
+                get(move || async move { Json(all.pages()) }),
+            )
+    }
+
+    pub(crate) fn record(&self, page: &Page) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut value = serde_json::to_value(page).unwrap_or(Value::Null);
+        if let Some(props) = value.get_mut("props") {
+            self.redact_value(props);
+        }
+        let mut pages = self.pages.lock().unwrap();
+        if pages.len() == self.capacity {
+            pages.pop_back();
+        }
+        pages.push_front(value);
+    }
+
+    fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if self.redacted_keys.contains(&key.to_lowercase()) {
+                        *value = Value::String(REDACTED.to_string());
+                    } else {
+                        self.redact_value(value);
+                    }
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(|v| self.redact_value(v)),
+            _ => {}
+        }
+    }
+}
+
+/// Logs which props changed between renders of a component in a
+/// session. See the [module documentation](self).
+#[derive(Clone)]
+pub struct PropsDiff {
+    session: Arc<SessionResolver>,
+    last: Arc<Mutex<HashMap<(String, String), Value>>>,
+    diffs: Arc<Mutex<VecDeque<Diff>>>,
+}
+
+/// The prop changes of one render.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Diff {
+    pub component: String,
+    pub url: String,
+    pub changes: Vec<Change>,
+}
+
+/// A prop that was added, removed or replaced, by json pointer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "op", content = "path", rename_all = "lowercase")]
+pub enum Change {
+    Add(String),
+    Remove(String),
+    Replace(String),
+}
+
+impl Default for PropsDiff {
+    fn default() -> Self {
+        PropsDiff {
+            session: Arc::new(|_| None),
+            last: Arc::default(),
+            diffs: Arc::default(),
+        }
+    }
+}
+
+impl PropsDiff {
+    /// Constructs a diff that treats all requests as one session.
+    pub fn new() -> PropsDiff {
+        Self::default()
+    }
+
+    /// Sets how the session of a request is identified, e.g. from a
+    /// session cookie.
+    pub fn session<F>(mut self, session: F) -> PropsDiff
+    where
+        F: Fn(&Request) -> Option<String> + Send + Sync + 'static,
+    {
+        self.session = Arc::new(session);
+        self
+    }
+
+    /// Returns the most recent diffs, newest first.
+    pub fn diffs(&self) -> Vec<Diff> {
+        self.diffs.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Returns a router serving the most recent diffs as json.
+    pub fn router<S>(&self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let diff = self.clone();
+        Router::new().route(
+            "/_inertia/props-diff",
+            get(move || async move { Json(diff.diffs()) }),
+        )
+    }
+
+    pub(crate) fn record(&self, request: &Request, page: &Page) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        let session = (self.session)(request).unwrap_or_default();
+        let key = (session, page.component.to_string());
+        let mut last = self.last.lock().unwrap();
+        let Some(previous) = last.get_mut(&key) else {
+            if last.len() < DIFF_MAX_ENTRIES {
+                last.insert(key, page.props.clone());
+            }
+            return;
+        };
+
+        let mut changes = Vec::new();
+        match (request.partial(), &mut *previous, &page.props) {
+            // Partial reloads only send some props; compare those.
+            (Some(_), Value::Object(previous), Value::Object(props)) => {
+                for (key, value) in props {
+                    let path = format!("/{}", escape_pointer(key));
+                    match previous.insert(key.clone(), value.clone()) {
+                        Some(old) => diff_values(&path, &old, value, &mut changes),
+                        None => changes.push(Change::Add(path)),
+                    }
+                }
+            }
+            (_, previous, props) => {
+                diff_values("", previous, props, &mut changes);
+                *previous = props.clone();
+            }
+        }
+        drop(last);
+
+        if changes.is_empty() {
+            tracing::debug!(component = %page.component, url = page.url, "inertia props unchanged");
+        } else {
+            let summary = changes
+                .iter()
+                .map(|change| match change {
+                    Change::Add(path) => format!("+{}", path),
+                    Change::Remove(path) => format!("-{}", path),
+                    Change::Replace(path) => format!("~{}", path),
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            tracing::debug!(component = %page.component, url = page.url, changes = summary, "inertia props changed");
+        }
+
+        let mut diffs = self.diffs.lock().unwrap();
+        if diffs.len() == DIFF_CAPACITY {
+            diffs.pop_back();
+        }
+        diffs.push_front(Diff {
+            component: page.component.to_string(),
+            url: page.url.clone(),
+            changes,
+        });
+    }
+}
+
+fn diff_values(path: &str, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for key in old.keys().filter(|key| !new.contains_key(*key)) {
+                changes.push(Change::Remove(format!("{}/{}", path, escape_pointer(key))));
+            }
+            for (key, value) in new {
+                let path = format!("{}/{}", path, escape_pointer(key));
+                match old.get(key) {
+                    Some(old) => diff_values(&path, old, value, changes),
+                    None => changes.push(Change::Add(path)),
+                }
+            }
+        }
+        _ if old != new => changes.push(Change::Replace(path.to_string())),
+        _ => {}
+    }
+}
+
+/// Escapes a key as a json pointer segment.
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Map};
+
+    fn page(props: Value) -> Page<'static> {
+        Page {
+            component: "Testing".into(),
+            props,
+            url: "/test".to_string(),
+            version: None,
+            modal: None,
+            deferred_props: None,
+            extra: Map::new(),
+        }
+    }
+
+    #[test]
+    fn test_redacts_nested_keys() {
+        let log = PageLog::new(5).redact("Password");
+        log.record(&page(json!({
+            "user": { "name": "mikey", "password": "hunter2" },
+            "items": [{ "PASSWORD": "x" }]
+        })));
+
+        let last = log.last_page().unwrap();
+        assert_eq!(last["props"]["user"]["name"], "mikey");
+        assert_eq!(last["props"]["user"]["password"], REDACTED);
+        assert_eq!(last["props"]["items"][0]["PASSWORD"], REDACTED);
+    }
+
+    #[test]
+    fn test_props_diff() {
+        let diff = PropsDiff::new();
+        let request = Request::test_request();
+        diff.record(
+            &request,
+            &page(json!({ "a": 1, "b": { "c": 2 }, "d/e": 3 })),
+        );
+        assert!(diff.diffs().is_empty());
+
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        diff.record(&request, &page(json!({ "a": 1, "b": { "c": 3 }, "f": 4 })));
+        assert_eq!(
+            diff.diffs()[0].changes,
+            vec![
+                Change::Remove("/d~1e".to_string()),
+                Change::Replace("/b/c".to_string()),
+                Change::Add("/f".to_string()),
+            ]
+        );
+
+        diff.record(&request, &page(json!({ "a": 1, "b": { "c": 3 }, "f": 4 })));
+        assert!(diff.diffs()[0].changes.is_empty());
+    }
+
+    #[test]
+    fn test_keeps_most_recent_pages() {
+        let log = PageLog::new(2);
+        log.record(&page(json!({ "n": 1 })));
+        log.record(&page(json!({ "n": 2 })));
+        log.record(&page(json!({ "n": 3 })));
+
+        let pages = log.pages();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0]["props"]["n"], 3);
+        assert_eq!(pages[1]["props"]["n"], 2);
+    }
+}
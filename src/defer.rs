@@ -0,0 +1,376 @@
+//! Deferred props streamed over server-sent events.
+//!
+//! With standard deferred props, the client renders the page, then
+//! asks for each deferred group with a partial reload. Here, the
+//! server starts computing the groups while it renders the page, and
+//! the page opens a short-lived event stream that delivers each group
+//! as soon as it's ready, saving a round trip per group:
+//!
+//! ```rust
+//! use axum::{response::IntoResponse, routing::get, Extension, Router};
+//! use axum_inertia::{defer::DeferStreams, layer::InertiaLayer, vite, Inertia};
+//! use serde_json::json;
+//!
+//! async fn dashboard(i: Inertia, Extension(streams): Extension<DeferStreams>) -> impl IntoResponse {
+//!     let mut deferred = streams.begin();
+//!     deferred.group("stats", &["userCount"], async {
+//!         // ... a slow query ...
+//!         Ok(json!({ "userCount": 10 }))
+//!     });
+//!     i.render("Dashboard", json!({ "title": "Dashboard" }))
+//!         .stream_deferred(deferred)
+//! }
+//!
+//! let streams = DeferStreams::new();
+//! let app: Router = Router::new()
+//!     .route("/dashboard", get(dashboard))
+//!     .layer(Extension(streams.clone()))
+//!     .layer(InertiaLayer::new(vite::Development::default().into_config()))
+//!     .merge(streams.router());
+//! ```
+//!
+//! The page object has a `deferredStream` field with the `url` of the
+//! stream and the props of its `groups`, e.g. `{"url":
+//! "/_inertia/defer/...", "groups": {"stats": ["userCount"]}}`. The
+//! groups aren't listed in `deferredProps`, so the client doesn't also
+//! fetch them with partial reloads. The stream sends a `props` event
+//! per group, with `group` and `props` fields, an `error` event with
+//! the `group` of each failed group, and a `done` event when all
+//! groups are sent. On the client:
+//!
+//! ```js
+//! router.on("navigate", (event) => {
+//!   const stream = event.detail.page.deferredStream;
+//!   if (!stream) return;
+//!   const events = new EventSource(stream.url);
+//!   events.addEventListener("props", (event) => {
+//!     const { props } = JSON.parse(event.data);
+//!     router.replace({
+//!       props: (current) => ({ ...current, ...props }),
+//!       preserveScroll: true,
+//!       preserveState: true,
+//!     });
+//!   });
+//!   events.addEventListener("done", () => events.close());
+//! });
+//! ```
+//!
+//! Streams can be opened once, and expire if the page doesn't open
+//! them in time (30 seconds by default). Partial reloads aren't
+//! streamed: the client asks for the props it wants, and the handler
+//! should include them as usual.
+
+use crate::clock::{Rng, SystemRng};
+use crate::config::InertiaConfig;
+use crate::props::PropsError;
+use axum::extract::Path;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::{routing::get, Router};
+use futures_util::stream::{self, FuturesUnordered, StreamExt};
+use http::StatusCode;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// The path of the events endpoint, followed by the stream token.
+pub const STREAM_PATH: &str = "/_inertia/defer";
+
+const DEFAULT_TTL: Duration = Duration::from_secs(30);
+
+type GroupResult = Result<Value, PropsError>;
+
+/// The props of each deferred group, by group name.
+pub(crate) type GroupKeys = BTreeMap<String, Vec<String>>;
+
+struct Group {
+    name: String,
+    task: JoinHandle<GroupResult>,
+}
+
+/// The groups of a registered stream, with the config of the page
+/// that sanitizes their props.
+struct Pending {
+    groups: Vec<Group>,
+    config: InertiaConfig,
+}
+
+/// The registry of streams waiting for their page to open them.
+#[derive(Clone)]
+pub struct DeferStreams {
+    pending: Arc<Mutex<HashMap<String, Pending>>>,
+    ttl: Duration,
+    rng: Arc<dyn Rng>,
+}
+
+impl Default for DeferStreams {
+    fn default() -> Self {
+        DeferStreams {
+            pending: Arc::default(),
+            ttl: DEFAULT_TTL,
+            rng: Arc::new(SystemRng),
+        }
+    }
+}
+
+impl DeferStreams {
+    pub fn new() -> DeferStreams {
+        Self::default()
+    }
+
+    /// Sets how long a stream waits for its page to open it before
+    /// its groups are cancelled.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the source of stream tokens, e.g. for predictable urls in
+    /// tests. See the [crate::clock] module.
+    pub fn rng(mut self, rng: impl Rng + 'static) -> Self {
+        self.rng = Arc::new(rng);
+        self
+    }
+
+    /// Begins a stream of deferred groups for one page.
+    pub fn begin(&self) -> DeferredStream {
+        DeferredStream {
+            streams: self.clone(),
+            groups: Vec::new(),
+            keys: GroupKeys::new(),
+        }
+    }
+
+    /// Returns a router serving the streams at
+    /// `/_inertia/defer/{token}`.
+    pub fn router<S>(&self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let streams = self.clone();
+        Router::new().route(
+            &format!("{}/:token", STREAM_PATH),
+            get(move |Path(token): Path<String>| async move { streams.sse(&token) }),
+        )
+    }
+
+    /// Returns the events response for the stream `token`, or `404 Not
+    /// Found` if it doesn't exist, was already opened, or expired.
+    pub fn sse(&self, token: &str) -> Response {
+        let Some(Pending { groups, config }) = self.pending.lock().unwrap().remove(token) else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        let results: FuturesUnordered<_> = groups
+            .into_iter()
+            .map(|group| async move { (group.name, group.task.await) })
+            .collect();
+        let events = results
+            .map(move |(group, result)| match result {
+                Ok(Ok(mut props)) => {
+                    config.sanitize_html(&mut props);
+                    Event::default()
+                        .event("props")
+                        .json_data(json!({ "group": group, "props": props }))
+                }
+                // Errors are only logged, as their text may be internal.
+                Ok(Err(err)) => {
+                    tracing::error!(group, error = %err, "deferred props failed");
+                    Event::default()
+                        .event("error")
+                        .json_data(json!({ "group": group }))
+                }
+                Err(err) => {
+                    tracing::error!(group, error = %err, "deferred props task failed");
+                    Event::default()
+                        .event("error")
+                        .json_data(json!({ "group": group }))
+                }
+            })
+            .chain(stream::once(async {
+                Ok(Event::default().event("done").data(""))
+            }));
+        Sse::new(events)
+            .keep_alive(KeepAlive::default())
+            .into_response()
+    }
+
+    /// Stores `groups` under a new token, cancelling them if they
+    /// aren't opened before the ttl.
+    fn register(&self, groups: Vec<Group>, config: &InertiaConfig) -> String {
+        let token = self.rng.token();
+        let pending = Pending {
+            groups,
+            config: config.clone(),
+        };
+        self.pending.lock().unwrap().insert(token.clone(), pending);
+
+        let pending = self.pending.clone();
+        let expired = token.clone();
+        let ttl = self.ttl;
+        tokio::spawn(async move {
+            tokio::time::sleep(ttl).await;
+            let expired = pending.lock().unwrap().remove(&expired);
+            for group in expired.into_iter().flat_map(|pending| pending.groups) {
+                group.task.abort();
+            }
+        });
+        token
+    }
+}
+
+/// The deferred groups of one page. Attach it to the response with
+/// `stream_deferred`; dropping it cancels the groups.
+pub struct DeferredStream {
+    streams: DeferStreams,
+    groups: Vec<Group>,
+    keys: GroupKeys,
+}
+
+impl DeferredStream {
+    /// Starts computing the props `keys` of `group`, on a new task.
+    ///
+    /// The future resolves to a json object with the props.
+    pub fn group<F>(&mut self, group: &str, keys: &[&str], future: F) -> &mut Self
+    where
+        F: Future<Output = Result<Value, PropsError>> + Send + 'static,
+    {
+        self.groups.push(Group {
+            name: group.to_string(),
+            task: tokio::spawn(future),
+        });
+        self.keys.insert(
+            group.to_string(),
+            keys.iter().map(|key| key.to_string()).collect(),
+        );
+        self
+    }
+
+    /// Registers the stream, returning its url and the deferred keys
+    /// by group, or `None` if there are no groups. Props are sanitized
+    /// with the [html_sanitizer](InertiaConfig::html_sanitizer) of
+    /// `config`.
+    pub(crate) fn register(mut self, config: &InertiaConfig) -> Option<(String, GroupKeys)> {
+        if self.groups.is_empty() {
+            return None;
+        }
+        let groups = std::mem::take(&mut self.groups);
+        let token = self.streams.register(groups, config);
+        Some((
+            format!("{}/{}", STREAM_PATH, token),
+            std::mem::take(&mut self.keys),
+        ))
+    }
+}
+
+impl Drop for DeferredStream {
+    fn drop(&mut self) {
+        for group in &self.groups {
+            group.task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::props::Html;
+    use crate::Inertia;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn it_streams_deferred_groups() {
+        let streams = DeferStreams::new();
+        let config = InertiaConfig::new(None, Box::new(|props| props))
+            .html_sanitizer(|html| html.replace("<script></script>", ""));
+        let mut deferred = streams.begin();
+        deferred.group("stats", &["count"], async { Ok(json!({ "count": 1 })) });
+        deferred.group("notes", &["note"], async {
+            Ok(json!({ "note": Html("<p>hi<script></script></p>".to_string()) }))
+        });
+        deferred.group("secret", &["key"], async {
+            Err(PropsError::new("connection to db-internal:5432 refused"))
+        });
+        let (url, keys) = deferred.register(&config).unwrap();
+        assert_eq!(
+            keys,
+            GroupKeys::from([
+                ("stats".to_string(), vec!["count".to_string()]),
+                ("notes".to_string(), vec!["note".to_string()]),
+                ("secret".to_string(), vec!["key".to_string()]),
+            ])
+        );
+
+        let app: Router = streams.router();
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let body = reqwest::get(format!("http://{}{}", &addr, url))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(body.contains("event: props"));
+        assert!(body.contains(r#"data: {"group":"stats","props":{"count":1}}"#));
+        assert!(body.contains(r#"data: {"group":"notes","props":{"note":"<p>hi</p>"}}"#));
+        assert!(body.contains("event: error\ndata: {\"group\":\"secret\"}\n"));
+        assert!(!body.contains("db-internal"));
+        assert!(body.contains("event: done"));
+
+        let res = reqwest::get(format!("http://{}{}", &addr, url))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn it_only_advertises_streamed_groups_in_the_stream_field() {
+        let streams = DeferStreams::new();
+        let handler_streams = streams.clone();
+        let app = Router::new()
+            .route(
+                "/",
+                get(move |i: Inertia| async move {
+                    let mut deferred = handler_streams.begin();
+                    deferred.group("stats", &["count"], async { Ok(json!({ "count": 1 })) });
+                    i.render("Home", json!({})).stream_deferred(deferred)
+                }),
+            )
+            .with_state(InertiaConfig::new(None, Box::new(|props| props)));
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let res = reqwest::Client::new()
+            .get(format!("http://{}/", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        let page: Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert!(page.get("deferredProps").is_none());
+        assert_eq!(
+            page["deferredStream"]["groups"],
+            json!({ "stats": ["count"] })
+        );
+        assert!(page["deferredStream"]["url"]
+            .as_str()
+            .unwrap()
+            .starts_with(STREAM_PATH));
+    }
+}
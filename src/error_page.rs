@@ -0,0 +1,287 @@
+//! Protocol-correct error responses.
+//!
+//! An [ErrorPage] renders an Inertia error component for Inertia
+//! requests, and a static html page for initial page loads (where
+//! the client-side app may not be able to boot). It's useful for
+//! responses like `429 Too Many Requests` and `503 Service
+//! Unavailable`:
+//!
+//! ```rust
+//! use axum_inertia::{error_page::ErrorPage, Inertia};
+//! use axum::response::IntoResponse;
+//!
+//! async fn handler(i: Inertia) -> impl IntoResponse {
+//!     ErrorPage::too_many_requests()
+//!         .retry_after(30)
+//!         .render(i.request())
+//! }
+//! ```
+//!
+//! See also [MaintenanceModeLayer](crate::maintenance::MaintenanceModeLayer).
+//!
+//! When props or the page object fail to serialize, the response is a
+//! `500 Internal Server Error`. In debug builds, its body is an html
+//! page with the error, its causes, the failing prop if known, and a
+//! backtrace. The Inertia client shows such responses in a modal, so
+//! the error is visible without opening the server logs.
+
+use crate::page::Page;
+use crate::props::PropsError;
+use crate::request::Request;
+use crate::serializer::SerializeError;
+use axum::response::{Html, IntoResponse, Json, Response};
+use http::{header::RETRY_AFTER, HeaderMap, HeaderValue, StatusCode};
+use maud::{html, DOCTYPE};
+use serde_json::Map;
+use std::backtrace::Backtrace;
+
+/// An error response for Inertia and non-Inertia requests.
+#[derive(Clone, Debug)]
+pub struct ErrorPage {
+    status: StatusCode,
+    component: &'static str,
+    message: &'static str,
+    html: Option<String>,
+    retry_after: Option<u64>,
+}
+
+impl ErrorPage {
+    /// Constructs an error page for the given status, rendered with
+    /// the `Error` component.
+    pub fn new(status: StatusCode, message: &'static str) -> ErrorPage {
+        ErrorPage {
+            status,
+            component: "Error",
+            message,
+            html: None,
+            retry_after: None,
+        }
+    }
+
+    /// A `429 Too Many Requests` error page.
+    pub fn too_many_requests() -> ErrorPage {
+        Self::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many requests. Please try again later.",
+        )
+    }
+
+    /// A `503 Service Unavailable` error page.
+    pub fn service_unavailable() -> ErrorPage {
+        Self::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "This site is down for maintenance. Please try again later.",
+        )
+    }
+
+    /// Sets the component rendered for Inertia requests. It receives
+    /// `status` and `message` props.
+    pub fn component(mut self, component: &'static str) -> Self {
+        self.component = component;
+        self
+    }
+
+    /// Sets the html page rendered for initial page loads, replacing
+    /// the default page.
+    pub fn html(mut self, html: impl Into<String>) -> Self {
+        self.html = Some(html.into());
+        self
+    }
+
+    /// Sets the `Retry-After` header, in seconds.
+    pub fn retry_after(mut self, seconds: u64) -> Self {
+        self.retry_after = Some(seconds);
+        self
+    }
+
+    /// Renders the error page for the given request.
+    pub fn render(&self, request: &Request) -> Response {
+        let mut headers = HeaderMap::new();
+        if let Some(seconds) = self.retry_after {
+            headers.insert(RETRY_AFTER, HeaderValue::from(seconds));
+        }
+
+        if request.is_xhr {
+            headers.insert(
+                request.inertia_header.clone(),
+                HeaderValue::from_static("true"),
+            );
+            let page = Page {
+                component: self.component.into(),
+                props: serde_json::json!({
+                    "status": self.status.as_u16(),
+                    "message": self.message,
+                }),
+                url: request.url.clone(),
+                version: None,
+                modal: None,
+                deferred_props: None,
+                extra: Map::new(),
+            };
+            (self.status, headers, Json(page)).into_response()
+        } else {
+            let html = self.html.clone().unwrap_or_else(|| self.default_html());
+            (self.status, headers, Html(html)).into_response()
+        }
+    }
+
+    fn default_html(&self) -> String {
+        let title = format!(
+            "{} {}",
+            self.status.as_u16(),
+            self.status.canonical_reason().unwrap_or("Error")
+        );
+        html! {
+            (DOCTYPE)
+            html {
+                head {
+                    title { (title) }
+                    meta charset="utf-8";
+                    meta name="viewport" content="width=device-width, initial-scale=1.0";
+                }
+                body {
+                    h1 { (title) }
+                    p { (self.message) }
+                }
+            }
+        }
+        .into_string()
+    }
+}
+
+/// A failure to render an Inertia response. See the [module
+/// documentation](self).
+pub(crate) struct RenderError {
+    error: SerializeError,
+    backtrace: Backtrace,
+}
+
+impl RenderError {
+    /// Wraps `error`, capturing a backtrace in debug builds.
+    pub(crate) fn new(error: impl Into<SerializeError>) -> RenderError {
+        let backtrace = if cfg!(debug_assertions) {
+            Backtrace::force_capture()
+        } else {
+            Backtrace::disabled()
+        };
+        RenderError {
+            error: error.into(),
+            backtrace,
+        }
+    }
+
+    pub(crate) fn into_inner(self) -> SerializeError {
+        self.error
+    }
+
+    fn html(&self) -> String {
+        let prop = self
+            .error
+            .downcast_ref::<PropsError>()
+            .and_then(PropsError::key);
+        let causes = std::iter::successors(self.error.source(), |err| (*err).source());
+        html! {
+            (DOCTYPE)
+            html {
+                head {
+                    title { "Inertia render error" }
+                    meta charset="utf-8";
+                }
+                body {
+                    h1 { "500 Internal Server Error" }
+                    p { (self.error) }
+                    @if let Some(prop) = prop {
+                        p { "Prop: " code { (prop) } }
+                    }
+                    ul {
+                        @for cause in causes {
+                            li { (cause) }
+                        }
+                    }
+                    pre { (self.backtrace) }
+                }
+            }
+        }
+        .into_string()
+    }
+}
+
+impl IntoResponse for RenderError {
+    fn into_response(self) -> Response {
+        tracing::error!(error = %self.error, "failed to serialize inertia page object");
+        if cfg!(debug_assertions) {
+            (StatusCode::INTERNAL_SERVER_ERROR, Html(self.html())).into_response()
+        } else {
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::X_INERTIA;
+    use http::HeaderName;
+    use http_body_util::BodyExt;
+
+    async fn body_string(response: Response) -> String {
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(body.into()).expect("decoded string")
+    }
+
+    #[tokio::test]
+    async fn test_renders_component_for_inertia_requests() {
+        let response = ErrorPage::too_many_requests()
+            .component("Errors/RateLimited")
+            .retry_after(30)
+            .render(&Request::test_request());
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers().get(X_INERTIA).unwrap(), "true");
+        assert_eq!(response.headers().get(RETRY_AFTER).unwrap(), "30");
+        let body = body_string(response).await;
+        assert!(body.contains(r#""component":"Errors/RateLimited""#));
+        assert!(body.contains(r#""status":429"#));
+    }
+
+    #[test]
+    fn test_uses_configured_header_names() {
+        let request = Request {
+            inertia_header: HeaderName::from_static("x-app-inertia"),
+            ..Request::test_request()
+        };
+        let response = ErrorPage::too_many_requests().render(&request);
+        assert_eq!(response.headers().get("x-app-inertia").unwrap(), "true");
+        assert!(response.headers().get(X_INERTIA).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_renders_html_for_initial_loads() {
+        let request = Request {
+            is_xhr: false,
+            ..Request::test_request()
+        };
+        let response = ErrorPage::service_unavailable().render(&request);
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(response.headers().get(X_INERTIA).is_none());
+        let body = body_string(response).await;
+        assert!(body.contains("<title>503 Service Unavailable</title>"));
+
+        let response = ErrorPage::service_unavailable()
+            .html("<p>Back soon</p>")
+            .render(&request);
+        assert_eq!(body_string(response).await, "<p>Back soon</p>");
+    }
+
+    #[tokio::test]
+    async fn test_render_error() {
+        let error = PropsError::new("boom").with_key("users");
+        let response = RenderError::new(error).into_response();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = body_string(response).await;
+        assert!(body.contains("<p>failed to serialize prop `users`: boom</p>"));
+        assert!(body.contains("<code>users</code>"));
+    }
+}
@@ -0,0 +1,166 @@
+//! Conversions from validation errors to the `errors` prop.
+//!
+//! Inertia form helpers expect validation errors as a flat object of
+//! field names to messages, with nested fields in dot notation, e.g.
+//! `{ "address.city": "...", "items.0.name": "..." }`. The
+//! [IntoInertiaErrors] trait converts errors to this shape. It is
+//! implemented for the error types of [garde] and [validator] when the
+//! `garde` and `validator` features are enabled:
+//!
+//! ```rust,ignore
+//! use axum::response::{IntoResponse, Redirect};
+//! use axum_inertia::flash::Flash;
+//! use axum::Form;
+//! use garde::Validate;
+//!
+//! async fn create_user(flash: Flash, form: Form<NewUser>) -> impl IntoResponse {
+//!     if let Err(report) = form.validate() {
+//!         flash.errors(report);
+//!         return Redirect::to("/users/new");
+//!     }
+//!     // ...
+//! }
+//! ```
+//!
+//! Only the first message of each field is kept.
+//!
+//! [garde]: https://crates.io/crates/garde
+//! [validator]: https://crates.io/crates/validator
+
+use serde_json::{Map, Value};
+
+/// Converts validation errors to a map of field paths to messages.
+pub trait IntoInertiaErrors {
+    fn into_inertia_errors(self) -> Map<String, Value>;
+}
+
+impl IntoInertiaErrors for Map<String, Value> {
+    fn into_inertia_errors(self) -> Map<String, Value> {
+        self
+    }
+}
+
+/// Inserts `message` under `path`, unless the field already has one.
+#[cfg(any(feature = "garde", feature = "validator"))]
+fn insert_first(errors: &mut Map<String, Value>, path: String, message: String) {
+    errors.entry(path).or_insert(Value::String(message));
+}
+
+/// Converts a path like `items[0].name` to dot notation,
+/// `items.0.name`.
+#[cfg_attr(not(feature = "garde"), allow(dead_code))]
+fn dot_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for c in path.chars() {
+        match c {
+            '[' if out.is_empty() => {}
+            '[' => out.push('.'),
+            ']' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(feature = "garde")]
+impl IntoInertiaErrors for garde::Report {
+    fn into_inertia_errors(self) -> Map<String, Value> {
+        let mut errors = Map::new();
+        for (path, error) in self.iter() {
+            insert_first(
+                &mut errors,
+                dot_path(&path.to_string()),
+                error.message().to_string(),
+            );
+        }
+        errors
+    }
+}
+
+#[cfg(feature = "validator")]
+impl IntoInertiaErrors for validator::ValidationErrors {
+    fn into_inertia_errors(self) -> Map<String, Value> {
+        let mut errors = Map::new();
+        collect_validator_errors(&self, "", &mut errors);
+        errors
+    }
+}
+
+#[cfg(feature = "validator")]
+fn collect_validator_errors(
+    errors: &validator::ValidationErrors,
+    prefix: &str,
+    out: &mut Map<String, Value>,
+) {
+    use validator::ValidationErrorsKind;
+
+    let join = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        }
+    };
+    for (field, kind) in errors.errors() {
+        let path = join(field);
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                if let Some(error) = field_errors.first() {
+                    let message = error.message.as_ref().unwrap_or(&error.code).to_string();
+                    insert_first(out, path, message);
+                }
+            }
+            ValidationErrorsKind::Struct(nested) => {
+                collect_validator_errors(nested, &path, out);
+            }
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect_validator_errors(nested, &format!("{}.{}", path, index), out);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dot_path() {
+        assert_eq!(dot_path("email"), "email");
+        assert_eq!(dot_path("address.city"), "address.city");
+        assert_eq!(dot_path("items[0].name"), "items.0.name");
+        assert_eq!(dot_path("matrix[1][2]"), "matrix.1.2");
+        assert_eq!(dot_path("[3]"), "3");
+    }
+
+    #[cfg(feature = "garde")]
+    #[test]
+    fn test_garde_report() {
+        let mut report = garde::Report::new();
+        report.append(garde::Path::new("email"), garde::Error::new("not an email"));
+        report.append(garde::Path::new("email"), garde::Error::new("too long"));
+        let errors = report.into_inertia_errors();
+        assert_eq!(errors["email"], "not an email");
+    }
+
+    #[cfg(feature = "validator")]
+    #[test]
+    fn test_validation_errors() {
+        use validator::{ValidationError, ValidationErrors};
+
+        let mut address = ValidationErrors::new();
+        address.add("city", ValidationError::new("required"));
+        let mut errors = ValidationErrors::new();
+        errors.add(
+            "email",
+            ValidationError::new("email").with_message("not an email".into()),
+        );
+        let errors = ValidationErrors::merge(Err(errors), "address", Err(address))
+            .unwrap_err()
+            .into_inertia_errors();
+        assert_eq!(errors["email"], "not an email");
+        assert_eq!(errors["address.city"], "required");
+    }
+}
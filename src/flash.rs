@@ -0,0 +1,533 @@
+//! Flash messages and validation errors that survive a redirect.
+//!
+//! Handlers flash data with the [Flash] extractor, typically before
+//! redirecting. On the next request, the data is added to rendered
+//! pages as the `flash` and `errors` props, and cleared. By default,
+//! [FlashLayer] keeps the data in a [CookieStore], signed with a
+//! secret key, so apps don't need a session store:
+//!
+//! ```rust
+//! use axum::{response::{IntoResponse, Redirect}, routing::{get, post}, Router};
+//! use axum_inertia::{flash::{Flash, FlashLayer}, vite, Inertia};
+//! use serde_json::json;
+//!
+//! async fn create_user(flash: Flash) -> impl IntoResponse {
+//!     flash.error("email", "The email has already been taken.");
+//!     Redirect::to("/users/new")
+//! }
+//!
+//! async fn new_user(i: Inertia) -> impl IntoResponse {
+//!     // props include `errors: { email: "The email ..." }`
+//!     i.render("Users/New", json!({}))
+//! }
+//!
+//! let key = std::env::var("FLASH_KEY").unwrap_or_else(|_| "x".repeat(32));
+//! let app: Router = Router::new()
+//!     .route("/users", post(create_user))
+//!     .route("/users/new", get(new_user))
+//!     .with_state(vite::Development::default().into_config())
+//!     .layer(FlashLayer::new(key));
+//! ```
+//!
+//! Cookies are limited to about 4kB, so flashed data that doesn't fit
+//! is dropped with a logged error. The signature covers an expiry time,
+//! so a cookie is only accepted for a minute after it was set.
+//!
+//! Apps with a session store can keep flashed data in the session
+//! instead, by implementing [FlashStore] and passing it to
+//! [FlashLayer::with_store]. [Flash] and the rendered props work the
+//! same with any store.
+
+use crate::errors::IntoInertiaErrors;
+use async_trait::async_trait;
+use axum::extract::FromRequestParts;
+use axum::response::Response;
+use hmac::{Hmac, Mac};
+use http::header::{COOKIE, SET_COOKIE};
+use http::{request::Parts, HeaderMap, HeaderValue, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::Sha256;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tower_layer::Layer;
+use tower_service::Service;
+
+const COOKIE_NAME: &str = "inertia_flash";
+const MAX_COOKIE_SIZE: usize = 4000;
+const MIN_KEY_LEN: usize = 32;
+const MAX_AGE: Duration = Duration::from_secs(60);
+
+/// Flashed messages and validation errors.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FlashData {
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub messages: Map<String, Value>,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub errors: Map<String, Value>,
+}
+
+impl FlashData {
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty() && self.errors.is_empty()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    incoming: Option<FlashData>,
+    outgoing: FlashData,
+}
+
+/// A handle for flashing data to the next request, and reading data
+/// flashed by the previous one. Requires a [FlashLayer].
+#[derive(Clone, Default)]
+pub struct Flash {
+    state: Arc<Mutex<State>>,
+}
+
+impl Flash {
+    /// Flashes a message, included in the `flash` prop.
+    pub fn message(&self, key: &str, value: impl Into<Value>) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .outgoing
+            .messages
+            .insert(key.to_string(), value.into());
+    }
+
+    /// Flashes a validation error, included in the `errors` prop.
+    pub fn error(&self, key: &str, message: impl Into<Value>) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .outgoing
+            .errors
+            .insert(key.to_string(), message.into());
+    }
+
+    /// Flashes all validation errors, e.g. a `garde::Report`. See
+    /// the [errors](crate::errors) module.
+    pub fn errors(&self, errors: impl IntoInertiaErrors) {
+        let mut state = self.state.lock().unwrap();
+        state.outgoing.errors.extend(errors.into_inertia_errors());
+    }
+
+    /// Returns the data flashed by the previous request, if any.
+    pub fn incoming(&self) -> Option<FlashData> {
+        self.state.lock().unwrap().incoming.clone()
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Flash
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<Flash>().cloned().ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Missing Flash. Add a FlashLayer.",
+        ))
+    }
+}
+
+/// Where flashed data is kept between requests. [FlashLayer] loads
+/// the data before each request and saves it after the response.
+#[async_trait]
+pub trait FlashStore: Send + Sync + 'static {
+    /// Returns the data flashed by the previous request, if any.
+    async fn load(&self, parts: &Parts) -> Option<FlashData>;
+
+    /// Saves the data flashed by this request, replacing the loaded
+    /// data. `data` is empty when nothing was flashed, in which case
+    /// the loaded data must be cleared.
+    async fn save(&self, parts: &Parts, data: FlashData, response: &mut Response);
+}
+
+/// Keeps flashed data in a cookie signed with HMAC-SHA256. See the
+/// [module documentation](self).
+#[derive(Clone)]
+pub struct CookieStore {
+    key: Arc<Vec<u8>>,
+    secure: bool,
+}
+
+impl CookieStore {
+    /// Constructs a store signing cookies with `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is shorter than 32 bytes.
+    pub fn new(key: impl Into<Vec<u8>>) -> CookieStore {
+        let key = key.into();
+        assert!(
+            key.len() >= MIN_KEY_LEN,
+            "flash key must be at least {} bytes",
+            MIN_KEY_LEN
+        );
+        CookieStore {
+            key: Arc::new(key),
+            secure: false,
+        }
+    }
+
+    /// Sets the `Secure` attribute on the cookie. Defaults to false.
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    fn cookie(&self, value: &str, max_age: u64) -> HeaderValue {
+        let secure = if self.secure { "; Secure" } else { "" };
+        let cookie = format!(
+            "{}={}; Path=/; Max-Age={}; HttpOnly; SameSite=Lax{}",
+            COOKIE_NAME, value, max_age, secure
+        );
+        HeaderValue::from_str(&cookie).unwrap()
+    }
+}
+
+#[async_trait]
+impl FlashStore for CookieStore {
+    async fn load(&self, parts: &Parts) -> Option<FlashData> {
+        let json = verify(&self.key, read_cookie(&parts.headers)?, unix_now())?;
+        serde_json::from_str(&json).ok()
+    }
+
+    async fn save(&self, parts: &Parts, data: FlashData, response: &mut Response) {
+        let had_cookie = read_cookie(&parts.headers).is_some();
+        let cookie = if data.is_empty() {
+            had_cookie.then(|| self.cookie("", 0))
+        } else {
+            let json = serde_json::to_string(&data).unwrap();
+            let value = seal(&self.key, &json, unix_now() + MAX_AGE.as_secs());
+            if value.len() > MAX_COOKIE_SIZE {
+                tracing::error!(size = value.len(), "flashed data is too large for a cookie");
+                had_cookie.then(|| self.cookie("", 0))
+            } else {
+                Some(self.cookie(&value, MAX_AGE.as_secs()))
+            }
+        };
+        if let Some(cookie) = cookie {
+            response.headers_mut().append(SET_COOKIE, cookie);
+        }
+    }
+}
+
+/// Layer that applies [FlashService] to a service.
+#[derive(Clone)]
+pub struct FlashLayer {
+    store: Arc<dyn FlashStore>,
+}
+
+impl FlashLayer {
+    /// Constructs a layer keeping flashed data in a [CookieStore]
+    /// signed with `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is shorter than 32 bytes.
+    pub fn new(key: impl Into<Vec<u8>>) -> FlashLayer {
+        Self::with_store(CookieStore::new(key))
+    }
+
+    /// Constructs a layer keeping flashed data in `store`, e.g. a
+    /// [CookieStore] with the `Secure` attribute, or the app's
+    /// session.
+    pub fn with_store(store: impl FlashStore) -> FlashLayer {
+        FlashLayer {
+            store: Arc::new(store),
+        }
+    }
+}
+
+impl<S> Layer<S> for FlashLayer {
+    type Service = FlashService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        FlashService {
+            inner,
+            store: self.store.clone(),
+        }
+    }
+}
+
+/// Service that loads and saves flashed data with a [FlashStore].
+#[derive(Clone)]
+pub struct FlashService<S> {
+    inner: S,
+    store: Arc<dyn FlashStore>,
+}
+
+impl<S, B> Service<http::Request<B>> for FlashService<S>
+where
+    S: Service<http::Request<B>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        // Take the service that was driven to readiness, leaving a
+        // clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let store = self.store.clone();
+
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let flash = Flash::default();
+            flash.state.lock().unwrap().incoming = store.load(&parts).await;
+            let mut req = http::Request::from_parts(parts.clone(), body);
+            req.extensions_mut().insert(flash.clone());
+
+            let mut response = inner.call(req).await?;
+            let outgoing = std::mem::take(&mut flash.state.lock().unwrap().outgoing);
+            store.save(&parts, outgoing, &mut response).await;
+            Ok(response)
+        })
+    }
+}
+
+/// Returns the value of the flash cookie, if any.
+fn read_cookie(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, value)| *name == COOKIE_NAME && !value.is_empty())
+        .map(|(_, value)| value)
+}
+
+/// Returns a cookie value of `json`, signed together with its expiry
+/// time in seconds since the epoch, as `signature.expires.payload`.
+fn seal(key: &[u8], json: &str, expires: u64) -> String {
+    let payload = format!(
+        "{}.{}",
+        expires,
+        percent_encoding::utf8_percent_encode(json, percent_encoding::NON_ALPHANUMERIC)
+    );
+    format!(
+        "{}.{}",
+        hex::encode(mac(key, &payload).finalize().into_bytes()),
+        payload
+    )
+}
+
+/// Returns the json payload of a sealed cookie value, if the signature
+/// is valid and it hasn't expired at `now`.
+fn verify(key: &[u8], value: &str, now: u64) -> Option<String> {
+    let (signature, payload) = value.split_once('.')?;
+    mac(key, payload)
+        .verify_slice(&hex::decode(signature).ok()?)
+        .ok()?;
+    let (expires, json) = payload.split_once('.')?;
+    if expires.parse::<u64>().ok()? <= now {
+        return None;
+    }
+    percent_encoding::percent_decode_str(json)
+        .decode_utf8()
+        .ok()
+        .map(|json| json.into_owned())
+}
+
+/// Returns an HMAC-SHA256 of `message`.
+fn mac(key: &[u8], message: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any size");
+    mac.update(message.as_bytes());
+    mac
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Inertia, InertiaConfig};
+    use axum::response::{IntoResponse, Redirect};
+    use axum::routing::{get, post};
+    use axum::Router;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    const KEY: &str = "0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn test_hmac_sha256() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            hex::encode(
+                mac(b"Jefe", "what do ya want for nothing?")
+                    .finalize()
+                    .into_bytes()
+            ),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_verifies_signatures_and_expiry() {
+        let json = r#"{"errors":{"a":"b"}}"#;
+        let value = seal(KEY.as_bytes(), json, 1000);
+        assert_eq!(verify(KEY.as_bytes(), &value, 999).as_deref(), Some(json));
+        assert_eq!(verify(KEY.as_bytes(), &value, 1000), None);
+
+        let tampered = value.replace("%22b%22", "%22c%22");
+        assert_eq!(verify(KEY.as_bytes(), &tampered, 999), None);
+        let extended = value.replace(".1000.", ".2000.");
+        assert_eq!(verify(KEY.as_bytes(), &extended, 999), None);
+        assert_eq!(
+            verify(b"another key, at least 32 bytes...", &value, 999),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn it_flashes_with_custom_stores() {
+        #[derive(Default)]
+        struct MemoryStore(Mutex<Option<FlashData>>);
+
+        #[async_trait]
+        impl FlashStore for Arc<MemoryStore> {
+            async fn load(&self, _parts: &Parts) -> Option<FlashData> {
+                self.0.lock().unwrap().take()
+            }
+
+            async fn save(&self, _parts: &Parts, data: FlashData, _response: &mut Response) {
+                if !data.is_empty() {
+                    *self.0.lock().unwrap() = Some(data);
+                }
+            }
+        }
+
+        let store = Arc::new(MemoryStore::default());
+        let app = Router::new()
+            .route(
+                "/create",
+                post(|flash: Flash| async move {
+                    flash.message("success", "Created!");
+                    Redirect::to("/new")
+                }),
+            )
+            .route(
+                "/new",
+                get(|i: Inertia| async move { i.render("New", json!({})) }),
+            )
+            .with_state(InertiaConfig::new(
+                None,
+                Box::new(|props| props.to_string()),
+            ))
+            .layer(FlashLayer::with_store(store.clone()));
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+        client
+            .post(format!("http://{}/create", &addr))
+            .send()
+            .await
+            .unwrap();
+        assert!(store.0.lock().unwrap().is_none());
+
+        let res = client
+            .post(format!("http://{}/create", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        let page: Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["props"]["flash"], json!({"success": "Created!"}));
+        assert!(store.0.lock().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn it_flashes_across_redirects() {
+        async fn create(flash: Flash) -> impl IntoResponse {
+            flash.message("success", "Created!");
+            flash.error("name", "Too short");
+            Redirect::to("/new")
+        }
+
+        async fn new(i: Inertia) -> impl IntoResponse {
+            i.render("New", json!({}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+        let app = Router::new()
+            .route("/create", post(create))
+            .route("/new", get(new))
+            .with_state(InertiaConfig::new(None, layout))
+            .layer(FlashLayer::new(KEY));
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap();
+
+        let res = client
+            .post(format!("http://{}/create", &addr))
+            .send()
+            .await
+            .unwrap();
+        let cookie = res.headers().get("Set-Cookie").unwrap().to_str().unwrap();
+        let cookie = cookie.split(';').next().unwrap().to_string();
+
+        let res = client
+            .get(format!("http://{}/new", &addr))
+            .header("X-Inertia", "true")
+            .header("Cookie", &cookie)
+            .send()
+            .await
+            .unwrap();
+        let cleared = res.headers().get("Set-Cookie").unwrap().to_str().unwrap();
+        assert!(cleared.starts_with("inertia_flash=; Path=/; Max-Age=0"));
+        let page: Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["props"]["flash"], json!({"success": "Created!"}));
+        assert_eq!(page["props"]["errors"], json!({"name": "Too short"}));
+
+        let res = client
+            .get(format!("http://{}/new", &addr))
+            .header("X-Inertia", "true")
+            .header("Cookie", cookie.replace("Created", "Hacked"))
+            .send()
+            .await
+            .unwrap();
+        let page: Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert!(page["props"].get("flash").is_none());
+    }
+}
@@ -0,0 +1,190 @@
+//! Rejection of malformed Inertia requests.
+//!
+//! The [Inertia](crate::Inertia) extractor ignores partial reload
+//! headers it can't use, e.g. `X-Inertia-Partial-Data` without
+//! `X-Inertia-Partial-Component`, and renders all props. That's
+//! forgiving, but hides client bugs. [InertiaGuardLayer] rejects such
+//! Inertia requests with `400 Bad Request` and a json error instead:
+//!
+//! ```rust
+//! use axum::{routing::get, Router};
+//! use axum_inertia::guard::InertiaGuardLayer;
+//!
+//! let app: Router = Router::new()
+//!     .route("/", get(|| async { "ok" }))
+//!     .layer(InertiaGuardLayer::new());
+//! ```
+//!
+//! Requests are rejected when, with `X-Inertia: true`:
+//!
+//! - only one of `X-Inertia-Partial-Data` and
+//!   `X-Inertia-Partial-Component` is sent;
+//! - `X-Inertia-Partial-Data` lists no props;
+//! - `X-Inertia-Partial-Component` is empty;
+//! - an Inertia header isn't valid text.
+//!
+//! Other requests pass through unchanged.
+
+use crate::partial::parse_partial_data;
+use crate::protocol::HeaderNames;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use futures_util::future::{self, Either, Ready};
+use http::{HeaderMap, HeaderName, StatusCode};
+use serde_json::json;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that applies [InertiaGuardService] to a service.
+#[derive(Clone, Debug, Default)]
+pub struct InertiaGuardLayer {
+    names: HeaderNames,
+}
+
+impl InertiaGuardLayer {
+    pub fn new() -> InertiaGuardLayer {
+        Self::default()
+    }
+
+    /// Reads the Inertia headers with the given names. See
+    /// [InertiaConfig::header_names](crate::InertiaConfig::header_names).
+    pub fn header_names(mut self, names: HeaderNames) -> Self {
+        self.names = names;
+        self
+    }
+}
+
+impl<S> Layer<S> for InertiaGuardLayer {
+    type Service = InertiaGuardService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InertiaGuardService {
+            inner,
+            names: self.names.clone(),
+        }
+    }
+}
+
+/// Service that rejects malformed Inertia requests.
+#[derive(Clone, Debug)]
+pub struct InertiaGuardService<S> {
+    inner: S,
+    names: HeaderNames,
+}
+
+impl<S, B> Service<http::Request<B>> for InertiaGuardService<S>
+where
+    S: Service<http::Request<B>, Response = Response>,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Either<Ready<Result<Response, S::Error>>, S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        match check(req.headers(), &self.names) {
+            Ok(()) => Either::Right(self.inner.call(req)),
+            Err((header, message)) => {
+                tracing::warn!(%header, message, "rejecting malformed inertia request");
+                let body = json!({ "message": message, "header": header.as_str() });
+                Either::Left(future::ok(
+                    (StatusCode::BAD_REQUEST, Json(body)).into_response(),
+                ))
+            }
+        }
+    }
+}
+
+/// Checks the Inertia headers of a request, returning the offending
+/// header and a description of the problem.
+fn check(headers: &HeaderMap, names: &HeaderNames) -> Result<(), (HeaderName, &'static str)> {
+    let header = |name: &HeaderName| {
+        headers
+            .get(name)
+            .map(|value| {
+                value
+                    .to_str()
+                    .map_err(|_| (name.clone(), "header is not valid text"))
+            })
+            .transpose()
+    };
+    if header(&names.inertia)? != Some("true") {
+        return Ok(());
+    }
+    header(&names.version)?;
+    header(&names.protocol)?;
+    match (
+        header(&names.partial_data)?,
+        header(&names.partial_component)?,
+    ) {
+        (None, None) => Ok(()),
+        (Some(_), None) => Err((
+            names.partial_component.clone(),
+            "partial reload data sent without a component",
+        )),
+        (None, Some(_)) => Err((
+            names.partial_data.clone(),
+            "partial reload component sent without data",
+        )),
+        (Some(data), Some(component)) => {
+            if parse_partial_data(data).is_empty() {
+                Err((names.partial_data.clone(), "partial reload lists no props"))
+            } else if component.trim().is_empty() {
+                Err((
+                    names.partial_component.clone(),
+                    "partial reload component is empty",
+                ))
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{X_INERTIA, X_INERTIA_PARTIAL_COMPONENT, X_INERTIA_PARTIAL_DATA};
+
+    fn headers(pairs: &[(HeaderName, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(name.clone(), value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_check() {
+        let names = HeaderNames::default();
+        let result =
+            |pairs: &[(HeaderName, &str)]| check(&headers(pairs), &names).map_err(|err| err.1);
+
+        assert_eq!(result(&[(X_INERTIA_PARTIAL_DATA, "")]), Ok(()));
+        assert_eq!(result(&[(X_INERTIA, "true")]), Ok(()));
+        assert_eq!(
+            result(&[
+                (X_INERTIA, "true"),
+                (X_INERTIA_PARTIAL_DATA, "users"),
+                (X_INERTIA_PARTIAL_COMPONENT, "Users"),
+            ]),
+            Ok(())
+        );
+        assert_eq!(
+            result(&[(X_INERTIA, "true"), (X_INERTIA_PARTIAL_DATA, "users")]),
+            Err("partial reload data sent without a component")
+        );
+        assert_eq!(
+            result(&[
+                (X_INERTIA, "true"),
+                (X_INERTIA_PARTIAL_DATA, " , "),
+                (X_INERTIA_PARTIAL_COMPONENT, "Users"),
+            ]),
+            Err("partial reload lists no props")
+        );
+    }
+}
@@ -0,0 +1,137 @@
+//! Merging of `<head>` tags returned by server-side rendering.
+//!
+//! This crate doesn't render pages on the server itself. Apps that
+//! call an Inertia SSR server (e.g. `@inertiajs/server`) get back a
+//! `head` array of html tags, which must be merged with the tags of
+//! the layout instead of just concatenated. [merge_head] does this
+//! the same way the Inertia client-side head manager does:
+//!
+//! - a `<title>` from SSR replaces the layout's title;
+//! - tags with the same `inertia` key (set by `<Head>` components
+//!   via `head-key`) are deduplicated, with the SSR tag winning.
+//!
+//! ```rust
+//! use axum_inertia::head::merge_head;
+//!
+//! let layout = ["<title>My app</title>", r#"<meta name="description" content="An app" inertia="description">"#];
+//! let ssr = ["<title inertia>Users</title>", r#"<meta name="description" content="All users" inertia="description">"#];
+//!
+//! assert_eq!(
+//!     merge_head(&layout, &ssr),
+//!     vec!["<title inertia>Users</title>", r#"<meta name="description" content="All users" inertia="description">"#]
+//! );
+//! ```
+
+/// Merges `ssr` head tags into `layout` head tags. Layout tags that
+/// are replaced by an SSR tag are dropped; the remaining layout tags
+/// come first, followed by the SSR tags.
+pub fn merge_head<L, S>(layout: &[L], ssr: &[S]) -> Vec<String>
+where
+    L: AsRef<str>,
+    S: AsRef<str>,
+{
+    let ssr_keys: Vec<String> = ssr.iter().filter_map(|tag| tag_key(tag.as_ref())).collect();
+    layout
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|tag| tag_key(tag).is_none_or(|key| !ssr_keys.contains(&key)))
+        .chain(ssr.iter().map(AsRef::as_ref))
+        .map(|tag| tag.trim().to_string())
+        .collect()
+}
+
+/// Returns the key identifying a tag for deduplication: `title` for
+/// title tags, otherwise the value of an `inertia` attribute.
+fn tag_key(tag: &str) -> Option<String> {
+    let open = tag.trim().strip_prefix('<')?;
+    let open = &open[..open.find('>').unwrap_or(open.len())];
+    let (name, mut attrs) = open
+        .trim_end_matches('/')
+        .split_once(char::is_whitespace)
+        .unwrap_or((open, ""));
+    if name.eq_ignore_ascii_case("title") {
+        return Some("title".to_string());
+    }
+
+    // Scan `name`, `name=value`, `name="value"` and `name='value'`
+    // attributes.
+    while !attrs.trim_start().is_empty() {
+        attrs = attrs.trim_start();
+        let end = attrs
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(attrs.len());
+        let (attr, rest) = attrs.split_at(end);
+        let rest = rest.trim_start();
+        let (value, rest) = match rest.strip_prefix('=') {
+            Some(rest) => {
+                let rest = rest.trim_start();
+                match rest.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let rest = &rest[1..];
+                        let end = rest.find(quote).unwrap_or(rest.len());
+                        (&rest[..end], rest.get(end + 1..).unwrap_or(""))
+                    }
+                    _ => {
+                        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                        rest.split_at(end)
+                    }
+                }
+            }
+            None => ("", rest),
+        };
+        if attr.eq_ignore_ascii_case("inertia") && !value.is_empty() {
+            return Some(value.to_string());
+        }
+        attrs = rest;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_keys() {
+        assert_eq!(tag_key("<title>A</title>"), Some("title".to_string()));
+        assert_eq!(
+            tag_key("<TITLE inertia>A</TITLE>"),
+            Some("title".to_string())
+        );
+        assert_eq!(
+            tag_key(r#"<meta name="x" inertia="desc">"#),
+            Some("desc".to_string())
+        );
+        assert_eq!(
+            tag_key("<link rel=icon inertia='icon' />"),
+            Some("icon".to_string())
+        );
+        assert_eq!(tag_key(r#"<meta data-inertia="x">"#), None);
+        assert_eq!(tag_key(r#"<meta name="x" inertia>"#), None);
+        assert_eq!(tag_key(r#"<meta content="a inertia=b" name=x>"#), None);
+        assert_eq!(tag_key("not a tag"), None);
+    }
+
+    #[test]
+    fn test_merges_head_tags() {
+        let layout = [
+            "<title>My app</title>",
+            r#"<meta charset="utf-8">"#,
+            r#"<link rel="icon" href="/favicon.ico" inertia="icon">"#,
+        ];
+        let ssr = [
+            r#"<link rel="icon" href="/users.ico" inertia="icon">"#,
+            r#"<meta name="robots" content="noindex">"#,
+        ];
+
+        assert_eq!(
+            merge_head(&layout, &ssr),
+            vec![
+                "<title>My app</title>",
+                r#"<meta charset="utf-8">"#,
+                r#"<link rel="icon" href="/users.ico" inertia="icon">"#,
+                r#"<meta name="robots" content="noindex">"#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Client hints about the user's display preferences.
+//!
+//! Browsers send [client hints] like `Sec-CH-Prefers-Color-Scheme`
+//! when the server asks for them with an `Accept-CH` header. They let
+//! apps pick a theme or image sizes on the first render, instead of
+//! flashing the wrong one until the client takes over.
+//!
+//! [Inertia::hints](crate::Inertia::hints) parses them from the
+//! request. [InertiaConfig::client_hints](crate::InertiaConfig::client_hints)
+//! asks browsers for them and shares them as a prop:
+//!
+//! ```rust
+//! use axum_inertia::vite;
+//!
+//! // Pages receive e.g. `{ "hints": { "prefersColorScheme": "dark" } }`
+//! let inertia = vite::Development::default()
+//!     .into_config()
+//!     .client_hints("hints");
+//! ```
+//!
+//! Browsers only send hints once they have seen an `Accept-CH` header,
+//! i.e. not on the very first visit, and some don't support them at
+//! all, so every hint is optional.
+//!
+//! [client hints]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Client_hints
+
+use http::header::USER_AGENT;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use serde::Serialize;
+
+pub const ACCEPT_CH: HeaderName = HeaderName::from_static("accept-ch");
+
+pub const SEC_CH_PREFERS_COLOR_SCHEME: HeaderName =
+    HeaderName::from_static("sec-ch-prefers-color-scheme");
+
+pub const SEC_CH_VIEWPORT_WIDTH: HeaderName = HeaderName::from_static("sec-ch-viewport-width");
+
+/// Sent by Chromium browsers on every request, without `Accept-CH`.
+pub const SEC_CH_UA_MOBILE: HeaderName = HeaderName::from_static("sec-ch-ua-mobile");
+
+/// The legacy name of `Sec-CH-Viewport-Width`.
+pub const VIEWPORT_WIDTH: HeaderName = HeaderName::from_static("viewport-width");
+
+/// The `Accept-CH` value asking for the hints parsed by
+/// [ClientHints].
+pub const ACCEPT_CH_VALUE: HeaderValue =
+    HeaderValue::from_static("Sec-CH-Prefers-Color-Scheme, Sec-CH-Viewport-Width");
+
+/// The user's preferred color scheme.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+/// Client hints sent with a request.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientHints {
+    /// From `Sec-CH-Prefers-Color-Scheme`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefers_color_scheme: Option<ColorScheme>,
+    /// The layout viewport width in css pixels, from
+    /// `Sec-CH-Viewport-Width` or `Viewport-Width`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub viewport_width: Option<u32>,
+}
+
+impl ClientHints {
+    /// Parses the hints in `headers`, ignoring invalid values.
+    pub fn from_headers(headers: &HeaderMap) -> ClientHints {
+        let header = |name: &HeaderName| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.trim().trim_matches('"'))
+        };
+        let prefers_color_scheme = match header(&SEC_CH_PREFERS_COLOR_SCHEME) {
+            Some("light") => Some(ColorScheme::Light),
+            Some("dark") => Some(ColorScheme::Dark),
+            _ => None,
+        };
+        let viewport_width = header(&SEC_CH_VIEWPORT_WIDTH)
+            .or_else(|| header(&VIEWPORT_WIDTH))
+            .and_then(|width| width.parse().ok());
+        ClientHints {
+            prefers_color_scheme,
+            viewport_width,
+        }
+    }
+}
+
+/// Whether a request comes from a mobile or a desktop browser. See
+/// [Inertia::device_class](crate::Inertia::device_class) and
+/// [PerDevice](crate::props::PerDevice).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceClass {
+    Mobile,
+    #[default]
+    Desktop,
+}
+
+impl DeviceClass {
+    /// Detects the device class from the `Sec-CH-UA-Mobile` hint or,
+    /// without it, from `Mobi` in the `User-Agent`. Defaults to
+    /// [Desktop](DeviceClass::Desktop).
+    ///
+    /// Responses that depend on it should vary on both headers.
+    pub fn from_headers(headers: &HeaderMap) -> DeviceClass {
+        let header = |name: &HeaderName| headers.get(name).and_then(|value| value.to_str().ok());
+        let mobile = match header(&SEC_CH_UA_MOBILE).map(str::trim) {
+            Some("?1") => true,
+            Some("?0") => false,
+            _ => header(&USER_AGENT).is_some_and(|agent| agent.contains("Mobi")),
+        };
+        if mobile {
+            DeviceClass::Mobile
+        } else {
+            DeviceClass::Desktop
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_class() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(DeviceClass::from_headers(&headers), DeviceClass::Desktop);
+
+        headers.insert(
+            USER_AGENT,
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) Mobile/15E148"
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(DeviceClass::from_headers(&headers), DeviceClass::Mobile);
+
+        headers.insert(SEC_CH_UA_MOBILE, "?0".parse().unwrap());
+        assert_eq!(DeviceClass::from_headers(&headers), DeviceClass::Desktop);
+    }
+
+    #[test]
+    fn test_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(ClientHints::from_headers(&headers), ClientHints::default());
+
+        headers.insert(SEC_CH_PREFERS_COLOR_SCHEME, r#""dark""#.parse().unwrap());
+        headers.insert(VIEWPORT_WIDTH, "1280".parse().unwrap());
+        assert_eq!(
+            ClientHints::from_headers(&headers),
+            ClientHints {
+                prefers_color_scheme: Some(ColorScheme::Dark),
+                viewport_width: Some(1280),
+            }
+        );
+
+        headers.insert(SEC_CH_PREFERS_COLOR_SCHEME, "sepia".parse().unwrap());
+        headers.insert(SEC_CH_VIEWPORT_WIDTH, "wide".parse().unwrap());
+        assert_eq!(ClientHints::from_headers(&headers), ClientHints::default());
+    }
+}
@@ -0,0 +1,63 @@
+//! Helpers for embedding the page object in html layouts.
+//!
+//! The serialized page object is user data, and may contain sequences
+//! that break out of the surrounding html, such as `</script>` or
+//! `<!--`, when it's embedded in a `<script>` tag or an attribute
+//! escaped by hand. [escape_json] escapes them following the [OWASP]
+//! guidance for inline json, and is used by the [vite](crate::vite)
+//! layouts. Custom layouts should use it too:
+//!
+//! ```rust
+//! use axum_inertia::{html::escape_json, InertiaConfig};
+//!
+//! let inertia = InertiaConfig::new(
+//!     None,
+//!     Box::new(|page| {
+//!         format!(
+//!             r#"<div id="app"></div><script data-page="app" type="application/json">{}</script>"#,
+//!             escape_json(&page)
+//!         )
+//!     }),
+//! );
+//! ```
+//!
+//! [OWASP]: https://cheatsheetseries.owasp.org/cheatsheets/Cross_Site_Scripting_Prevention_Cheat_Sheet.html
+
+/// Escapes `<`, `>`, `&`, U+2028 and U+2029 in `json` as `\uXXXX`
+/// sequences.
+///
+/// The result is equivalent json: these characters can only occur
+/// inside json strings, where the escapes decode to the same text.
+/// Escaping `<` and `>` covers `</script` and `<!--`, and U+2028 and
+/// U+2029 are line terminators in older JavaScript engines.
+pub fn escape_json(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    for c in json.chars() {
+        match c {
+            '<' => out.push_str("\\u003c"),
+            '>' => out.push_str("\\u003e"),
+            '&' => out.push_str("\\u0026"),
+            '\u{2028}' => out.push_str("\\u2028"),
+            '\u{2029}' => out.push_str("\\u2029"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_escape_json() {
+        let value = json!({ "bio": "</script><!-- a & b \u{2028}\u{2029}" });
+        let escaped = escape_json(&value.to_string());
+        assert_eq!(
+            escaped,
+            r#"{"bio":"\u003c/script\u003e\u003c!-- a \u0026 b \u2028\u2029"}"#
+        );
+        assert_eq!(serde_json::from_str::<Value>(&escaped).unwrap(), value);
+    }
+}
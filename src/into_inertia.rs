@@ -0,0 +1,130 @@
+//! Returning pages from handlers without calling
+//! [render](crate::Inertia::render).
+//!
+//! Handlers behind an [InertiaLayer] can return a [PageResponse]
+//! directly. The layer extracts [Inertia] from the request and
+//! renders the page:
+//!
+//! ```rust
+//! use axum::{routing::get, Router};
+//! use axum_inertia::{layer::InertiaLayer, vite, PageResponse};
+//! use serde_json::json;
+//!
+//! async fn users() -> PageResponse<serde_json::Value> {
+//!     PageResponse::new("Users/Index", json!({ "users": ["mikey"] }))
+//! }
+//!
+//! let inertia = vite::Development::default().into_config();
+//! let app: Router = Router::new()
+//!     .route("/users", get(users))
+//!     .layer(InertiaLayer::new(inertia));
+//! ```
+//!
+//! Without an [InertiaLayer], a [PageResponse] responds with `500
+//! Internal Server Error`.
+//!
+//! Anything implementing [IntoInertia], including `(component,
+//! props)` tuples, can also be rendered with
+//! [Inertia::respond](crate::Inertia::respond).
+//!
+//! [InertiaLayer]: crate::layer::InertiaLayer
+
+use crate::partial::Partial;
+use crate::props::{Props, PropsError};
+use crate::Inertia;
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// Values that describe an Inertia page: a component and its props.
+pub trait IntoInertia {
+    type Props: Props;
+
+    fn into_page_response(self) -> PageResponse<Self::Props>;
+}
+
+/// An Inertia page returned from a handler. See the [module
+/// documentation](self).
+pub struct PageResponse<P> {
+    pub(crate) component: Cow<'static, str>,
+    pub(crate) props: P,
+}
+
+impl<P: Props> PageResponse<P> {
+    pub fn new(component: impl Into<Cow<'static, str>>, props: P) -> PageResponse<P> {
+        PageResponse {
+            component: component.into(),
+            props,
+        }
+    }
+}
+
+impl<P: Props> IntoInertia for PageResponse<P> {
+    type Props = P;
+
+    fn into_page_response(self) -> PageResponse<P> {
+        self
+    }
+}
+
+impl<P: Props> IntoInertia for (&'static str, P) {
+    type Props = P;
+
+    fn into_page_response(self) -> PageResponse<P> {
+        PageResponse::new(self.0, self.1)
+    }
+}
+
+impl<P: Props> IntoInertia for (String, P) {
+    type Props = P;
+
+    fn into_page_response(self) -> PageResponse<P> {
+        PageResponse::new(self.0, self.1)
+    }
+}
+
+/// A page waiting to be rendered by an
+/// [InertiaLayer](crate::layer::InertiaLayer), stored in response
+/// extensions.
+#[derive(Clone)]
+pub(crate) struct PendingPage {
+    component: Cow<'static, str>,
+    props: Arc<dyn Props + Send + Sync>,
+}
+
+impl PendingPage {
+    pub(crate) fn render(self, inertia: Inertia) -> Response {
+        inertia
+            .render(&self.component, ArcProps(self.props))
+            .into_response()
+    }
+}
+
+struct ArcProps(Arc<dyn Props + Send + Sync>);
+
+impl Props for ArcProps {
+    fn serialize(&self, partial: Option<&Partial>) -> Result<Value, PropsError> {
+        self.0.serialize(partial)
+    }
+}
+
+impl<P> IntoResponse for PageResponse<P>
+where
+    P: Props + Send + Sync + 'static,
+{
+    fn into_response(self) -> Response {
+        let page = PendingPage {
+            component: self.component,
+            props: Arc::new(self.props),
+        };
+        let mut response = (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "PageResponse must be rendered by an InertiaLayer",
+        )
+            .into_response();
+        response.extensions_mut().insert(page);
+        response
+    }
+}
@@ -0,0 +1,330 @@
+//! A layer providing [InertiaConfig] through request extensions.
+//!
+//! The [Inertia](crate::Inertia) extractor usually gets its config
+//! from router state. Apps that can't change their state type, e.g.
+//! when mounting a library-provided `Router<()>`, can instead add an
+//! [InertiaLayer]:
+//!
+//! ```rust
+//! use axum::{response::IntoResponse, routing::get, Router};
+//! use axum_inertia::{layer::InertiaLayer, vite, Inertia};
+//! use serde_json::json;
+//!
+//! async fn home(i: Inertia) -> impl IntoResponse {
+//!     i.render("Home", json!({}))
+//! }
+//!
+//! let inertia = vite::Development::default().into_config();
+//! let app: Router = Router::new()
+//!     .route("/", get(home))
+//!     .layer(InertiaLayer::new(inertia));
+//! ```
+//!
+//! The extractor only falls back to extensions for routers without
+//! state; routers with state must provide the config through
+//! [FromRef](axum::extract::FromRef), or override it with an
+//! [InertiaScopeLayer].
+//!
+//! An [InertiaScopeLayer] overrides the config for a subtree of
+//! routes, even in routers with state, e.g. for a differently themed
+//! layout:
+//!
+//! ```rust
+//! use axum::{response::IntoResponse, routing::get, Router};
+//! use axum_inertia::{layer::InertiaScopeLayer, vite, Inertia};
+//! use serde_json::json;
+//!
+//! async fn page(i: Inertia) -> impl IntoResponse {
+//!     i.render("Page", json!({}))
+//! }
+//!
+//! let docs = vite::Development::default().title("Docs").into_config();
+//! let app: Router = Router::new()
+//!     .route("/", get(page))
+//!     .nest(
+//!         "/docs",
+//!         Router::new()
+//!             .route("/intro", get(page))
+//!             .layer(InertiaScopeLayer::new(docs)),
+//!     )
+//!     .with_state(vite::Development::default().into_config());
+//! ```
+//!
+//! Both layers also render [PageResponse](crate::PageResponse)s
+//! returned by handlers. See the [into_inertia](crate::into_inertia)
+//! module.
+
+use crate::config::InertiaConfig;
+use crate::into_inertia::PendingPage;
+use crate::Inertia;
+use axum::response::{IntoResponse, Response};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that applies [InertiaService] to a service.
+#[derive(Clone)]
+pub struct InertiaLayer {
+    config: InertiaConfig,
+}
+
+impl InertiaLayer {
+    pub fn new(config: InertiaConfig) -> InertiaLayer {
+        InertiaLayer { config }
+    }
+}
+
+impl<S> Layer<S> for InertiaLayer {
+    type Service = InertiaService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InertiaService {
+            inner,
+            config: self.config.clone(),
+            scoped: false,
+        }
+    }
+}
+
+/// Layer that applies [InertiaService] to a service, overriding the
+/// config from router state.
+#[derive(Clone)]
+pub struct InertiaScopeLayer {
+    config: InertiaConfig,
+}
+
+impl InertiaScopeLayer {
+    pub fn new(config: InertiaConfig) -> InertiaScopeLayer {
+        InertiaScopeLayer { config }
+    }
+}
+
+impl<S> Layer<S> for InertiaScopeLayer {
+    type Service = InertiaService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InertiaService {
+            inner,
+            config: self.config.clone(),
+            scoped: true,
+        }
+    }
+}
+
+/// An [InertiaConfig] that takes precedence over router state.
+#[derive(Clone)]
+pub(crate) struct ScopedConfig(pub(crate) InertiaConfig);
+
+/// Service that inserts an [InertiaConfig] into request extensions,
+/// and renders [PageResponse](crate::PageResponse)s.
+#[derive(Clone)]
+pub struct InertiaService<S> {
+    inner: S,
+    config: InertiaConfig,
+    scoped: bool,
+}
+
+impl<S, B> Service<http::Request<B>> for InertiaService<S>
+where
+    S: Service<http::Request<B>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        // Take the service that was driven to readiness, leaving a
+        // clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let config = self.config.clone();
+        let scoped = self.scoped;
+
+        Box::pin(async move {
+            let (mut parts, body) = req.into_parts();
+            parts.extensions.insert(config.clone());
+            if scoped {
+                parts.extensions.insert(ScopedConfig(config.clone()));
+            }
+            let saved_parts = parts.clone();
+
+            let mut response = inner.call(http::Request::from_parts(parts, body)).await?;
+            if let Some(mode) = config.strict_mode() {
+                if saved_parts
+                    .headers
+                    .contains_key(&config.header_names_ref().inertia)
+                {
+                    mode.check_redirect(&saved_parts.method, response.status());
+                }
+            }
+            let Some(page) = response.extensions_mut().remove::<PendingPage>() else {
+                return Ok(response);
+            };
+            Ok(match Inertia::from_parts(&saved_parts, config).await {
+                Ok(inertia) => page.render(inertia),
+                Err(rejection) => rejection.into_response(),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PageResponse;
+    use axum::{response::IntoResponse, routing::get, Router};
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn it_renders_page_responses() {
+        async fn handler() -> PageResponse<serde_json::Value> {
+            PageResponse::new("Users", json!({"users": ["mikey"]}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+        let config = InertiaConfig::new(None, layout);
+
+        let app = Router::new()
+            .route("/users", get(handler))
+            .layer(InertiaLayer::new(config))
+            .route("/without-layer", get(handler));
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/users", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Partial-Component", "Users")
+            .header("X-Inertia-Partial-Data", "users")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.headers().get("X-Inertia").unwrap(), "true");
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["component"], "Users");
+        assert_eq!(page["props"]["users"][0], "mikey");
+
+        let res = client
+            .get(format!("http://{}/without-layer", &addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 500);
+    }
+
+    #[tokio::test]
+    async fn it_provides_config_to_stateless_routers() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render("Home", json!({"a": 1}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+        let config = InertiaConfig::new(Some("123".to_string()), layout);
+
+        let app = Router::new()
+            .route("/with-layer", get(handler))
+            .layer(InertiaLayer::new(config))
+            .route("/without-layer", get(handler));
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/with-layer", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Version", "123")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["props"]["a"], 1);
+        assert_eq!(page["version"], "123");
+
+        let res = client
+            .get(format!("http://{}/without-layer", &addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 500);
+    }
+
+    #[tokio::test]
+    async fn it_overrides_state_config_in_scopes() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render("Page", json!({}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+        let docs_layout =
+            Box::new(|props| format!(r#"<html><body class="docs" data-page='{}'></body>"#, props));
+        let config = InertiaConfig::new(Some("app".to_string()), layout);
+        let docs = InertiaConfig::new(Some("docs".to_string()), docs_layout);
+
+        let app = Router::new()
+            .route("/", get(handler))
+            .nest(
+                "/docs",
+                Router::new()
+                    .route("/intro", get(handler))
+                    .layer(InertiaScopeLayer::new(docs)),
+            )
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/docs/intro", &addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.headers().get("X-Inertia-Version").unwrap(), "docs");
+        assert!(res.text().await.unwrap().contains(r#"class="docs""#));
+
+        let res = client
+            .get(format!("http://{}/", &addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.headers().get("X-Inertia-Version").unwrap(), "app");
+    }
+}
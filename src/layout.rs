@@ -0,0 +1,224 @@
+//! Html layouts read from template files.
+//!
+//! [FileLayout] renders the initial page from an html file, so the
+//! root html can be edited without recompiling. The file marks where
+//! the Inertia app goes with an `@inertia` placeholder, like Laravel's
+//! blade directive:
+//!
+//! ```html
+//! <!DOCTYPE html>
+//! <html>
+//!   <head>
+//!     <script type="module" src="http://localhost:5173/src/main.ts"></script>
+//!   </head>
+//!   <body>
+//!     @inertia
+//!   </body>
+//! </html>
+//! ```
+//!
+//! The placeholder is replaced with `<div id="app" data-page="..."></div>`.
+//! With [hot_reload](FileLayout::hot_reload), the file is read again
+//! whenever its modification time changes:
+//!
+//! ```rust,no_run
+//! use axum_inertia::layout::FileLayout;
+//!
+//! let inertia = FileLayout::new("templates/app.html")
+//!     .expect("couldn't read layout")
+//!     .hot_reload(cfg!(debug_assertions))
+//!     .into_config();
+//! ```
+
+use crate::config::InertiaConfig;
+use crate::html::escape_json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+/// The placeholder replaced with the Inertia app element.
+pub const PLACEHOLDER: &str = "@inertia";
+
+/// A layout read from an html file with an `@inertia` placeholder.
+#[derive(Clone)]
+pub struct FileLayout {
+    path: PathBuf,
+    version: Option<String>,
+    hot_reload: bool,
+    template: Arc<RwLock<Template>>,
+}
+
+struct Template {
+    html: String,
+    modified: Option<SystemTime>,
+}
+
+impl Template {
+    fn read(path: &Path) -> Result<Template, LayoutError> {
+        let html = fs::read_to_string(path).map_err(LayoutError::Io)?;
+        if !html.contains(PLACEHOLDER) {
+            return Err(LayoutError::PlaceholderMissing);
+        }
+        Ok(Template {
+            html,
+            modified: modified(path),
+        })
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+impl FileLayout {
+    /// Reads the layout at `path`.
+    ///
+    /// Fails if the file can't be read or has no `@inertia`
+    /// placeholder.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, LayoutError> {
+        let path = path.into();
+        let template = Template::read(&path)?;
+        Ok(FileLayout {
+            path,
+            version: None,
+            hot_reload: false,
+            template: Arc::new(RwLock::new(template)),
+        })
+    }
+
+    /// Sets the asset version. See
+    /// [InertiaConfig::version](crate::InertiaConfig::version).
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Reads the file again on render when its modification time has
+    /// changed. Meant for development; defaults to false.
+    ///
+    /// If the changed file can't be read or has no placeholder, the
+    /// error is logged and the previous layout is kept.
+    pub fn hot_reload(mut self, hot_reload: bool) -> Self {
+        self.hot_reload = hot_reload;
+        self
+    }
+
+    /// Renders the layout with the serialized page object.
+    pub fn render(&self, page: &str) -> String {
+        if self.hot_reload {
+            self.reload_if_modified();
+        }
+        let app = format!(
+            r#"<div id="app" data-page="{}"></div>"#,
+            escape_json(page).replace('"', "&quot;")
+        );
+        let template = self.template.read().unwrap_or_else(|e| e.into_inner());
+        template.html.replacen(PLACEHOLDER, &app, 1)
+    }
+
+    fn reload_if_modified(&self) {
+        let modified = modified(&self.path);
+        {
+            let template = self.template.read().unwrap_or_else(|e| e.into_inner());
+            if modified == template.modified {
+                return;
+            }
+        }
+        match Template::read(&self.path) {
+            Ok(template) => {
+                *self.template.write().unwrap_or_else(|e| e.into_inner()) = template;
+            }
+            Err(err) => {
+                tracing::error!(path = %self.path.display(), error = %err, "couldn't reload inertia layout");
+                self.template
+                    .write()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .modified = modified;
+            }
+        }
+    }
+
+    pub fn into_config(self) -> InertiaConfig {
+        let version = self.version.clone();
+        InertiaConfig::new(version, Box::new(move |page| self.render(&page)))
+    }
+}
+
+#[derive(Debug)]
+pub enum LayoutError {
+    Io(std::io::Error),
+    PlaceholderMissing,
+}
+
+impl std::fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(_) => write!(f, "couldn't read layout file"),
+            Self::PlaceholderMissing => write!(f, "layout missing {} placeholder", PLACEHOLDER),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_layout(name: &str, html: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "axum-inertia-layout-{}-{}.html",
+            std::process::id(),
+            name
+        ));
+        fs::write(&path, html).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_renders_placeholder() {
+        let path = write_layout("render", "<body>@inertia</body>");
+        let layout = FileLayout::new(&path).unwrap();
+        assert_eq!(
+            layout.render(r#"{"component":"</script>"}"#),
+            r#"<body><div id="app" data-page="{&quot;component&quot;:&quot;\u003c/script\u003e&quot;}"></div></body>"#
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_requires_placeholder() {
+        let path = write_layout("missing", "<body></body>");
+        assert!(matches!(
+            FileLayout::new(&path),
+            Err(LayoutError::PlaceholderMissing)
+        ));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_hot_reload() {
+        let path = write_layout("reload", "<p>@inertia</p>");
+        let layout = FileLayout::new(&path).unwrap().hot_reload(true);
+        assert!(layout.render("{}").starts_with("<p>"));
+
+        fs::write(&path, "<main>@inertia</main>").unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(layout.render("{}").starts_with("<main>"));
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -138,26 +138,143 @@
 //! [Extractor]: https://docs.rs/axum/latest/axum/#extractors
 
 use async_trait::async_trait;
+use axum::body::{Body, Bytes};
 use axum::extract::{FromRef, FromRequestParts};
+use axum::response::IntoResponse;
 pub use config::InertiaConfig;
+use config::{ConflictAction, PartialMismatch};
+use error_page::RenderError;
+use futures_util::future;
+use http::header::{CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_TYPE, LOCATION};
 use http::{request::Parts, HeaderMap, HeaderValue, StatusCode};
-use page::Page;
-use props::Props;
-use request::Request;
-use response::Response;
+pub use into_inertia::{IntoInertia, PageResponse};
+use negotiate::{Negotiate, Negotiated};
+use page::{Modal, Page, RawPage};
+use partial::Partial;
+use prefix::ComponentPrefix;
+use props::{Props, Timed};
+use rejection::InertiaRejection;
+use render_guard::RenderGuard;
+pub use request::Request;
+pub use response::ResponseParts;
+use response::{RawResponse, Response};
+use serde_json::{Map, Value};
+use server_timing::ServerTiming;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
+#[cfg(feature = "axum-login")]
+pub mod auth;
+pub mod bfcache;
+pub mod clock;
 pub mod config;
+#[cfg(all(test, feature = "conformance"))]
+mod conformance;
+pub mod cors;
+#[cfg(feature = "debug")]
+pub mod debug;
+pub mod defer;
+pub mod error_page;
+pub mod errors;
+pub mod flash;
+pub mod guard;
+pub mod head;
+pub mod hints;
+pub mod html;
+pub mod into_inertia;
+pub mod layer;
+pub mod layout;
+#[cfg(feature = "live")]
+pub mod live;
+pub mod maintenance;
+pub mod method_override;
+pub mod negotiate;
+pub mod normalize;
+#[cfg(feature = "otel")]
+pub mod otel;
 mod page;
+pub mod page_store;
 pub mod partial;
+pub mod prefix;
 pub mod props;
+pub mod protocol;
+pub mod provider;
+pub mod rejection;
+mod render_guard;
 mod request;
+pub mod request_id;
 mod response;
+pub mod routing;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod serializer;
+mod server_timing;
+pub mod shell;
+pub mod strict;
+#[cfg(feature = "test-helpers")]
+pub mod test_helpers;
+#[cfg(feature = "typegen")]
+pub mod typegen;
+pub mod version;
 pub mod vite;
 
+// Allows derive macros to refer to `::axum_inertia` from within this
+// crate's own tests.
+#[cfg(any(feature = "derive", feature = "typegen"))]
+extern crate self as axum_inertia;
+
+/// Turns an async fn returning [Props], or a `Result` of props, into a
+/// handler that renders a component:
+///
+/// ```rust,ignore
+/// use axum::extract::State;
+/// use axum_inertia::{inertia, props::Props};
+/// use serde_json::json;
+///
+/// #[inertia("Pages/Home")]
+/// async fn home(State(db): State<Db>) -> Result<impl Props, AppError> {
+///     let posts = db.recent_posts().await?;
+///     Ok(json!({ "posts": posts }))
+/// }
+/// ```
+///
+/// The handler extracts [Inertia] before its other arguments, so it
+/// works wherever `Inertia` does. Errors are returned with their own
+/// [IntoResponse] implementation, and partial reloads are handled by
+/// the props as with [Inertia::render]. See
+/// [render_result](Inertia::render_result).
+///
+/// Requires the `derive` feature.
+#[cfg(feature = "derive")]
+pub use axum_inertia_macros::inertia;
+
+// Paths used by the `inertia` macro.
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use axum::response::Response;
+}
+
 #[derive(Clone)]
 pub struct Inertia {
     request: Request,
     config: InertiaConfig,
+    component_prefix: Option<ComponentPrefix>,
+    deferred: Vec<String>,
+    flash: Option<flash::FlashData>,
+    auth: Option<Value>,
+    request_id: Option<request_id::RequestId>,
+    params: Option<Map<String, Value>>,
+    provided: Vec<(String, Value)>,
+    // Shared with clones, as props errors can't be cloned.
+    provider_error: Option<Arc<props::PropsError>>,
+    // Set when props were resolved for the partial reload before
+    // render, which knows whether it's for the rendered component.
+    partial_resolved: Arc<AtomicBool>,
+    guard: RenderGuard,
 }
 
 #[async_trait]
@@ -166,62 +283,899 @@ where
     S: Send + Sync,
     InertiaConfig: FromRef<S>,
 {
-    type Rejection = (StatusCode, HeaderMap<HeaderValue>);
+    type Rejection = InertiaRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let config = InertiaConfig::from_ref(state);
+        Inertia::from_parts(parts, config).await
+    }
+}
+
+/// Extracts [Inertia] in routers without state, using the config
+/// inserted by an [InertiaLayer](layer::InertiaLayer).
+#[async_trait]
+impl FromRequestParts<()> for Inertia {
+    type Rejection = InertiaRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _: &()) -> Result<Self, Self::Rejection> {
+        let config = parts
+            .extensions
+            .get::<InertiaConfig>()
+            .cloned()
+            .ok_or(InertiaRejection::MissingConfig)?;
+        Inertia::from_parts(parts, config).await
+    }
+}
+
+/// Extracts [Inertia] for Inertia requests only, for routes shared
+/// between the Inertia app and classic server-rendered pages:
+///
+/// ```rust
+/// use axum::response::{Html, IntoResponse};
+/// use axum_inertia::InertiaOrHtml;
+/// use serde_json::json;
+///
+/// async fn about(page: InertiaOrHtml) -> impl IntoResponse {
+///     match page {
+///         InertiaOrHtml::Inertia(i) => i.render("About", json!({})).into_response(),
+///         InertiaOrHtml::Html => Html("<h1>About</h1>").into_response(),
+///     }
+/// }
+/// ```
+///
+/// Requests without an `X-Inertia: true` header never fail
+/// extraction, e.g. on an asset version mismatch. Responses differ by
+/// the header, so shared caches should vary on it.
+pub enum InertiaOrHtml {
+    /// An Inertia request. Boxed, as the extractor is much larger than
+    /// the other variant.
+    Inertia(Box<Inertia>),
+    /// Any other request, e.g. a full page load.
+    Html,
+}
+
+impl InertiaOrHtml {
+    async fn from_parts(
+        parts: &Parts,
+        config: InertiaConfig,
+    ) -> Result<InertiaOrHtml, InertiaRejection> {
+        let names = match parts.extensions.get::<layer::ScopedConfig>() {
+            Some(scoped) => scoped.0.header_names_ref(),
+            None => config.header_names_ref(),
+        };
+        // Like Request::is_xhr, only `X-Inertia: true` is an Inertia
+        // request.
+        let is_xhr = parts
+            .headers
+            .get(&names.inertia)
+            .is_some_and(|value| value == "true");
+        if !is_xhr {
+            return Ok(InertiaOrHtml::Html);
+        }
+        Inertia::from_parts(parts, config)
+            .await
+            .map(|inertia| InertiaOrHtml::Inertia(Box::new(inertia)))
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for InertiaOrHtml
+where
+    S: Send + Sync,
+    InertiaConfig: FromRef<S>,
+{
+    type Rejection = InertiaRejection;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let config = InertiaConfig::from_ref(state);
-        let request = Request::from_request_parts(parts, state).await?;
+        InertiaOrHtml::from_parts(parts, config).await
+    }
+}
+
+/// Extracts [InertiaOrHtml] in routers without state, using the
+/// config inserted by an [InertiaLayer](layer::InertiaLayer).
+#[async_trait]
+impl FromRequestParts<()> for InertiaOrHtml {
+    type Rejection = InertiaRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _: &()) -> Result<Self, Self::Rejection> {
+        let config = parts
+            .extensions
+            .get::<InertiaConfig>()
+            .cloned()
+            .ok_or(InertiaRejection::MissingConfig)?;
+        InertiaOrHtml::from_parts(parts, config).await
+    }
+}
+
+/// Returns the version conflict rejection for a request.
+fn version_conflict(parts: &Parts, config: &InertiaConfig) -> InertiaRejection {
+    let base_url = config
+        .uses_conflict_full_url()
+        .then(|| request::base_url(parts, config))
+        .flatten();
+    let location = format!("{}{}", base_url.unwrap_or_default(), parts.uri.path());
+    InertiaRejection::VersionConflict {
+        location,
+        body: config.conflict_body_kind(),
+        header: config.header_names_ref().location.clone(),
+    }
+}
+
+impl Inertia {
+    pub(crate) async fn from_parts(
+        parts: &Parts,
+        config: InertiaConfig,
+    ) -> Result<Inertia, InertiaRejection> {
+        let config = match parts.extensions.get::<layer::ScopedConfig>() {
+            Some(scoped) => scoped.0.clone(),
+            None => config,
+        };
+        let mut request = Request::from_parts_with_names(
+            parts,
+            config.header_names_ref(),
+            config.invalid_headers_kind(),
+        )?;
+        if config.uses_absolute_urls() {
+            if let Some(base_url) = request::base_url(parts, &config) {
+                request.url = format!("{}{}", base_url, request.url);
+            }
+        }
+        if config.uses_query_partials() && !request.is_xhr && request.partial.is_none() {
+            // The component is filled in on render.
+            request.partial = request
+                .uri
+                .query()
+                .and_then(partial::parse_partial_query)
+                .map(|props| Partial {
+                    props,
+                    component: String::new(),
+                });
+        }
 
         // Respond with a 409 conflict if X-Inertia-Version values
-        // don't match for GET requests. See more at:
+        // don't match for GET requests (by default). See more at:
         // https://inertiajs.com/the-protocol#asset-versioning
-        if parts.method == "GET"
-            && request.is_xhr
+        if request.is_xhr
             && config.version().is_some()
             && request.version != config.version()
+            && config.conflict_policy_kind().conflicts(&request)
+            && config.resolve_version_conflict(&request) == ConflictAction::Conflict
         {
-            let mut headers = HeaderMap::new();
-            headers.insert("X-Inertia-Location", parts.uri.path().parse().unwrap());
-            return Err((StatusCode::CONFLICT, headers));
+            return Err(version_conflict(parts, &config));
         }
 
-        Ok(Inertia::new(request, config))
+        let mut inertia = Inertia::new(request, config);
+        inertia.component_prefix = parts.extensions.get::<ComponentPrefix>().cloned();
+        inertia.flash = parts
+            .extensions
+            .get::<flash::Flash>()
+            .and_then(flash::Flash::incoming);
+        inertia.request_id = parts.extensions.get::<request_id::RequestId>().cloned();
+        #[cfg(feature = "axum-login")]
+        {
+            inertia.auth = inertia
+                .config
+                .auth_user_resolver()
+                .map(|resolve| resolve(parts));
+        }
+        if inertia.config.shares_params() {
+            inertia.params = Some(request::params(parts).await);
+        }
+        // Providers run concurrently, skipping those whose prop a
+        // partial reload didn't ask for.
+        let provided = future::join_all(
+            inertia
+                .config
+                .prop_providers()
+                .iter()
+                .filter(
+                    |provider| match (provider.key(), &inertia.request.partial) {
+                        (Some(key), Some(partial)) => partial.props.iter().any(|p| p == key),
+                        _ => true,
+                    },
+                )
+                .map(|provider| provider.provide(&inertia.request)),
+        )
+        .await;
+        for result in provided {
+            match result {
+                Ok(prop) => inertia.provided.push(prop),
+                Err(err) => match inertia.config.prop_errors_mode() {
+                    config::PropErrors::Strict => {
+                        inertia.provider_error.get_or_insert(Arc::new(err));
+                    }
+                    config::PropErrors::Lenient => {
+                        tracing::error!(error = %err, "skipping provided inertia prop");
+                    }
+                },
+            }
+        }
+        Ok(inertia)
     }
-}
 
-impl Inertia {
     fn new(request: Request, config: InertiaConfig) -> Inertia {
-        Inertia { request, config }
+        let guard = RenderGuard::new(&request.url);
+        Inertia {
+            request,
+            config,
+            component_prefix: None,
+            deferred: Vec::new(),
+            flash: None,
+            auth: None,
+            request_id: None,
+            params: None,
+            provided: Vec::new(),
+            provider_error: None,
+            partial_resolved: Arc::default(),
+            guard,
+        }
+    }
+
+    /// Returns the full component name, with any [ComponentPrefix]
+    /// applied.
+    fn component_name<'a>(&self, component: &'a str) -> Cow<'a, str> {
+        match &self.component_prefix {
+            Some(prefix) => Cow::Owned(prefix.apply(component)),
+            None => Cow::Borrowed(component),
+        }
+    }
+
+    /// Responds with a file download.
+    ///
+    /// The response is a plain (non-Inertia) response with a
+    /// `Content-Disposition: attachment` header, even if the request
+    /// is an Inertia request, since downloads bypass the protocol.
+    /// `body` can be anything convertible into an
+    /// [axum::body::Body], e.g. bytes, a string, or a stream via
+    /// [axum::body::Body::from_stream].
+    ///
+    /// ```rust
+    /// use axum_inertia::Inertia;
+    /// use axum::response::IntoResponse;
+    ///
+    /// async fn export(i: Inertia) -> impl IntoResponse {
+    ///     i.download("id,name\n1,mikey\n", "users.csv")
+    /// }
+    /// ```
+    pub fn download(self, body: impl Into<Body>, filename: &str) -> axum::response::Response {
+        self.guard.rendered();
+        let ascii: String = filename
+            .chars()
+            .map(|c| match c {
+                '"' | '\\' => '_',
+                c if c.is_ascii() && !c.is_ascii_control() => c,
+                _ => '_',
+            })
+            .collect();
+        let mut disposition = format!(r#"attachment; filename="{}""#, ascii);
+        if ascii != filename {
+            let encoded =
+                percent_encoding::utf8_percent_encode(filename, percent_encoding::NON_ALPHANUMERIC);
+            disposition.push_str(&format!("; filename*=UTF-8''{}", encoded));
+        }
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/octet-stream"),
+        );
+        match HeaderValue::from_str(&disposition) {
+            Ok(value) => headers.insert(CONTENT_DISPOSITION, value),
+            Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        };
+        (headers, body.into()).into_response()
+    }
+
+    /// Redirects to `uri`, with `303 See Other` after `PUT`, `PATCH`
+    /// and `DELETE` requests so the client follows with a `GET`, and
+    /// `302 Found` otherwise. See
+    /// [redirect_status](protocol::redirect_status).
+    ///
+    /// ```rust
+    /// use axum_inertia::Inertia;
+    /// use axum::response::IntoResponse;
+    ///
+    /// async fn update_user(i: Inertia) -> impl IntoResponse {
+    ///     i.redirect("/users/1")
+    /// }
+    /// ```
+    pub fn redirect(self, uri: &str) -> axum::response::Response {
+        self.guard.rendered();
+        (
+            protocol::redirect_status(self.request.method()),
+            [(LOCATION, protocol::url_header_value(uri))],
+        )
+            .into_response()
+    }
+
+    /// Leaves the app with a `POST` of `params` to the external `url`,
+    /// e.g. to start an OAuth or SAML flow, or to log out of an
+    /// identity provider.
+    ///
+    /// Initial page loads get a small html page with a form that
+    /// submits itself. Inertia requests can't follow a form, so they
+    /// get `409 Conflict` with an `X-Inertia-Location` of the current
+    /// url, query included: the client visits it again as a full page
+    /// load with `GET`, which gets the form. The route must therefore
+    /// answer `GET` requests with the same call, e.g. with the same
+    /// handler:
+    ///
+    /// ```rust
+    /// use axum_inertia::{vite, Inertia};
+    /// use axum::{response::IntoResponse, routing::get, Router};
+    ///
+    /// async fn logout(i: Inertia) -> impl IntoResponse {
+    ///     i.external_redirect_post(
+    ///         "https://idp.example.com/logout",
+    ///         &[("id_token_hint", "..."), ("state", "...")],
+    ///     )
+    /// }
+    ///
+    /// let app: Router = Router::new()
+    ///     .route("/logout", get(logout).post(logout))
+    ///     .with_state(vite::Development::default().into_config());
+    /// ```
+    pub fn external_redirect_post(
+        self,
+        url: &str,
+        params: &[(&str, &str)],
+    ) -> axum::response::Response {
+        self.guard.rendered();
+        if self.request.is_xhr {
+            let header = self.config.header_names_ref().location.clone();
+            let location = match self.request.uri.path_and_query() {
+                Some(path_and_query) => path_and_query.as_str(),
+                None => self.request.uri.path(),
+            };
+            let location = protocol::url_header_value(location);
+            return (protocol::CONFLICT, [(header, location)]).into_response();
+        }
+        let page = maud::html! {
+            (maud::DOCTYPE)
+            html {
+                head { meta charset="utf-8"; title { "Redirecting…" } }
+                body {
+                    form method="post" action=(url) {
+                        @for (name, value) in params {
+                            input type="hidden" name=(name) value=(value);
+                        }
+                        noscript { button type="submit" { "Continue" } }
+                    }
+                    script { (maud::PreEscaped("document.forms[0].submit();")) }
+                }
+            }
+        };
+        (
+            [(CACHE_CONTROL, HeaderValue::from_static("no-store"))],
+            axum::response::Html(page.into_string()),
+        )
+            .into_response()
+    }
+
+    /// Renders the props of `result`, or responds with its error.
+    ///
+    /// ```rust
+    /// use axum_inertia::Inertia;
+    /// use axum::response::IntoResponse;
+    /// use http::StatusCode;
+    /// use serde_json::json;
+    ///
+    /// async fn home(i: Inertia) -> impl IntoResponse {
+    ///     let result: Result<_, StatusCode> = Ok(json!({ "posts": [] }));
+    ///     i.render_result("Home", result)
+    /// }
+    /// ```
+    pub fn render_result<S, E>(
+        self,
+        component: &str,
+        result: Result<S, E>,
+    ) -> axum::response::Response
+    where
+        S: Props,
+        E: IntoResponse,
+    {
+        match result {
+            Ok(props) => self.render(component, props).into_response(),
+            Err(err) => {
+                self.guard.rendered();
+                err.into_response()
+            }
+        }
+    }
+
+    /// Returns the Inertia request information.
+    pub fn request(&self) -> &Request {
+        &self.request
+    }
+
+    /// Returns the request headers, e.g. for computing a locale or
+    /// theme prop without another extractor.
+    ///
+    /// ```rust
+    /// use axum_inertia::Inertia;
+    /// use axum::response::IntoResponse;
+    /// use serde_json::json;
+    ///
+    /// async fn home(i: Inertia) -> impl IntoResponse {
+    ///     let locale = i
+    ///         .headers()
+    ///         .get("Accept-Language")
+    ///         .and_then(|v| v.to_str().ok())
+    ///         .unwrap_or("en")
+    ///         .to_string();
+    ///     i.render("Home", json!({ "locale": locale }))
+    /// }
+    /// ```
+    pub fn headers(&self) -> &HeaderMap {
+        self.request.headers()
+    }
+
+    /// Returns the [ClientHints](hints::ClientHints) sent with the
+    /// request, e.g. to pick a theme on the first render.
+    ///
+    /// ```rust
+    /// use axum_inertia::{hints::ColorScheme, Inertia};
+    /// use axum::response::IntoResponse;
+    /// use serde_json::json;
+    ///
+    /// async fn home(i: Inertia) -> impl IntoResponse {
+    ///     let dark = i.hints().prefers_color_scheme == Some(ColorScheme::Dark);
+    ///     i.render("Home", json!({ "dark": dark }))
+    /// }
+    /// ```
+    pub fn hints(&self) -> hints::ClientHints {
+        hints::ClientHints::from_headers(self.request.headers())
+    }
+
+    /// Returns whether the request comes from a mobile or a desktop
+    /// browser. See [DeviceClass::from_headers](hints::DeviceClass::from_headers).
+    ///
+    /// ```rust
+    /// use axum_inertia::{hints::DeviceClass, Inertia};
+    /// use axum::response::IntoResponse;
+    /// use serde_json::json;
+    ///
+    /// async fn home(i: Inertia) -> impl IntoResponse {
+    ///     let mobile = i.device_class() == DeviceClass::Mobile;
+    ///     i.render("Home", json!({ "compact": mobile }))
+    /// }
+    /// ```
+    pub fn device_class(&self) -> hints::DeviceClass {
+        hints::DeviceClass::from_headers(self.request.headers())
+    }
+
+    /// Returns the original request uri, including the query string.
+    pub fn uri(&self) -> &http::Uri {
+        self.request.uri()
+    }
+
+    /// Returns the request method.
+    pub fn method(&self) -> &http::Method {
+        self.request.method()
+    }
+
+    /// Resolves a [Timed] prop named `key`.
+    ///
+    /// Returns the output of the prop's future if it completes within
+    /// the timeout. Otherwise, returns the fallback and marks `key` as
+    /// deferred in the rendered page object, so the client fetches it
+    /// with a partial reload. When `key` is explicitly requested in a
+    /// partial reload, the future is awaited without a timeout.
+    pub async fn resolve_timed<F: Future>(&mut self, key: &str, timed: Timed<F>) -> F::Output {
+        let requested = self
+            .resolving_partial()
+            .is_some_and(|partial| partial.props.iter().any(|p| p == key));
+        if requested {
+            return timed.future.await;
+        }
+
+        match tokio::time::timeout(timed.timeout, timed.future).await {
+            Ok(output) => output,
+            Err(_) => {
+                self.deferred.push(key.to_string());
+                timed.fallback
+            }
+        }
     }
 
     /// Renders an Inertia response.
-    pub fn render<S: Props>(self, component: &str, props: S) -> Response {
-        let request = self.request;
+    pub fn render<S: Props>(self, component: &str, props: S) -> Response<'_> {
+        self.guard.rendered();
+        let component = self.component_name(component);
+        let mut request = self.request;
+        if let Some(partial) = &mut request.partial {
+            if partial.component.is_empty() {
+                partial.component = component.to_string();
+            }
+        }
+        let mut conflict_location = None;
+        if let Some(partial) = request
+            .partial
+            .take_if(|partial| partial.component != component)
+        {
+            tracing::info!(
+                partial_component = partial.component,
+                component = %component,
+                "partial reload of a component other than the rendered one"
+            );
+            // Props resolved before render were filtered for the other
+            // component, so a full response would miss some.
+            if self.config.partial_mismatch_kind() == PartialMismatch::Conflict
+                || self.partial_resolved.load(Ordering::Relaxed)
+            {
+                conflict_location = Some(request.url.clone());
+            }
+        }
+        if let Some(partial) = &request.partial {
+            for hook in self.config.partial_reload_hooks() {
+                hook(&component, partial);
+            }
+        }
         let url = request.url.clone();
+        let mut timing = ServerTiming::default();
+        let props = timing.measure("inertia-props", || {
+            props.serialize(request.partial.as_ref())
+        });
+        let props = match self.provider_error {
+            Some(err) => Err(Arc::try_unwrap(err).unwrap_or_else(props::PropsError::new)),
+            None => props,
+        };
+        let (mut props, error) = match props {
+            Ok(props) => (props, None),
+            Err(err) => (Value::Object(Map::new()), Some(RenderError::new(err))),
+        };
+        let mut defaults = self.config.default_props().clone();
+        if let Some(component_defaults) = self.config.component_defaults(&component) {
+            defaults.extend(component_defaults.clone());
+        }
+        if let Some(flash) = self.flash {
+            if !flash.messages.is_empty() {
+                defaults.insert("flash".to_string(), Value::Object(flash.messages));
+            }
+            if !flash.errors.is_empty() {
+                defaults.insert("errors".to_string(), Value::Object(flash.errors));
+            }
+        }
+        if let Some(auth) = self.auth {
+            defaults.insert("auth".to_string(), auth);
+        }
+        if let Some(id) = self.request_id {
+            defaults.insert("requestId".to_string(), Value::String(id.to_string()));
+        }
+        if let Some(params) = self.params {
+            defaults.insert("params".to_string(), Value::Object(params));
+        }
+        defaults.extend(self.provided);
+        let mut defaults = Value::Object(defaults);
+        if let Some(case) = self.config.props_case_kind() {
+            props = case.convert_keys(props);
+            defaults = case.convert_keys(defaults);
+        }
+        // Serializable props ignore the partial, and no client filters
+        // the page of a query partial, so keep only its requested keys.
+        if let (false, Some(partial), Value::Object(props)) =
+            (request.is_xhr, &request.partial, &mut props)
+        {
+            props.retain(|key, _| partial.props.contains(key));
+        }
+        if let (Value::Object(props), Value::Object(defaults)) = (&mut props, defaults) {
+            for (key, value) in defaults {
+                let requested = match &request.partial {
+                    Some(partial) => partial.props.contains(&key),
+                    None => true,
+                };
+                if requested && !props.contains_key(&key) {
+                    props.insert(key, value);
+                }
+            }
+        }
         let page = Page {
             component,
-            props: props
-                .serialize(request.partial.as_ref())
-                // TODO: error handling
-                .expect("serialization failure"),
+            props,
             url,
             version: self.config.version().clone(),
+            modal: None,
+            deferred_props: if self.deferred.is_empty() {
+                None
+            } else {
+                Some(BTreeMap::from([("default".to_string(), self.deferred)]))
+            },
+            extra: Map::new(),
         };
 
         Response {
             page,
             request,
             config: self.config,
+            status: StatusCode::OK,
+            timing,
+            error,
+            conflict_location,
+        }
+    }
+
+    /// Renders an Inertia response, serializing large page objects on
+    /// a blocking thread. See
+    /// [InertiaConfig::blocking_serialization].
+    ///
+    /// ```rust
+    /// use axum_inertia::Inertia;
+    /// use axum::response::IntoResponse;
+    /// use serde_json::json;
+    ///
+    /// async fn report(i: Inertia) -> impl IntoResponse {
+    ///     let rows: Vec<u32> = (0..100_000).collect();
+    ///     i.render_async("Report", json!({ "rows": rows })).await
+    /// }
+    /// ```
+    pub async fn render_async<S: Props>(
+        self,
+        component: &str,
+        props: S,
+    ) -> axum::response::Response {
+        self.render(component, props).into_response_async().await
+    }
+
+    /// Renders an Inertia response with the given status, e.g. a not
+    /// found or validation error page.
+    ///
+    /// ```rust
+    /// use axum_inertia::Inertia;
+    /// use axum::response::IntoResponse;
+    /// use http::StatusCode;
+    /// use serde_json::json;
+    ///
+    /// async fn missing(i: Inertia) -> impl IntoResponse {
+    ///     i.render_with_status("Errors/NotFound", json!({}), StatusCode::NOT_FOUND)
+    /// }
+    /// ```
+    pub fn render_with_status<S: Props>(
+        self,
+        component: &str,
+        props: S,
+        status: StatusCode,
+    ) -> Response<'_> {
+        let mut response = self.render(component, props);
+        response.status = status;
+        response
+    }
+
+    /// Responds with the page object to Inertia requests, the props
+    /// alone to clients preferring `application/json`, and the html
+    /// page otherwise. See the [negotiate] module.
+    pub fn negotiate<'a, S: Props>(self, component: &'a str, props: S) -> Negotiate<'a> {
+        if self.request.is_xhr || !negotiate::prefers_json(self.request.headers()) {
+            return Negotiate(Negotiated::Page(Box::new(self.render(component, props))));
+        }
+        self.guard.rendered();
+        match props.serialize(None) {
+            Ok(props) => {
+                let page = Page {
+                    component: self.component_name(component),
+                    props,
+                    url: self.request.url.clone(),
+                    version: self.config.version().clone(),
+                    modal: None,
+                    deferred_props: None,
+                    extra: Map::new(),
+                };
+                Negotiate(Negotiated::Json(Box::new(Response {
+                    request: self.request,
+                    page,
+                    config: self.config,
+                    status: StatusCode::OK,
+                    timing: ServerTiming::default(),
+                    error: None,
+                    conflict_location: None,
+                })))
+            }
+            Err(err) => {
+                tracing::error!(error = %err, "failed to serialize props");
+                Negotiate(Negotiated::Error)
+            }
+        }
+    }
+
+    /// Resolves a [props::Map] for this request, handling failures of
+    /// individual props according to the configured
+    /// [PropErrors](config::PropErrors) mode.
+    ///
+    /// ```rust
+    /// use axum_inertia::{props, Inertia};
+    /// use axum::response::IntoResponse;
+    ///
+    /// async fn dashboard(i: Inertia) -> impl IntoResponse {
+    ///     let map = props::Map::new().value("title", "Dashboard");
+    ///     let props = i.resolve_map(map).await.unwrap();
+    ///     i.render("Dashboard", props)
+    /// }
+    /// ```
+    pub async fn resolve_map(&self, map: props::Map) -> Result<Value, props::PropsError> {
+        map.resolve_partial(self.resolving_partial(), self.config.prop_errors_mode())
+            .await
+    }
+
+    /// Returns the partial reload props are resolved for before
+    /// render, remembering that they were.
+    fn resolving_partial(&self) -> Option<&Partial> {
+        let partial = self.request.partial.as_ref()?;
+        self.partial_resolved.store(true, Ordering::Relaxed);
+        Some(partial)
+    }
+
+    /// Renders anything implementing [IntoInertia], e.g. a `(component,
+    /// props)` tuple or a [PageResponse].
+    ///
+    /// ```rust
+    /// use axum_inertia::Inertia;
+    /// use axum::response::IntoResponse;
+    /// use serde_json::json;
+    ///
+    /// async fn users(i: Inertia) -> impl IntoResponse {
+    ///     i.respond(("Users/Index", json!({ "users": ["mikey"] })))
+    /// }
+    /// ```
+    pub fn respond(self, page: impl IntoInertia) -> axum::response::Response {
+        let page = page.into_page_response();
+        self.render(&page.component, page.props).into_response()
+    }
+
+    /// Renders an Inertia response with props that are already
+    /// serialized to json, e.g. from a cache or another service.
+    ///
+    /// The props are validated but not otherwise parsed, so this skips
+    /// building a [Value] and serializing it again. As a consequence,
+    /// shared props, [props_case](InertiaConfig::props_case) and
+    /// [response hooks](InertiaConfig::on_response) are not applied,
+    /// and partial reloads receive all props. Responds with `500
+    /// Internal Server Error` if `raw_json` is not valid json.
+    ///
+    /// ```rust
+    /// use axum_inertia::Inertia;
+    /// use axum::body::Bytes;
+    /// use axum::response::IntoResponse;
+    ///
+    /// async fn report(i: Inertia) -> impl IntoResponse {
+    ///     let cached = Bytes::from_static(br#"{"rows":[1,2,3]}"#);
+    ///     i.render_raw("Report", cached)
+    /// }
+    /// ```
+    pub fn render_raw(self, component: &str, raw_json: Bytes) -> axum::response::Response {
+        self.guard.rendered();
+        let props = match serde_json::from_slice(&raw_json) {
+            Ok(props) => props,
+            Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        };
+        let page = RawPage {
+            component: self.component_name(component),
+            props,
+            url: self.request.url.clone(),
+            version: self.config.version(),
+        };
+        RawResponse {
+            page,
+            request: self.request,
+            config: self.config,
+        }
+        .into_response()
+    }
+
+    /// Responds with cached [ResponseParts], as the json page object
+    /// for Inertia requests or the html layout otherwise.
+    ///
+    /// ```rust
+    /// use axum_inertia::{Inertia, ResponseParts};
+    /// use axum::response::IntoResponse;
+    /// use serde_json::json;
+    /// use std::sync::OnceLock;
+    ///
+    /// static HOME: OnceLock<ResponseParts> = OnceLock::new();
+    ///
+    /// async fn home(i: Inertia) -> axum::response::Response {
+    ///     if let Some(parts) = HOME.get() {
+    ///         return i.render_parts(parts);
+    ///     }
+    ///     match i.render("Home", json!({ "posts": [] })).into_parts() {
+    ///         Ok(parts) => HOME.get_or_init(|| parts).into_response(),
+    ///         Err(_) => http::StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    ///     }
+    /// }
+    /// ```
+    pub fn render_parts(&self, parts: &ResponseParts) -> axum::response::Response {
+        self.guard.rendered();
+        parts.respond(self.request.is_xhr)
+    }
+
+    /// Renders an Inertia response that displays `component` in a
+    /// modal over the page at `base_url`.
+    ///
+    /// On Inertia requests the page object includes a `modal` field
+    /// describing the modal component and base page. Initial page
+    /// loads have no base page to render over, so they fall back to a
+    /// normal [render](Inertia::render) of the component.
+    ///
+    /// ```rust
+    /// use axum_inertia::Inertia;
+    /// use axum::response::IntoResponse;
+    /// use serde_json::json;
+    ///
+    /// async fn show_user(i: Inertia) -> impl IntoResponse {
+    ///     i.render_modal("Users/Show", json!({ "id": 1 }), "/users")
+    /// }
+    /// ```
+    pub fn render_modal<'a, S: Props>(
+        self,
+        component: &'a str,
+        props: S,
+        base_url: &str,
+    ) -> Response<'a> {
+        let is_xhr = self.request.is_xhr;
+        let mut response = self.render(component, props);
+        if is_xhr {
+            response.page.modal = Some(Modal {
+                component: response.page.component.clone(),
+                base_url: base_url.to_string(),
+            });
+        }
+        response
+    }
+
+    /// Renders a minimal Inertia response for polling.
+    ///
+    /// Inertia requests are treated as partial reloads of the `only`
+    /// props (unless the client already requested a partial reload),
+    /// and only those props are included in the page object. The
+    /// response is marked `Cache-Control: no-store` so that polled
+    /// data is never served stale. Initial page loads are rendered
+    /// normally.
+    ///
+    /// ```rust
+    /// use axum_inertia::Inertia;
+    /// use axum::response::IntoResponse;
+    /// use serde_json::json;
+    ///
+    /// async fn dashboard(i: Inertia) -> impl IntoResponse {
+    ///     let props = json!({ "stats": { "users": 10 }, "settings": {} });
+    ///     i.render_poll("Dashboard", props, &["stats"])
+    /// }
+    /// ```
+    pub fn render_poll<'a, S: Props>(
+        mut self,
+        component: &'a str,
+        props: S,
+        only: &[&str],
+    ) -> impl IntoResponse + 'a {
+        let mut headers = HeaderMap::new();
+        if !self.request.is_xhr {
+            return (headers, self.render(component, props));
         }
+
+        let full_component = self.component_name(component).into_owned();
+        let partial = self.request.partial.get_or_insert_with(|| Partial {
+            props: only.iter().map(|s| s.to_string()).collect(),
+            component: full_component,
+        });
+        let only = partial.props.clone();
+        let mut response = self.render(component, props);
+        if let Value::Object(props) = &mut response.page.props {
+            props.retain(|key, _| only.contains(key));
+        }
+
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        (headers, response)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use axum::{self, response::IntoResponse, routing::get, Router};
+    use crate::config::ConflictBody;
+    use axum::{self, response::IntoResponse, routing::get, Extension, Router};
     use reqwest::StatusCode;
     use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
     use tokio::net::TcpListener;
 
     #[tokio::test]
@@ -261,19 +1215,87 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn it_responds_with_conflict_on_version_mismatch() {
+    async fn it_renders_raw_props() {
         async fn handler(i: Inertia) -> impl IntoResponse {
-            i.render("foo!", json!({"bar": "baz"}))
+            i.render_raw("Report", Bytes::from_static(br#"{"rows": [1, 2]}"#))
+        }
+
+        async fn invalid(i: Inertia) -> impl IntoResponse {
+            i.render_raw("Report", Bytes::from_static(b"{"))
         }
 
         let layout =
             Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+        let config = InertiaConfig::new(Some("123".to_string()), layout);
 
-        let inertia = InertiaConfig::new(Some("123".to_string()), layout);
+        let app = Router::new()
+            .route("/report", get(handler))
+            .route("/invalid", get(invalid))
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/report", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Version", "123")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.headers().get("X-Inertia").unwrap(), "true");
+        assert_eq!(
+            res.text().await.unwrap(),
+            r#"{"component":"Report","props":{"rows": [1, 2]},"url":"/report","version":"123"}"#
+        );
+
+        let res = client
+            .get(format!("http://{}/report", &addr))
+            .send()
+            .await
+            .unwrap();
+        assert!(res
+            .text()
+            .await
+            .unwrap()
+            .contains(r#"data-page='{"component":"Report","props":{"rows": [1, 2]}"#));
+
+        let res = client
+            .get(format!("http://{}/invalid", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Version", "123")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 500);
+    }
+
+    #[tokio::test]
+    async fn it_responds_with_into_inertia_values() {
+        async fn tuple(i: Inertia) -> impl IntoResponse {
+            i.respond(("Tuple", json!({"a": 1})))
+        }
+
+        async fn page_response(i: Inertia) -> impl IntoResponse {
+            i.respond(PageResponse::new(String::from("Page"), json!({"b": 2})))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+        let config = InertiaConfig::new(None, layout);
 
         let app = Router::new()
-            .route("/test", get(handler))
-            .with_state(inertia);
+            .route("/tuple", get(tuple))
+            .route("/page", get(page_response))
+            .with_state(config);
 
         let listener = TcpListener::bind("127.0.0.1:0")
             .await
@@ -287,19 +1309,1236 @@ mod tests {
         let client = reqwest::Client::new();
 
         let res = client
-            .get(format!("http://{}/test", &addr))
+            .get(format!("http://{}/tuple", &addr))
             .header("X-Inertia", "true")
-            .header("X-Inertia-Version", "456")
             .send()
             .await
             .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["component"], "Tuple");
+        assert_eq!(page["props"]["a"], 1);
 
-        assert_eq!(res.status(), StatusCode::CONFLICT);
+        let res = client
+            .get(format!("http://{}/page", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["component"], "Page");
+        assert_eq!(page["props"]["b"], 2);
+    }
+
+    #[tokio::test]
+    async fn it_renders_modals() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render_modal("Users/Show", json!({"id": 1}), "/users")
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+
+        let config = InertiaConfig::new(None, layout);
+
+        let app = Router::new()
+            .route("/users/1", get(handler))
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/users/1", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["component"], "Users/Show");
         assert_eq!(
-            res.headers()
-                .get("X-Inertia-Location")
-                .map(|h| h.to_str().unwrap()),
-            Some("/test")
+            page["modal"],
+            json!({"component": "Users/Show", "baseUrl": "/users"})
         );
+
+        let res = client
+            .get(format!("http://{}/users/1", &addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.text().await.unwrap();
+        assert!(body.contains(r#""component":"Users/Show""#));
+        assert!(!body.contains("modal"));
+    }
+
+    #[tokio::test]
+    async fn it_renders_poll_responses() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render_poll(
+                "Dashboard",
+                json!({"stats": 1, "settings": 2, "users": 3}),
+                &["stats"],
+            )
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+
+        let config = InertiaConfig::new(None, layout);
+
+        let app = Router::new()
+            .route("/dashboard", get(handler))
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/dashboard", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.headers().get("Cache-Control").unwrap(), "no-store");
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["props"], json!({"stats": 1}));
+
+        let res = client
+            .get(format!("http://{}/dashboard", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Partial-Component", "Dashboard")
+            .header("X-Inertia-Partial-Data", "users")
+            .send()
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["props"], json!({"users": 3}));
+
+        let res = client
+            .get(format!("http://{}/dashboard", &addr))
+            .send()
+            .await
+            .unwrap();
+        assert!(res.headers().get("Cache-Control").is_none());
+        let body = res.text().await.unwrap();
+        assert!(body.contains(r#""settings":2"#));
+    }
+
+    #[tokio::test]
+    async fn it_defers_timed_out_props() {
+        async fn handler(mut i: Inertia) -> impl IntoResponse {
+            let fast = Timed::new(Duration::from_secs(5), async { 1 }, 0);
+            let slow = Timed::new(
+                Duration::from_millis(10),
+                async {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    2
+                },
+                0,
+            );
+            let fast = i.resolve_timed("fast", fast).await;
+            let slow = i.resolve_timed("slow", slow).await;
+            i.render("Dashboard", json!({"fast": fast, "slow": slow}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+
+        let config = InertiaConfig::new(None, layout);
+
+        let app = Router::new()
+            .route("/dashboard", get(handler))
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/dashboard", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["props"], json!({"fast": 1, "slow": 0}));
+        assert_eq!(page["deferredProps"], json!({"default": ["slow"]}));
+
+        let res = client
+            .get(format!("http://{}/dashboard", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Partial-Component", "Dashboard")
+            .header("X-Inertia-Partial-Data", "slow")
+            .send()
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["props"]["slow"], 2);
+        assert!(page.get("deferredProps").is_none());
+    }
+
+    #[tokio::test]
+    async fn it_responds_with_downloads() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.download("a,b\n", "résumé \"final\".csv")
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+
+        let config = InertiaConfig::new(Some("123".to_string()), layout);
+
+        let app = Router::new()
+            .route("/export", get(handler))
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/export", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Version", "123")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().get("X-Inertia").is_none());
+        assert_eq!(
+            res.headers().get("Content-Disposition").unwrap(),
+            r#"attachment; filename="r_sum_ _final_.csv"; filename*=UTF-8''r%C3%A9sum%C3%A9%20%22final%22%2Ecsv"#
+        );
+        assert_eq!(res.text().await.unwrap(), "a,b\n");
+    }
+
+    #[tokio::test]
+    async fn it_applies_component_prefixes() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render("Users/Index", json!({}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+
+        let config = InertiaConfig::new(None, layout);
+
+        let admin = Router::new()
+            .route("/users", get(handler))
+            .layer(Extension(ComponentPrefix::new("Admin")));
+        let app = Router::new()
+            .route("/users", get(handler))
+            .nest("/admin", admin)
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        for (path, component) in [
+            ("/users", "Users/Index"),
+            ("/admin/users", "Admin/Users/Index"),
+        ] {
+            let res = client
+                .get(format!("http://{}{}", &addr, path))
+                .header("X-Inertia", "true")
+                .send()
+                .await
+                .unwrap();
+            let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+            assert_eq!(page["component"], component);
+        }
+    }
+
+    #[tokio::test]
+    async fn it_uses_absolute_urls_when_configured() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render("Dashboard", json!({}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+
+        let config = InertiaConfig::new(None, layout).absolute_urls(true);
+
+        let app = Router::new()
+            .route("/dashboard", get(handler))
+            .with_state(config.clone().trust_proxy(true))
+            .route("/untrusted", get(handler))
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/dashboard", &addr))
+            .header("X-Inertia", "true")
+            .header("Host", "acme.example.com:8443")
+            .header("X-Forwarded-Proto", "https")
+            .send()
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["url"], "https://acme.example.com:8443/dashboard");
+
+        let res = client
+            .get(format!("http://{}/untrusted", &addr))
+            .header("X-Inertia", "true")
+            .header("Host", "evil.example.com")
+            .send()
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["url"], "/untrusted");
+    }
+
+    #[cfg(feature = "derive")]
+    #[tokio::test]
+    async fn it_renders_inertia_handlers() {
+        #[inertia("Pages/Post")]
+        async fn post(
+            axum::extract::Path(id): axum::extract::Path<u32>,
+        ) -> Result<impl Props, http::StatusCode> {
+            if id == 0 {
+                return Err(http::StatusCode::NOT_FOUND);
+            }
+            Ok(json!({ "id": id }))
+        }
+
+        #[inertia("Pages/Home")]
+        async fn home() -> impl Props {
+            json!({ "title": "Home" })
+        }
+
+        let app = Router::new()
+            .route("/", get(home))
+            .route("/posts/:id", get(post))
+            .with_state(InertiaConfig::new(None, Box::new(|props| props)));
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/posts/1", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["component"], "Pages/Post");
+        assert_eq!(page["props"], json!({ "id": 1 }));
+
+        let res = client
+            .get(format!("http://{}/posts/0", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let res = client
+            .get(format!("http://{}/", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["props"], json!({ "title": "Home" }));
+    }
+
+    #[tokio::test]
+    async fn it_reads_the_version_from_a_watch_channel() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render("Home", json!({}))
+        }
+
+        let (tx, rx) = tokio::sync::watch::channel("v1".to_string());
+        let config = InertiaConfig::new(None, Box::new(|props| props)).version_watch(rx);
+
+        let app = Router::new().route("/", get(handler)).with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+        let visit = || {
+            client
+                .get(format!("http://{}/", &addr))
+                .header("X-Inertia", "true")
+                .header("X-Inertia-Version", "v1")
+                .send()
+        };
+
+        let res = visit().await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["x-inertia-version"], "v1");
+
+        tx.send("v2".to_string()).unwrap();
+        let res = visit().await.unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn it_extracts_inertia_or_html() {
+        async fn handler(page: InertiaOrHtml) -> axum::response::Response {
+            match page {
+                InertiaOrHtml::Inertia(i) => i.render("About", json!({})).into_response(),
+                InertiaOrHtml::Html => axum::response::Html("<h1>About</h1>").into_response(),
+            }
+        }
+
+        let config = InertiaConfig::new(Some("v1".to_string()), Box::new(|props| props));
+        let app = Router::new()
+            .route("/about", get(handler))
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/about", &addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.text().await.unwrap(), "<h1>About</h1>");
+
+        let res = client
+            .get(format!("http://{}/about", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Version", "v1")
+            .send()
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["component"], "About");
+
+        let res = client
+            .get(format!("http://{}/about", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Version", "v0")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+
+        let res = client
+            .get(format!("http://{}/about", &addr))
+            .header("X-Inertia", "false")
+            .header("X-Inertia-Version", "v0")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.text().await.unwrap(), "<h1>About</h1>");
+    }
+
+    #[tokio::test]
+    async fn it_handles_partial_component_mismatches() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render("Users/Show", json!({"user": 1, "posts": 2}))
+        }
+
+        async fn resolved(i: Inertia) -> impl IntoResponse {
+            let map = props::Map::new().value("user", 1).value("posts", 2);
+            let props = i.resolve_map(map).await.unwrap();
+            i.render("Users/Show", props)
+        }
+
+        let config = InertiaConfig::new(None, Box::new(|props| props));
+        let app = Router::new()
+            .route("/full", get(handler))
+            .route("/resolved", get(resolved))
+            .with_state(config.clone())
+            .merge(
+                Router::new()
+                    .route("/conflict", get(handler))
+                    .with_state(config.partial_mismatch(PartialMismatch::Conflict)),
+            );
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+        let reload = |path: &str| {
+            client
+                .get(format!("http://{}{}", &addr, path))
+                .header("X-Inertia", "true")
+                .header("X-Inertia-Partial-Component", "Users/Index")
+                .header("X-Inertia-Partial-Data", "posts")
+                .send()
+        };
+
+        let res = reload("/full").await.unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["props"], json!({"user": 1, "posts": 2}));
+
+        // The props were resolved for the partial reload of Users/Index,
+        // so the full response would miss `user`.
+        let res = reload("/resolved").await.unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+        assert_eq!(res.headers()["x-inertia-location"], "/resolved");
+
+        let res = reload("/conflict").await.unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+        assert_eq!(res.headers()["x-inertia-location"], "/conflict");
+    }
+
+    #[tokio::test]
+    async fn it_shares_route_params() {
+        let app = Router::new()
+            .route(
+                "/users/:id",
+                get(|i: Inertia| async move { i.render("Users/Show", json!({})) }),
+            )
+            .with_state(
+                InertiaConfig::new(None, Box::new(|props| props.to_string())).share_params(true),
+            );
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let res = reqwest::Client::new()
+            .get(format!("http://{}/users/1?tab=posts&id=2", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        let page: Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(
+            page["props"]["params"],
+            json!({ "id": "1", "tab": "posts" })
+        );
+    }
+
+    #[tokio::test]
+    async fn it_sanitizes_html_props() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render(
+                "Post",
+                json!({"title": "<b>t</b>", "body": props::Html("<p>hi<script></script></p>".to_string())}),
+            )
+        }
+
+        let config = InertiaConfig::new(None, Box::new(|props| props))
+            .html_sanitizer(|html| html.replace("<script></script>", ""));
+
+        let app = Router::new()
+            .route("/post", get(handler))
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let res = reqwest::Client::new()
+            .get(format!("http://{}/post", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(
+            page["props"],
+            json!({"title": "<b>t</b>", "body": "<p>hi</p>"})
+        );
+    }
+
+    #[tokio::test]
+    async fn it_merges_default_props() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render("Dashboard", json!({"env": "handler", "stats": 1}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+
+        let config = InertiaConfig::new(None, layout)
+            .share("appName", "My app")
+            .share("env", "production")
+            .defaults(
+                "Dashboard",
+                json!({"refreshInterval": 30, "env": "dashboard"}),
+            )
+            .defaults("Other", json!({"other": true}));
+
+        let app = Router::new()
+            .route("/dashboard", get(handler))
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/dashboard", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(
+            page["props"],
+            json!({"appName": "My app", "env": "handler", "refreshInterval": 30, "stats": 1})
+        );
+
+        let res = client
+            .get(format!("http://{}/dashboard", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Partial-Component", "Dashboard")
+            .header("X-Inertia-Partial-Data", "stats")
+            .send()
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert!(page["props"].get("appName").is_none());
+    }
+
+    #[tokio::test]
+    async fn it_shares_props_when_a_header_matches() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render("Dashboard", json!({"stats": 1}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+
+        let config = InertiaConfig::new(None, layout).share_when(props::When::header(
+            "X-Feature-Flags",
+            props::contains("beta"),
+            json!({"charts": []}),
+        ));
+
+        let app = Router::new()
+            .route("/dashboard", get(handler))
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/dashboard", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Feature-Flags", "beta")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.headers().get("vary").unwrap(), "x-feature-flags");
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["props"], json!({"stats": 1, "charts": []}));
+
+        let res = client
+            .get(format!("http://{}/dashboard", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["props"], json!({"stats": 1}));
+    }
+
+    #[tokio::test]
+    async fn it_redirects() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.redirect("/users")
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+        let config = InertiaConfig::new(None, layout);
+
+        let app = Router::new()
+            .route("/users/1", get(handler).put(handler))
+            .route(
+                "/users/unicode",
+                get(|i: Inertia| async move { i.redirect("/users/josé martí") }),
+            )
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap();
+
+        let res = client
+            .put(format!("http://{}/users/1", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::SEE_OTHER);
+        assert_eq!(res.headers().get("location").unwrap(), "/users");
+
+        let res = client
+            .get(format!("http://{}/users/unicode", &addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FOUND);
+        assert_eq!(
+            res.headers().get("location").unwrap(),
+            "/users/jos%C3%A9%20mart%C3%AD"
+        );
+
+        let res = client
+            .get(format!("http://{}/users/1", &addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FOUND);
+    }
+
+    #[tokio::test]
+    async fn it_redirects_externally_with_a_post() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.external_redirect_post("https://idp.example.com/logout", &[("state", "a&b")])
+        }
+
+        let app = Router::new()
+            .route("/logout", get(handler).post(handler))
+            .with_state(InertiaConfig::new(None, Box::new(|props| props)));
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .post(format!("http://{}/logout?from=menu", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+        let location = res.headers()["x-inertia-location"].to_str().unwrap();
+        assert_eq!(location, "/logout?from=menu");
+
+        // The client follows up with a full page visit, which must get
+        // the form from the same route.
+        let res = client
+            .get(format!("http://{}{}", &addr, location))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["cache-control"], "no-store");
+        let body = res.text().await.unwrap();
+        assert!(body.contains(r#"<form method="post" action="https://idp.example.com/logout">"#));
+        assert!(body.contains(r#"<input type="hidden" name="state" value="a&amp;b">"#));
+    }
+
+    #[tokio::test]
+    async fn it_converts_props_case() {
+        #[derive(serde::Serialize)]
+        struct Post {
+            post_title: String,
+        }
+
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            let posts = vec![Post {
+                post_title: "Hello".to_string(),
+            }];
+            i.render("Posts", json!({"all_posts": posts, "_private": 1}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+
+        let config = InertiaConfig::new(None, layout)
+            .share("current_user", json!({"user_name": "mikey"}))
+            .props_case(config::Case::Camel);
+
+        let app = Router::new()
+            .route("/posts", get(handler))
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let res = reqwest::Client::new()
+            .get(format!("http://{}/posts", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(
+            page["props"],
+            json!({
+                "allPosts": [{"postTitle": "Hello"}],
+                "_private": 1,
+                "currentUser": {"userName": "mikey"}
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn it_runs_partial_reload_hooks() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render("Dashboard", json!({"stats": 1, "users": []}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+        let reloads = Arc::new(Mutex::new(Vec::new()));
+        let recorder = reloads.clone();
+        let config =
+            InertiaConfig::new(None, layout).on_partial_reload(move |component, partial| {
+                recorder
+                    .lock()
+                    .unwrap()
+                    .push((component.to_string(), partial.props.clone()));
+            });
+
+        let app = Router::new()
+            .route("/dashboard", get(handler))
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+        let url = format!("http://{}/dashboard", &addr);
+
+        client
+            .get(&url)
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        client
+            .get(&url)
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Partial-Component", "Dashboard")
+            .header("X-Inertia-Partial-Data", "stats")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *reloads.lock().unwrap(),
+            vec![("Dashboard".to_string(), vec!["stats".to_string()])]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_filters_props_with_the_partial_query() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render("Dashboard", json!({"stats": 1, "users": []}))
+        }
+
+        let layout = Box::new(|props| format!("<html>{}</html>", props));
+        let config = InertiaConfig::new(None, layout).query_partials(true);
+
+        let app = Router::new()
+            .route("/dashboard", get(handler))
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let res = reqwest::get(format!("http://{}/dashboard?partial=stats", &addr))
+            .await
+            .expect("request failed");
+        let body = res.text().await.unwrap();
+        assert!(body.contains(r#""props":{"stats":1}"#), "{}", body);
+    }
+
+    #[tokio::test]
+    async fn it_responds_with_conflict_on_version_mismatch() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render("foo!", json!({"bar": "baz"}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+
+        let inertia = InertiaConfig::new(Some("123".to_string()), layout);
+
+        let app = Router::new()
+            .route("/test", get(handler))
+            .with_state(inertia);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/test", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Version", "456")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+        assert_eq!(
+            res.headers()
+                .get("X-Inertia-Location")
+                .map(|h| h.to_str().unwrap()),
+            Some("/test")
+        );
+    }
+
+    #[tokio::test]
+    async fn it_responds_with_a_configured_conflict_body() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render("foo!", json!({"bar": "baz"}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+
+        let inertia = InertiaConfig::new(Some("123".to_string()), layout)
+            .conflict_full_url(true)
+            .conflict_body(ConflictBody::Json);
+
+        let app = Router::new()
+            .route("/test", get(handler))
+            .with_state(inertia.clone().origin("https://example.com/"))
+            .route("/relative", get(handler))
+            .with_state(inertia);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/test", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Version", "456")
+            .header("Host", "evil.example.com")
+            .header("X-Forwarded-Proto", "http")
+            .send()
+            .await
+            .unwrap();
+
+        let location = "https://example.com/test";
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+        assert_eq!(
+            res.headers()
+                .get("X-Inertia-Location")
+                .map(|h| h.to_str().unwrap()),
+            Some(location)
+        );
+        let body: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(body["location"], location);
+
+        let res = client
+            .get(format!("http://{}/relative", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Version", "456")
+            .header("Host", "evil.example.com")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.headers()["X-Inertia-Location"], "/relative");
+    }
+
+    #[tokio::test]
+    async fn it_runs_version_conflict_hooks() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render("foo!", json!({"bar": "baz"}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+
+        let conflicts = Arc::new(Mutex::new(Vec::new()));
+        let recorder = conflicts.clone();
+        let inertia = InertiaConfig::new(Some("123".to_string()), layout).on_version_conflict(
+            move |request| {
+                let version = request.version().unwrap_or_default().to_string();
+                recorder.lock().unwrap().push(version.clone());
+                if version == "122" {
+                    ConflictAction::Bypass
+                } else {
+                    ConflictAction::Conflict
+                }
+            },
+        );
+
+        let app = Router::new()
+            .route("/test", get(handler))
+            .with_state(inertia);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/test", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Version", "122")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = client
+            .get(format!("http://{}/test", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Version", "456")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+
+        assert_eq!(*conflicts.lock().unwrap(), vec!["122", "456"]);
+    }
+
+    #[tokio::test]
+    async fn it_applies_the_conflict_policy() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render("foo!", json!({"bar": "baz"}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+        let config = InertiaConfig::new(Some("123".to_string()), layout);
+
+        let app = Router::new()
+            .route("/get-only", axum::routing::post(handler))
+            .with_state(config.clone())
+            .merge(
+                Router::new()
+                    .route("/all-methods", axum::routing::post(handler))
+                    .with_state(config.conflict_policy(config::ConflictPolicy::AllMethods)),
+            );
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .post(format!("http://{}/get-only", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Version", "456")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = client
+            .post(format!("http://{}/all-methods", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Version", "456")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn it_uses_configured_header_names() {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render("foo!", json!({"bar": "baz"}))
+        }
+
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+        let inertia = InertiaConfig::new(Some("123".to_string()), layout).header_names(
+            protocol::HeaderNames {
+                inertia: http::HeaderName::from_static("x-app-inertia"),
+                version: http::HeaderName::from_static("x-app-version"),
+                ..Default::default()
+            },
+        );
+
+        let app = Router::new()
+            .route("/test", get(handler))
+            .with_state(inertia);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/test", &addr))
+            .header("X-App-Inertia", "true")
+            .header("X-App-Version", "123")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get("X-App-Inertia").unwrap(), "true");
+        assert_eq!(res.headers().get("X-App-Version").unwrap(), "123");
+        assert!(res.headers().get("X-Inertia-Version").is_none());
+
+        let res = client
+            .get(format!("http://{}/test", &addr))
+            .header("X-App-Inertia", "true")
+            .header("X-App-Version", "456")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+        assert_eq!(res.headers().get("X-Inertia-Location").unwrap(), "/test");
     }
 }
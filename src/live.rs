@@ -0,0 +1,168 @@
+//! Server-sent prop invalidation, available with the `live` feature.
+//!
+//! A [PropInvalidator] is a cheap, cloneable handle that handlers can
+//! use to announce that some props have changed. Clients connected to
+//! its server-sent events endpoint receive an `invalidate` event
+//! listing the changed props, and can refresh them with a standard
+//! Inertia partial reload.
+//!
+//! ```rust
+//! use axum::{extract::State, routing::post, Router};
+//! use axum_inertia::live::PropInvalidator;
+//!
+//! let invalidator = PropInvalidator::new();
+//!
+//! let app: Router = Router::new()
+//!     .route(
+//!         "/posts",
+//!         post(|State(invalidator): State<PropInvalidator>| async move {
+//!             // ... create the post ...
+//!             invalidator.touch("posts");
+//!         }),
+//!     )
+//!     .with_state(invalidator.clone())
+//!     .merge(invalidator.router());
+//! ```
+//!
+//! On the client:
+//!
+//! ```js
+//! const events = new EventSource("/_inertia/live");
+//! events.addEventListener("invalidate", (event) => {
+//!   router.reload({ only: JSON.parse(event.data).props });
+//! });
+//! events.addEventListener("reload", () => router.reload());
+//! ```
+//!
+//! Clients that fall behind by more than 64 invalidations miss some of
+//! them, and get a `reload` event instead, telling them to reload all
+//! props.
+
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::{routing::get, Router};
+use futures_util::stream::{self, Stream};
+use std::convert::Infallible;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// The number of invalidations buffered for slow clients before they
+/// start missing events.
+const CAPACITY: usize = 64;
+
+/// Handle for notifying connected clients that props have changed.
+#[derive(Clone)]
+pub struct PropInvalidator {
+    sender: broadcast::Sender<Vec<String>>,
+}
+
+impl Default for PropInvalidator {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        PropInvalidator { sender }
+    }
+}
+
+impl PropInvalidator {
+    pub fn new() -> PropInvalidator {
+        Self::default()
+    }
+
+    /// Tells connected clients to reload `prop`.
+    pub fn touch(&self, prop: &str) {
+        self.touch_all(&[prop]);
+    }
+
+    /// Tells connected clients to reload all of `props`.
+    pub fn touch_all(&self, props: &[&str]) {
+        let props = props.iter().map(|p| p.to_string()).collect();
+        // Sending only fails if there are no connected clients.
+        let _ = self.sender.send(props);
+    }
+
+    /// Returns the number of connected clients.
+    pub fn client_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Returns a server-sent events response streaming invalidations
+    /// from now on.
+    pub fn sse(&self) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+        let receiver = self.sender.subscribe();
+        let events = stream::unfold(receiver, |mut receiver| async move {
+            let event = match receiver.recv().await {
+                Ok(props) => Event::default()
+                    .event("invalidate")
+                    .json_data(serde_json::json!({ "props": props }))
+                    .expect("props serialize to json"),
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!(missed, "live client lagged, sending a reload event");
+                    Event::default().event("reload").data("{}")
+                }
+                Err(RecvError::Closed) => return None,
+            };
+            Some((Ok(event), receiver))
+        });
+        Sse::new(events).keep_alive(KeepAlive::default())
+    }
+
+    /// Returns a router serving the events endpoint at
+    /// `/_inertia/live`.
+    pub fn router<S>(&self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let invalidator = self.clone();
+        Router::new().route(
+            "/_inertia/live",
+            get(move || async move { invalidator.sse() }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn it_streams_invalidations() {
+        let invalidator = PropInvalidator::new();
+        let app: Router = invalidator.router();
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let mut res = reqwest::get(format!("http://{}/_inertia/live", &addr))
+            .await
+            .unwrap();
+        assert_eq!(
+            res.headers().get("Content-Type").unwrap(),
+            "text/event-stream"
+        );
+
+        while invalidator.client_count() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        invalidator.touch_all(&["posts", "count"]);
+
+        let chunk = res.chunk().await.unwrap().unwrap();
+        let chunk = String::from_utf8(chunk.to_vec()).unwrap();
+        assert!(chunk.contains("event: invalidate"));
+        assert!(chunk.contains(r#"data: {"props":["posts","count"]}"#));
+
+        // The test runtime is single-threaded, so the stream can't keep
+        // up until the loop is done.
+        for _ in 0..=CAPACITY {
+            invalidator.touch("posts");
+        }
+        let chunk = res.chunk().await.unwrap().unwrap();
+        let chunk = String::from_utf8(chunk.to_vec()).unwrap();
+        assert!(chunk.contains("event: reload"));
+    }
+}
@@ -0,0 +1,235 @@
+//! Maintenance mode, togglable at runtime.
+//!
+//! While maintenance mode is enabled, [MaintenanceModeLayer] responds
+//! to every request with an [ErrorPage], `503 Service Unavailable` by
+//! default, except those let through by its
+//! [bypass](MaintenanceModeLayer::bypass) predicate. Toggle it with the
+//! [MaintenanceMode] handle, e.g. from a signal handler, or from an
+//! admin endpoint that bypasses maintenance mode:
+//!
+//! ```rust
+//! use axum::{routing::{get, post}, Router};
+//! use axum_inertia::maintenance::{MaintenanceMode, MaintenanceModeLayer};
+//!
+//! let maintenance = MaintenanceMode::new();
+//! let toggle = maintenance.clone();
+//!
+//! let app: Router = Router::new()
+//!     .route("/", get(|| async { "hello" }))
+//!     .route(
+//!         "/admin/maintenance",
+//!         post(move || async move {
+//!             if toggle.is_enabled() {
+//!                 toggle.disable()
+//!             } else {
+//!                 toggle.enable()
+//!             }
+//!         }),
+//!     )
+//!     .layer(
+//!         MaintenanceModeLayer::new(maintenance)
+//!             .bypass(|parts| parts.uri.path().starts_with("/admin/")),
+//!     );
+//! ```
+//!
+//! Protect bypassed routes like any admin route, e.g. with
+//! authentication: the bypass only skips the maintenance page.
+
+use crate::config::InvalidHeaders;
+use crate::error_page::ErrorPage;
+use crate::protocol::HeaderNames;
+use crate::request::Request;
+use axum::response::{IntoResponse, Response};
+use futures_util::future::{self, Either, Ready};
+use http::request::Parts;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A cloneable handle for toggling maintenance mode.
+#[derive(Clone, Debug, Default)]
+pub struct MaintenanceMode {
+    enabled: Arc<AtomicBool>,
+}
+
+impl MaintenanceMode {
+    /// Constructs a new handle, with maintenance mode disabled.
+    pub fn new() -> MaintenanceMode {
+        Self::default()
+    }
+
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+}
+
+type Bypass = dyn Fn(&Parts) -> bool + Send + Sync;
+
+/// Layer that applies [MaintenanceModeService] to a service.
+#[derive(Clone)]
+pub struct MaintenanceModeLayer {
+    mode: MaintenanceMode,
+    page: ErrorPage,
+    names: HeaderNames,
+    bypass: Option<Arc<Bypass>>,
+}
+
+impl MaintenanceModeLayer {
+    pub fn new(mode: MaintenanceMode) -> MaintenanceModeLayer {
+        MaintenanceModeLayer {
+            mode,
+            page: ErrorPage::service_unavailable(),
+            names: HeaderNames::default(),
+            bypass: None,
+        }
+    }
+
+    /// Lets requests for which `bypass` returns true through while
+    /// maintenance mode is enabled, e.g. by path, header or client
+    /// address (from the
+    /// [ConnectInfo](axum::extract::ConnectInfo) extension).
+    pub fn bypass<F>(mut self, bypass: F) -> Self
+    where
+        F: Fn(&Parts) -> bool + Send + Sync + 'static,
+    {
+        self.bypass = Some(Arc::new(bypass));
+        self
+    }
+
+    /// Sets the page rendered while maintenance mode is enabled.
+    pub fn page(mut self, page: ErrorPage) -> Self {
+        self.page = page;
+        self
+    }
+
+    /// Reads and writes the Inertia headers with the given names. See
+    /// [InertiaConfig::header_names](crate::InertiaConfig::header_names).
+    pub fn header_names(mut self, names: HeaderNames) -> Self {
+        self.names = names;
+        self
+    }
+}
+
+impl<S> Layer<S> for MaintenanceModeLayer {
+    type Service = MaintenanceModeService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MaintenanceModeService {
+            inner,
+            mode: self.mode.clone(),
+            page: self.page.clone(),
+            names: self.names.clone(),
+            bypass: self.bypass.clone(),
+        }
+    }
+}
+
+/// Service that responds with an error page while maintenance mode is
+/// enabled.
+#[derive(Clone)]
+pub struct MaintenanceModeService<S> {
+    inner: S,
+    mode: MaintenanceMode,
+    page: ErrorPage,
+    names: HeaderNames,
+    bypass: Option<Arc<Bypass>>,
+}
+
+impl<S, B> Service<http::Request<B>> for MaintenanceModeService<S>
+where
+    S: Service<http::Request<B>, Response = Response>,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Either<Ready<Result<Response, S::Error>>, S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        if !self.mode.is_enabled() {
+            return Either::Right(self.inner.call(req));
+        }
+
+        let (parts, body) = req.into_parts();
+        if self.bypass.as_ref().is_some_and(|bypass| bypass(&parts)) {
+            return Either::Right(self.inner.call(http::Request::from_parts(parts, body)));
+        }
+        let response =
+            match Request::from_parts_with_names(&parts, &self.names, InvalidHeaders::Reject) {
+                Ok(request) => self.page.render(&request),
+                Err(rejection) => rejection.into_response(),
+            };
+        Either::Left(future::ok(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn it_toggles_maintenance_mode() {
+        let maintenance = MaintenanceMode::new();
+        let app = Router::new()
+            .route("/test", get(|| async { "ok" }))
+            .route("/admin", get(|| async { "admin" }))
+            .layer(
+                MaintenanceModeLayer::new(maintenance.clone())
+                    .bypass(|parts| parts.uri.path() == "/admin"),
+            );
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+        let url = format!("http://{}/test", &addr);
+
+        let res = client.get(&url).send().await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+        maintenance.enable();
+        let res = client.get(&url).send().await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert!(res.text().await.unwrap().contains("down for maintenance"));
+
+        let res = client
+            .get(&url)
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers().get("X-Inertia").unwrap(), "true");
+
+        let res = client
+            .get(format!("http://{}/admin", &addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+        maintenance.disable();
+        let res = client.get(&url).send().await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::OK);
+    }
+}
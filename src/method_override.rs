@@ -0,0 +1,352 @@
+//! Method spoofing for Inertia form submissions.
+//!
+//! Browsers can only `POST` forms, so Inertia form helpers commonly
+//! send a `_method` field instead, e.g. a `POST` with
+//! `_method=DELETE`. [InertiaMethodOverrideLayer]
+//! rewrites the request method before routing, so that such requests
+//! reach `delete`, `put` and `patch` handlers.
+//!
+//! The override is read from, in order:
+//!
+//! - the `X-HTTP-Method-Override` header,
+//! - a `_method` query parameter,
+//! - a `_method` field in a json or url-encoded form body.
+//!
+//! Only `POST` requests are rewritten, and only to `PUT`, `PATCH` or
+//! `DELETE`.
+//!
+//! Multipart bodies are not read, so file uploads must send the
+//! override in the header or the query string, e.g. `POST
+//! /posts/1?_method=PUT`.
+//!
+//! The layer must wrap the whole router, since routing happens before
+//! any layer added with [axum::Router::layer] is run:
+//!
+//! ```rust
+//! use axum::{routing::delete, Router, ServiceExt};
+//! use axum_inertia::method_override::InertiaMethodOverrideLayer;
+//! use tower_layer::Layer;
+//!
+//! let app: Router = Router::new().route("/posts/1", delete(|| async { "deleted" }));
+//! let app = InertiaMethodOverrideLayer::new().layer(app);
+//!
+//! # async {
+//! let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+//! axum::serve(listener, app.into_make_service());
+//! # };
+//! ```
+
+use axum::body::Body;
+use axum::response::{IntoResponse, Response};
+use http::{header::CONTENT_TYPE, HeaderName, Method, Request, StatusCode};
+use http_body_util::LengthLimitError;
+use serde::Deserialize;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Header used to override the request method.
+pub const X_HTTP_METHOD_OVERRIDE: HeaderName = HeaderName::from_static("x-http-method-override");
+
+/// The default limit, in bytes, on bodies read for a `_method` field.
+pub const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Layer that applies [MethodOverride] to a service.
+#[derive(Clone, Debug)]
+pub struct InertiaMethodOverrideLayer {
+    body_limit: usize,
+}
+
+impl Default for InertiaMethodOverrideLayer {
+    fn default() -> Self {
+        InertiaMethodOverrideLayer {
+            body_limit: DEFAULT_BODY_LIMIT,
+        }
+    }
+}
+
+impl InertiaMethodOverrideLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum size of json or form bodies read when looking
+    /// for a `_method` field. Larger bodies are rejected with `413
+    /// Payload Too Large`.
+    pub fn body_limit(mut self, body_limit: usize) -> Self {
+        self.body_limit = body_limit;
+        self
+    }
+}
+
+impl<S> Layer<S> for InertiaMethodOverrideLayer {
+    type Service = MethodOverride<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MethodOverride {
+            inner,
+            body_limit: self.body_limit,
+        }
+    }
+}
+
+/// Service that rewrites spoofed `POST` requests. See the [module
+/// documentation](self) for more.
+#[derive(Clone, Debug)]
+pub struct MethodOverride<S> {
+    inner: S,
+    body_limit: usize,
+}
+
+impl<S> Service<Request<Body>> for MethodOverride<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // Take the service that was driven to readiness, leaving a
+        // clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let body_limit = self.body_limit;
+
+        Box::pin(async move {
+            if req.method() != Method::POST {
+                return inner.call(req).await;
+            }
+
+            let (mut parts, body) = req.into_parts();
+            let mut method = parts
+                .headers
+                .get(X_HTTP_METHOD_OVERRIDE)
+                .and_then(|v| v.to_str().ok())
+                .map(|s| s.to_string())
+                .or_else(|| parts.uri.query().and_then(method_from_query));
+
+            let body = match (&method, body_kind(&parts.headers)) {
+                (None, Some(kind)) => {
+                    let bytes = match axum::body::to_bytes(body, body_limit).await {
+                        Ok(bytes) => bytes,
+                        Err(err) if is_length_limit(&err) => {
+                            return Ok(StatusCode::PAYLOAD_TOO_LARGE.into_response())
+                        }
+                        Err(_) => return Ok(StatusCode::BAD_REQUEST.into_response()),
+                    };
+                    method = match kind {
+                        BodyKind::Json => method_from_json(&bytes),
+                        BodyKind::Form => method_from_query(&String::from_utf8_lossy(&bytes)),
+                    };
+                    Body::from(bytes)
+                }
+                _ => body,
+            };
+
+            if let Some(method) = method.as_deref().and_then(parse_override) {
+                parts.method = method;
+            }
+
+            inner.call(Request::from_parts(parts, body)).await
+        })
+    }
+}
+
+/// Whether reading the body failed because it's over the limit, rather
+/// than e.g. the client disconnecting.
+fn is_length_limit(err: &axum::Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(err) = source {
+        if err.is::<LengthLimitError>() {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+enum BodyKind {
+    Json,
+    Form,
+}
+
+fn body_kind(headers: &http::HeaderMap) -> Option<BodyKind> {
+    let content_type = headers.get(CONTENT_TYPE)?.to_str().ok()?;
+    if content_type.starts_with("application/json") {
+        Some(BodyKind::Json)
+    } else if content_type.starts_with("application/x-www-form-urlencoded") {
+        Some(BodyKind::Form)
+    } else {
+        None
+    }
+}
+
+fn method_from_query(query: &str) -> Option<String> {
+    form_urlencoded::parse(query.as_bytes())
+        .find(|(key, _)| key == "_method")
+        .map(|(_, value)| value.into_owned())
+}
+
+fn method_from_json(bytes: &[u8]) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Fields {
+        #[serde(rename = "_method")]
+        method: Option<String>,
+    }
+
+    serde_json::from_slice::<Fields>(bytes).ok()?.method
+}
+
+fn parse_override(method: &str) -> Option<Method> {
+    match method.to_ascii_uppercase().as_str() {
+        "PUT" => Some(Method::PUT),
+        "PATCH" => Some(Method::PATCH),
+        "DELETE" => Some(Method::DELETE),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::*;
+    use crate::protocol::redirect_status;
+    use axum::{
+        routing::{get, post},
+        Router, ServiceExt,
+    };
+    use tokio::net::TcpListener;
+
+    async fn spawn_test_app() -> SocketAddr {
+        async fn destroy(method: Method) -> impl IntoResponse {
+            (
+                redirect_status(&method),
+                [(http::header::LOCATION, "/posts")],
+            )
+        }
+
+        let app = Router::new()
+            .route("/posts", get(|| async { "index" }))
+            .route(
+                "/posts/1",
+                post(|| async { "created" })
+                    .put(|| async { "updated" })
+                    .delete(destroy),
+            )
+            .route("/posts/1/comments", post(|| async { "commented" }));
+        let app = InertiaMethodOverrideLayer::new()
+            .body_limit(1024)
+            .layer(app);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app.into_make_service())
+                .await
+                .expect("server error");
+        });
+
+        addr
+    }
+
+    #[tokio::test]
+    async fn it_overrides_from_json_body_and_redirects_to_get() {
+        let addr = spawn_test_app().await;
+        let client = reqwest::Client::new();
+
+        let res = client
+            .post(format!("http://{}/posts/1", &addr))
+            .header("Content-Type", "application/json")
+            .body(r#"{"_method":"delete"}"#)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), reqwest::StatusCode::OK);
+        assert_eq!(res.url().path(), "/posts");
+        assert_eq!(res.text().await.unwrap(), "index");
+    }
+
+    #[tokio::test]
+    async fn it_overrides_from_form_body_query_and_header() {
+        let addr = spawn_test_app().await;
+        let client = reqwest::Client::new();
+
+        let res = client
+            .post(format!("http://{}/posts/1", &addr))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body("title=foo&_method=PUT")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.text().await.unwrap(), "updated");
+
+        let res = client
+            .post(format!("http://{}/posts/1?_method=put", &addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.text().await.unwrap(), "updated");
+
+        let res = client
+            .post(format!("http://{}/posts/1", &addr))
+            .header("X-HTTP-Method-Override", "PUT")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.text().await.unwrap(), "updated");
+    }
+
+    #[tokio::test]
+    async fn it_ignores_unsupported_overrides() {
+        let addr = spawn_test_app().await;
+        let client = reqwest::Client::new();
+
+        let res = client
+            .post(format!("http://{}/posts/1/comments", &addr))
+            .header("Content-Type", "application/json")
+            .body(r#"{"_method":"GET","body":"hi"}"#)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.text().await.unwrap(), "commented");
+    }
+
+    #[tokio::test]
+    async fn it_rejects_bodies_over_the_limit() {
+        let addr = spawn_test_app().await;
+        let client = reqwest::Client::new();
+
+        let res = client
+            .post(format!("http://{}/posts/1/comments", &addr))
+            .header("Content-Type", "application/json")
+            .body(format!(r#"{{"body":"{}"}}"#, "a".repeat(2048)))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_is_length_limit() {
+        let err = axum::body::to_bytes(Body::from("too long"), 4)
+            .await
+            .unwrap_err();
+        assert!(is_length_limit(&err));
+        assert!(!is_length_limit(&axum::Error::new(std::io::Error::other(
+            "connection reset"
+        ))));
+    }
+}
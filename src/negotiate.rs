@@ -0,0 +1,187 @@
+//! Serving an endpoint to both Inertia and JSON API clients.
+//!
+//! [Inertia::negotiate](crate::Inertia::negotiate) responds with the
+//! page object to Inertia requests, with the props alone to clients
+//! that prefer `application/json` (e.g. mobile apps), and with the
+//! html page otherwise:
+//!
+//! ```rust
+//! use axum::response::IntoResponse;
+//! use axum_inertia::Inertia;
+//! use serde_json::json;
+//!
+//! async fn users(i: Inertia) -> impl IntoResponse {
+//!     i.negotiate("Users/Index", json!({ "users": ["mikey"] }))
+//! }
+//! ```
+//!
+//! The json props go through the
+//! [response hooks](crate::InertiaConfig::on_response) and the
+//! [html_sanitizer](crate::InertiaConfig::html_sanitizer) like a page.
+//! Responses vary on `Accept`, so that caches don't serve the html
+//! page to API clients or the other way around.
+
+use crate::response::Response;
+use axum::response::IntoResponse;
+use http::header::{ACCEPT, VARY};
+use http::{HeaderMap, HeaderValue, StatusCode};
+
+/// A response negotiated between the Inertia page and JSON props.
+/// See the [module documentation](self).
+pub struct Negotiate<'a>(pub(crate) Negotiated<'a>);
+
+/// Both responses are boxed, as they're much larger than `Error`.
+pub(crate) enum Negotiated<'a> {
+    Page(Box<Response<'a>>),
+    Json(Box<Response<'a>>),
+    Error,
+}
+
+impl IntoResponse for Negotiate<'_> {
+    fn into_response(self) -> axum::response::Response {
+        let mut response = match self.0 {
+            Negotiated::Page(response) => response.into_response(),
+            Negotiated::Json(response) => response.into_props_response(),
+            Negotiated::Error => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        };
+        response
+            .headers_mut()
+            .append(VARY, HeaderValue::from_static("accept"));
+        response
+    }
+}
+
+/// Returns true if the `Accept` header ranks `application/json` above
+/// `text/html`.
+///
+/// ```rust
+/// use axum_inertia::negotiate::prefers_json;
+/// use http::{header::ACCEPT, HeaderMap};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert(ACCEPT, "application/json".parse().unwrap());
+/// assert!(prefers_json(&headers));
+///
+/// headers.insert(ACCEPT, "text/html,application/json;q=0.9".parse().unwrap());
+/// assert!(!prefers_json(&headers));
+/// ```
+pub fn prefers_json(headers: &HeaderMap) -> bool {
+    let mut json = 0.0;
+    let mut html = 0.0;
+    let accept = headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok());
+    for range in accept.flat_map(|value| value.split(',')) {
+        let mut params = range.split(';');
+        let media = params.next().unwrap_or("").trim().to_ascii_lowercase();
+        let quality = params
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        match media.as_str() {
+            "application/json" => json = quality.max(json),
+            "text/html" => html = quality.max(html),
+            _ => {}
+        }
+    }
+    json > 0.0 && json > html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{props::Html, Inertia, InertiaConfig};
+    use axum::{routing::get, Router};
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_prefers_json() {
+        assert!(!prefers_json(&HeaderMap::new()));
+        assert!(!prefers_json(&accept("*/*")));
+        assert!(!prefers_json(&accept(
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"
+        )));
+        assert!(prefers_json(&accept("application/json, text/plain, */*")));
+        assert!(prefers_json(&accept("text/html;q=0.5, Application/JSON")));
+        assert!(!prefers_json(&accept("application/json;q=0")));
+    }
+
+    #[tokio::test]
+    async fn it_sanitizes_html_props_of_json_responses() {
+        let app = Router::new()
+            .route(
+                "/post",
+                get(|i: Inertia| async move {
+                    let body = Html("<p>hi<script></script></p>".to_string());
+                    i.negotiate("Post", json!({ "body": body }))
+                }),
+            )
+            .with_state(
+                InertiaConfig::new(None, Box::new(|props| props))
+                    .html_sanitizer(|html| html.replace("<script></script>", "")),
+            );
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let res = reqwest::Client::new()
+            .get(format!("http://{}/post", &addr))
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .unwrap();
+        let props: Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(props, json!({ "body": "<p>hi</p>" }));
+    }
+
+    #[tokio::test]
+    async fn it_runs_response_hooks_on_json_responses() {
+        let app = Router::new()
+            .route(
+                "/users",
+                get(|i: Inertia| async move { i.negotiate("Users", json!({ "users": [] })) }),
+            )
+            .with_state(
+                InertiaConfig::new(None, Box::new(|props| props)).on_response(
+                    |_, page, headers| {
+                        headers.insert("X-Hook", HeaderValue::from_static("yes"));
+                        page.props["hooked"] = json!(true);
+                    },
+                ),
+            );
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let res = reqwest::Client::new()
+            .get(format!("http://{}/users", &addr))
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.headers()["x-hook"], "yes");
+        assert_eq!(res.headers()["vary"], "accept");
+        let props: Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(props, json!({ "users": [], "hooked": true }));
+    }
+}
@@ -0,0 +1,222 @@
+//! Protocol-correct redirects from handlers that don't know about
+//! Inertia.
+//!
+//! Existing endpoints, e.g. a REST api shared with the Inertia app,
+//! may redirect in ways that break Inertia requests: a `307 Temporary
+//! Redirect` after a `PUT` makes the client repeat the `PUT`, and a
+//! redirect to
+//! another origin fails the XHR on CORS. [InertiaResponseNormalizerLayer]
+//! fixes up the responses to Inertia requests:
+//!
+//! - `301`, `302`, `307` and `308` redirects after requests other than
+//!   `GET` and `HEAD` get the status of
+//!   [redirect_status](crate::protocol::redirect_status), e.g. `303 See
+//!   Other` after `PUT`, `PATCH` or `DELETE`;
+//! - redirects to another origin become `409 Conflict` responses with
+//!   an `X-Inertia-Location` header, so the client does a full page
+//!   visit.
+//!
+//! Other responses, and responses to other requests, are unchanged.
+//!
+//! ```rust
+//! use axum::{response::Redirect, routing::put, Router};
+//! use axum_inertia::normalize::InertiaResponseNormalizerLayer;
+//!
+//! let app: Router = Router::new()
+//!     .route("/posts/1", put(|| async { Redirect::temporary("/posts") }))
+//!     .layer(InertiaResponseNormalizerLayer::new());
+//! ```
+
+use crate::protocol::{self, redirect_status, HeaderNames};
+use axum::response::Response;
+use http::header::{HOST, LOCATION};
+use http::{HeaderMap, Method, StatusCode, Uri};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Layer that applies [InertiaResponseNormalizerService] to a service.
+#[derive(Clone, Debug, Default)]
+pub struct InertiaResponseNormalizerLayer {
+    names: HeaderNames,
+}
+
+impl InertiaResponseNormalizerLayer {
+    pub fn new() -> InertiaResponseNormalizerLayer {
+        Self::default()
+    }
+
+    /// Reads and writes the Inertia headers with the given names. See
+    /// [InertiaConfig::header_names](crate::InertiaConfig::header_names).
+    pub fn header_names(mut self, names: HeaderNames) -> Self {
+        self.names = names;
+        self
+    }
+}
+
+impl<S> Layer<S> for InertiaResponseNormalizerLayer {
+    type Service = InertiaResponseNormalizerService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InertiaResponseNormalizerService {
+            inner,
+            names: self.names.clone(),
+        }
+    }
+}
+
+/// Service that makes redirects in responses to Inertia requests
+/// protocol-correct.
+#[derive(Clone, Debug)]
+pub struct InertiaResponseNormalizerService<S> {
+    inner: S,
+    names: HeaderNames,
+}
+
+impl<S, B> Service<http::Request<B>> for InertiaResponseNormalizerService<S>
+where
+    S: Service<http::Request<B>, Response = Response>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let is_xhr = req
+            .headers()
+            .get(&self.names.inertia)
+            .is_some_and(|value| value == "true");
+        let method = req.method().clone();
+        let host = request_host(req.uri(), req.headers());
+        let names = self.names.clone();
+        let future = self.inner.call(req);
+
+        Box::pin(async move {
+            let mut response = future.await?;
+            if is_xhr {
+                normalize(&mut response, &method, host.as_deref(), &names);
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// Returns the host of a request, from its uri or `Host` header.
+fn request_host(uri: &Uri, headers: &HeaderMap) -> Option<String> {
+    uri.authority()
+        .map(|authority| authority.as_str().to_string())
+        .or_else(|| {
+            headers
+                .get(HOST)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        })
+}
+
+fn normalize(response: &mut Response, method: &Method, host: Option<&str>, names: &HeaderNames) {
+    if !response.status().is_redirection() {
+        return;
+    }
+    let Some(location) = response.headers().get(LOCATION).cloned() else {
+        return;
+    };
+
+    let target_host = location
+        .to_str()
+        .ok()
+        .and_then(|location| location.parse::<Uri>().ok())
+        .and_then(|uri| {
+            uri.authority()
+                .map(|authority| authority.as_str().to_string())
+        });
+    let external = match (target_host, host) {
+        (Some(target), Some(host)) => !target.eq_ignore_ascii_case(host),
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+
+    if external {
+        *response.status_mut() = protocol::CONFLICT;
+        let headers = response.headers_mut();
+        headers.remove(LOCATION);
+        headers.remove(&names.inertia);
+        headers.insert(names.location.clone(), location);
+    } else if method != Method::GET && method != Method::HEAD && is_rewritable(response.status()) {
+        *response.status_mut() = redirect_status(method);
+    }
+}
+
+/// Whether a redirect status can be replaced with the one for the
+/// request method, i.e. isn't e.g. `304 Not Modified`.
+fn is_rewritable(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::MOVED_PERMANENTLY
+            | StatusCode::FOUND
+            | StatusCode::TEMPORARY_REDIRECT
+            | StatusCode::PERMANENT_REDIRECT
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::{IntoResponse, Redirect};
+
+    fn normalized(redirect: Redirect, method: Method) -> Response {
+        let mut response = redirect.into_response();
+        normalize(
+            &mut response,
+            &method,
+            Some("app.test"),
+            &HeaderNames::default(),
+        );
+        response
+    }
+
+    #[test]
+    fn test_normalize() {
+        let response = normalized(Redirect::temporary("/posts"), Method::PUT);
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let response = normalized(Redirect::permanent("/posts"), Method::DELETE);
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let response = normalized(Redirect::temporary("/posts"), Method::POST);
+        assert_eq!(response.status(), StatusCode::FOUND);
+
+        let response = normalized(Redirect::temporary("/posts"), Method::GET);
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+
+        let mut response = Response::new(axum::body::Body::empty());
+        *response.status_mut() = StatusCode::FOUND;
+        response
+            .headers_mut()
+            .insert(LOCATION, "/posts".parse().unwrap());
+        normalize(
+            &mut response,
+            &Method::DELETE,
+            Some("app.test"),
+            &HeaderNames::default(),
+        );
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let response = normalized(Redirect::to("https://app.test/posts"), Method::POST);
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let response = normalized(Redirect::to("https://auth.example/login"), Method::GET);
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(
+            response.headers().get("X-Inertia-Location").unwrap(),
+            "https://auth.example/login"
+        );
+        assert!(response.headers().get(LOCATION).is_none());
+    }
+}
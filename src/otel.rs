@@ -0,0 +1,284 @@
+//! OpenTelemetry span attributes for Inertia responses.
+//!
+//! [InertiaOtelLayer] sets these attributes on the span of the active
+//! OpenTelemetry context of each Inertia response, so traces show which
+//! page each request rendered:
+//!
+//! - `inertia.component`: the rendered component,
+//! - `inertia.partial`: whether the request was a partial reload,
+//! - `inertia.version_conflict`: whether the client's asset version
+//!   was stale, in which case no component was rendered.
+//!
+//! ```rust
+//! use axum::{response::IntoResponse, routing::get, Router};
+//! use axum_inertia::{otel::InertiaOtelLayer, vite, Inertia};
+//! use serde_json::json;
+//!
+//! async fn home(i: Inertia) -> impl IntoResponse {
+//!     i.render("Home", json!({}))
+//! }
+//!
+//! let app: Router = Router::new()
+//!     .route("/", get(home))
+//!     .layer(InertiaOtelLayer::new())
+//!     .with_state(vite::Development::default().into_config());
+//! ```
+//!
+//! The layer must be added before (inside) the layer that starts the
+//! span, and that layer must attach an OpenTelemetry
+//! [Context](opentelemetry::Context) with the span while calling its
+//! inner service, e.g. with
+//! [FutureExt::with_context](opentelemetry::trace::FutureExt::with_context)
+//! or [Context::attach](opentelemetry::Context::attach):
+//!
+//! ```rust
+//! use axum::{extract::Request, middleware::{self, Next}, Router};
+//! use axum_inertia::otel::InertiaOtelLayer;
+//! use opentelemetry::global;
+//! use opentelemetry::trace::{FutureExt, TraceContextExt, Tracer};
+//! use opentelemetry::Context;
+//!
+//! async fn trace(req: Request, next: Next) -> axum::response::Response {
+//!     let span = global::tracer("app").start(req.uri().path().to_string());
+//!     next.run(req).with_context(Context::current_with_span(span)).await
+//! }
+//!
+//! let app: Router = Router::new()
+//!     // ... routes ...
+//!     .layer(InertiaOtelLayer::new())
+//!     .layer(middleware::from_fn(trace));
+//! ```
+//!
+//! A `tracing` span alone, e.g. from `tower_http::trace::TraceLayer`
+//! with `tracing-opentelemetry`, doesn't attach a context, so the
+//! attributes would be set on no span.
+//!
+//! Requires the `otel` feature.
+
+use axum::response::Response;
+use opentelemetry::trace::{FutureExt, TraceContextExt};
+use opentelemetry::KeyValue;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// What an Inertia response did, stored in its extensions for
+/// [InertiaOtelLayer].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SpanInfo {
+    pub(crate) component: Option<Arc<str>>,
+    pub(crate) partial: bool,
+    pub(crate) version_conflict: bool,
+}
+
+impl SpanInfo {
+    pub(crate) fn page(component: &str, partial: bool) -> SpanInfo {
+        SpanInfo {
+            component: Some(component.into()),
+            partial,
+            version_conflict: false,
+        }
+    }
+
+    pub(crate) fn version_conflict() -> SpanInfo {
+        SpanInfo {
+            version_conflict: true,
+            ..SpanInfo::default()
+        }
+    }
+
+    fn attributes(&self) -> Vec<KeyValue> {
+        let mut attributes = vec![
+            KeyValue::new("inertia.partial", self.partial),
+            KeyValue::new("inertia.version_conflict", self.version_conflict),
+        ];
+        if let Some(component) = &self.component {
+            attributes.push(KeyValue::new("inertia.component", component.to_string()));
+        }
+        attributes
+    }
+}
+
+/// Layer that applies [RecordInertiaSpan] to a service.
+#[derive(Clone, Debug, Default)]
+pub struct InertiaOtelLayer;
+
+impl InertiaOtelLayer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for InertiaOtelLayer {
+    type Service = RecordInertiaSpan<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RecordInertiaSpan { inner }
+    }
+}
+
+/// Service that sets Inertia attributes on the span of the context
+/// active when it's called. See the
+/// [module documentation](self) for more.
+#[derive(Clone, Debug)]
+pub struct RecordInertiaSpan<S> {
+    inner: S,
+}
+
+impl<S, B> Service<http::Request<B>> for RecordInertiaSpan<S>
+where
+    S: Service<http::Request<B>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        // Take the service that was driven to readiness, leaving a
+        // clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        // The context is only attached while the outer layer polls, so
+        // keep it to find the span once the response is ready.
+        let cx = opentelemetry::Context::current();
+
+        Box::pin(
+            async move {
+                let res = inner.call(req).await?;
+                if let Some(info) = res.extensions().get::<SpanInfo>() {
+                    opentelemetry::Context::current()
+                        .span()
+                        .set_attributes(info.attributes());
+                }
+                Ok(res)
+            }
+            .with_context(cx),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Inertia, InertiaConfig};
+    use axum::extract::Request;
+    use axum::middleware::{self, map_response, Next};
+    use axum::{routing::get, Router};
+    use http::HeaderValue;
+    use opentelemetry::trace::{Tracer, TracerProvider as _};
+    use opentelemetry_sdk::testing::trace::InMemorySpanExporter;
+    use opentelemetry_sdk::trace::TracerProvider;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn it_records_rendered_pages() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|i: Inertia| async move { i.render("Home", json!({})) }),
+            )
+            .layer(map_response(|mut res: Response| async move {
+                let info = res.extensions().get::<SpanInfo>().cloned().unwrap();
+                let summary = format!(
+                    "{:?} {} {}",
+                    info.component, info.partial, info.version_conflict
+                );
+                res.headers_mut()
+                    .insert("x-span-info", HeaderValue::try_from(summary).unwrap());
+                res
+            }))
+            .layer(InertiaOtelLayer::new())
+            .with_state(InertiaConfig::new(
+                Some("1".to_string()),
+                Box::new(|props| props.to_string()),
+            ));
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+        let res = client
+            .get(format!("http://{}/", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Version", "1")
+            .header("X-Inertia-Partial-Component", "Home")
+            .header("X-Inertia-Partial-Data", "a")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.headers()["x-span-info"], r#"Some("Home") true false"#);
+
+        let res = client
+            .get(format!("http://{}/", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Version", "0")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.headers()["x-span-info"], "None false true");
+    }
+
+    #[tokio::test]
+    async fn it_sets_attributes_on_the_context_span() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = TracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let tracer = provider.tracer("test");
+
+        let app = Router::new()
+            .route(
+                "/",
+                get(|i: Inertia| async move { i.render("Home", json!({})) }),
+            )
+            .layer(InertiaOtelLayer::new())
+            .layer(middleware::from_fn(move |req: Request, next: Next| {
+                let span = tracer.start("request");
+                next.run(req)
+                    .with_context(opentelemetry::Context::current_with_span(span))
+            }))
+            .with_state(InertiaConfig::new(
+                None,
+                Box::new(|props| props.to_string()),
+            ));
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        reqwest::Client::new()
+            .get(format!("http://{}/", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        let attributes = &spans[0].attributes;
+        assert!(attributes.contains(&KeyValue::new("inertia.component", "Home")));
+        assert!(attributes.contains(&KeyValue::new("inertia.partial", false)));
+        assert!(attributes.contains(&KeyValue::new("inertia.version_conflict", false)));
+    }
+}
@@ -1,5 +1,8 @@
 use serde::Serialize;
-use serde_json::Value;
+use serde_json::value::RawValue;
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 
 /// Holds data for the Inertia page object.
 ///
@@ -7,10 +10,69 @@ use serde_json::Value;
 /// initial html page, or sent as the payload for Inertia requests.
 ///
 /// More info at: https://inertiajs.com/the-protocol#the-page-object
+///
+/// New fields may be added as the protocol evolves, so pages can't be
+/// constructed outside of this crate.
+#[derive(Serialize)]
+#[non_exhaustive]
+pub struct Page<'a> {
+    pub component: Cow<'a, str>,
+    /// The props, omitted if null, e.g. when moved under a custom key
+    /// with [InertiaConfig::props_key](crate::InertiaConfig::props_key).
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub props: Value,
+    pub url: String,
+    pub version: Option<String>,
+    /// Set when the component should be rendered as a modal over
+    /// another page. See [Inertia::render_modal](crate::Inertia::render_modal).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modal: Option<Modal<'a>>,
+    /// Props left out of this response that the client should fetch
+    /// with a partial reload, keyed by group.
+    #[serde(rename = "deferredProps", skip_serializing_if = "Option::is_none")]
+    pub deferred_props: Option<BTreeMap<String, Vec<String>>>,
+    /// Custom top-level fields, for client adapters that expect them.
+    /// Set them with `with_page_field` on the response of
+    /// [Inertia::render](crate::Inertia::render).
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Page<'_> {
+    /// Returns the page with owned data, e.g. for moving it to another
+    /// thread.
+    pub(crate) fn into_owned(self) -> Page<'static> {
+        Page {
+            component: Cow::Owned(self.component.into_owned()),
+            props: self.props,
+            url: self.url,
+            version: self.version,
+            modal: self.modal.map(|modal| Modal {
+                component: Cow::Owned(modal.component.into_owned()),
+                base_url: modal.base_url,
+            }),
+            deferred_props: self.deferred_props,
+            extra: self.extra,
+        }
+    }
+}
+
+/// A page object with pre-serialized props. See
+/// [Inertia::render_raw](crate::Inertia::render_raw).
+#[derive(Serialize)]
+pub(crate) struct RawPage<'a> {
+    pub component: Cow<'a, str>,
+    pub props: &'a RawValue,
+    pub url: String,
+    pub version: Option<String>,
+}
+
+/// Modal data for the Inertia page object.
 #[derive(Serialize)]
-pub(crate) struct Page<'a> {
-    pub(crate) component: &'a str,
-    pub(crate) props: Value,
-    pub(crate) url: String,
-    pub(crate) version: Option<String>,
+#[serde(rename_all = "camelCase")]
+pub struct Modal<'a> {
+    /// The component rendered in the modal.
+    pub component: Cow<'a, str>,
+    /// The url of the page the modal is rendered over.
+    pub base_url: String,
 }
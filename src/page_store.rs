@@ -0,0 +1,237 @@
+//! Serving the page object of initial page loads from a separate url.
+//!
+//! By default, the initial html embeds the page object in the
+//! `data-page` attribute. With a [PageStore], the
+//! [vite](crate::vite::Production::external_page) layout stores it
+//! instead, and references it by url; an inline script fetches it
+//! before loading the main entry. The html is then much smaller, and
+//! only differs between pages by the url:
+//!
+//! ```rust,no_run
+//! use axum::Router;
+//! use axum_inertia::{page_store::PageStore, vite};
+//!
+//! let store = PageStore::new();
+//! let inertia = vite::Production::new("client/dist/manifest.json", "src/main.ts")
+//!     .unwrap()
+//!     .external_page(store.clone())
+//!     .into_config();
+//! let app: Router = Router::new()
+//!     // ... routes ...
+//!     .merge(store.router())
+//!     .with_state(inertia);
+//! ```
+//!
+//! Page objects are served at `/_inertia/page/{hash}.json` for a short
+//! time (60 seconds by default), long enough for the page to fetch
+//! them. The hash is keyed with a secret of the store, so urls can't be
+//! guessed from a page's contents, and the same page object always gets
+//! the same url, which caches can use. The store is in memory: with
+//! several servers, requests for a page object must reach the server
+//! that rendered it.
+//!
+//! The urls are bearer urls: they aren't tied to the session that
+//! rendered the page, so anyone who learns one, e.g. from a proxy log,
+//! can fetch the page object, with the user's props, until it expires.
+//! Don't use a store for pages with secrets, or keep the
+//! [ttl](PageStore::ttl) short.
+//!
+//! The store holds at most 1000 page objects unless set with
+//! [max_entries](PageStore::max_entries). Once full, pages embed their
+//! page object as usual.
+
+use crate::clock::{Clock, Rng, SystemClock, SystemRng};
+use axum::extract::Path;
+use axum::response::{IntoResponse, Response};
+use axum::{routing::get, Router};
+use http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use http::{HeaderValue, StatusCode};
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The path of the page object endpoint, followed by the hash.
+pub const PAGE_PATH: &str = "/_inertia/page";
+
+const DEFAULT_TTL: Duration = Duration::from_secs(60);
+const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+/// The stored page objects by hash, with when they expire.
+type Pages = HashMap<String, (Arc<str>, Instant)>;
+
+/// The page objects waiting for their page to fetch them. See the
+/// [module documentation](self).
+#[derive(Clone)]
+pub struct PageStore {
+    key: Arc<str>,
+    pages: Arc<Mutex<Pages>>,
+    ttl: Duration,
+    max_entries: usize,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for PageStore {
+    fn default() -> Self {
+        PageStore {
+            key: SystemRng.token().into(),
+            pages: Arc::default(),
+            ttl: DEFAULT_TTL,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl PageStore {
+    pub fn new() -> PageStore {
+        Self::default()
+    }
+
+    /// Sets how long page objects are served after being rendered.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the maximum number of stored page objects. Once full, and
+    /// none have expired, [insert](PageStore::insert) stores nothing.
+    /// Defaults to 1000.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Sets the clock page objects expire by. See the [crate::clock]
+    /// module.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Draws the secret key of the hashes from `rng`, e.g. for
+    /// predictable urls in tests. See the [crate::clock] module.
+    pub fn rng(mut self, rng: impl Rng) -> Self {
+        self.key = rng.token().into();
+        self
+    }
+
+    /// Returns a router serving the page objects at
+    /// `/_inertia/page/{hash}.json`.
+    pub fn router<S>(&self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let store = self.clone();
+        Router::new().route(
+            &format!("{}/:file", PAGE_PATH),
+            get(move |Path(file): Path<String>| async move { store.respond(&file) }),
+        )
+    }
+
+    /// Stores the page object `page`, returning its url, or `None` if
+    /// the store is full.
+    pub fn insert(&self, page: &str) -> Option<String> {
+        let hash = hex::encode(
+            Sha1::new()
+                .chain_update(self.key.as_bytes())
+                .chain_update(page.as_bytes())
+                .finalize(),
+        );
+        let now = self.clock.now();
+        let mut pages = self.pages.lock().unwrap();
+        if pages.len() >= self.max_entries && !pages.contains_key(&hash) {
+            // Only sweep expired pages once full, to keep inserts cheap.
+            pages.retain(|_, (_, expires)| *expires > now);
+            if pages.len() >= self.max_entries {
+                tracing::warn!(
+                    max_entries = self.max_entries,
+                    "page store is full, not storing the page object"
+                );
+                return None;
+            }
+        }
+        pages.insert(hash.clone(), (page.into(), now + self.ttl));
+        Some(format!("{}/{}.json", PAGE_PATH, hash))
+    }
+
+    /// Returns the page object of `file`, or `404 Not Found` if it
+    /// doesn't exist or expired.
+    fn respond(&self, file: &str) -> Response {
+        let page = file.strip_suffix(".json").and_then(|hash| {
+            let mut pages = self.pages.lock().unwrap();
+            let (page, expires) = pages.get(hash)?;
+            if *expires > self.clock.now() {
+                return Some(page.clone());
+            }
+            pages.remove(hash);
+            None
+        });
+        let Some(page) = page else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        let cache_control = format!("private, max-age={}", self.ttl.as_secs());
+        (
+            [
+                (CONTENT_TYPE, HeaderValue::from_static("application/json")),
+                (
+                    CACHE_CONTROL,
+                    HeaderValue::try_from(cache_control).expect("valid cache-control"),
+                ),
+            ],
+            page.to_string(),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn it_serves_stored_pages() {
+        let store = PageStore::new();
+        let url = store.insert(r#"{"component":"Home"}"#).unwrap();
+        assert_eq!(Some(&url), store.insert(r#"{"component":"Home"}"#).as_ref());
+        assert!(url.starts_with("/_inertia/page/") && url.ends_with(".json"));
+
+        let app: Router = store.router();
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let res = reqwest::get(format!("http://{}{}", &addr, url))
+            .await
+            .unwrap();
+        assert_eq!(res.headers()["cache-control"], "private, max-age=60");
+        assert_eq!(res.text().await.unwrap(), r#"{"component":"Home"}"#);
+
+        let res = reqwest::get(format!("http://{}{}/missing.json", &addr, PAGE_PATH))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_max_entries_and_expiry() {
+        let clock = MockClock::new();
+        let store = PageStore::new().max_entries(1).clock(clock.clone());
+        let url = store.insert("{}").unwrap();
+        let file = url.rsplit('/').next().unwrap();
+        assert_eq!(store.insert("{}"), Some(url.clone()));
+        assert_eq!(store.insert("[]"), None);
+
+        clock.advance(Duration::from_secs(61));
+        assert_eq!(store.respond(file).status(), StatusCode::NOT_FOUND);
+        assert!(store.pages.lock().unwrap().is_empty());
+        assert!(store.insert("[]").is_some());
+    }
+}
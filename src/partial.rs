@@ -1,3 +1,10 @@
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use http::StatusCode;
+use percent_encoding::percent_decode_str;
+use serde_json::json;
+use std::fmt;
+
 /// Partial reload data.
 ///
 /// Clients can request a subset of the props if a page component is
@@ -9,3 +16,221 @@ pub struct Partial {
     pub props: Vec<String>,
     pub component: String,
 }
+
+impl Partial {
+    /// Parses the requested props into typed keys, failing with the
+    /// keys that aren't props of the component. With the `derive`
+    /// feature, [PropKey] can be derived for an enum:
+    ///
+    /// ```rust,ignore
+    /// use axum_inertia::partial::{Partial, PropKey};
+    ///
+    /// #[derive(Debug, PartialEq, PropKey)]
+    /// #[serde(rename_all = "camelCase")]
+    /// enum DashboardProp {
+    ///     Stats,
+    ///     RecentUsers,
+    /// }
+    ///
+    /// let partial = Partial {
+    ///     props: vec!["recentUsers".to_string()],
+    ///     component: "Dashboard".to_string(),
+    /// };
+    /// assert_eq!(partial.keys::<DashboardProp>().unwrap(), vec![DashboardProp::RecentUsers]);
+    /// ```
+    ///
+    /// [UnknownPropKeys] responds with `400 Bad Request`, so handlers
+    /// can reject mismatched partial reloads with `?`.
+    pub fn keys<K: PropKey>(&self) -> Result<Vec<K>, UnknownPropKeys> {
+        let mut keys = Vec::new();
+        let mut unknown = Vec::new();
+        for prop in &self.props {
+            match K::from_key(prop) {
+                Some(key) => keys.push(key),
+                None => unknown.push(prop.clone()),
+            }
+        }
+        if unknown.is_empty() {
+            Ok(keys)
+        } else {
+            Err(UnknownPropKeys {
+                component: self.component.clone(),
+                keys: unknown,
+            })
+        }
+    }
+}
+
+/// The props of a component, as an enum of keys. See
+/// [Partial::keys].
+pub trait PropKey: Sized {
+    /// All prop keys.
+    const KEYS: &'static [&'static str];
+
+    fn from_key(key: &str) -> Option<Self>;
+
+    fn key(&self) -> &'static str;
+}
+
+#[cfg(feature = "derive")]
+pub use axum_inertia_macros::PropKey;
+
+/// Props requested in a partial reload that the component doesn't
+/// have. Responds with `400 Bad Request` and a json error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownPropKeys {
+    pub component: String,
+    pub keys: Vec<String>,
+}
+
+impl fmt::Display for UnknownPropKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown props of `{}` in partial reload: {}",
+            self.component,
+            self.keys.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnknownPropKeys {}
+
+impl IntoResponse for UnknownPropKeys {
+    fn into_response(self) -> Response {
+        tracing::warn!(component = self.component, keys = ?self.keys, "unknown props in partial reload");
+        let body = json!({ "message": self.to_string(), "unknown": self.keys });
+        (StatusCode::BAD_REQUEST, Json(body)).into_response()
+    }
+}
+
+/// Parses the comma-separated list of props in an
+/// `X-Inertia-Partial-Data` header value.
+///
+/// Keys are percent-decoded and trimmed of surrounding whitespace.
+/// Empty and duplicate keys are dropped; otherwise the order of the
+/// header is kept.
+///
+/// ```rust
+/// use axum_inertia::partial::parse_partial_data;
+///
+/// assert_eq!(
+///     parse_partial_data(" posts, ,users,posts,user%20name"),
+///     vec!["posts", "users", "user name"]
+/// );
+/// ```
+pub fn parse_partial_data(value: &str) -> Vec<String> {
+    let mut props: Vec<String> = Vec::new();
+    for key in value.split(',') {
+        let key = percent_decode_str(key.trim()).decode_utf8_lossy();
+        let key = key.trim();
+        if !key.is_empty() && !props.iter().any(|p| p == key) {
+            props.push(key.to_string());
+        }
+    }
+    props
+}
+
+/// Parses the props of a `partial` query parameter, e.g.
+/// `partial=users,filters` or `partial=users%2Cfilters`. See
+/// [InertiaConfig::query_partials](crate::InertiaConfig::query_partials).
+pub(crate) fn parse_partial_query(query: &str) -> Option<Vec<String>> {
+    form_urlencoded::parse(query.as_bytes())
+        .find(|(key, _)| key == "partial")
+        .map(|(_, value)| parse_partial_data(&value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_partial_data() {
+        assert_eq!(parse_partial_data("one,two"), vec!["one", "two"]);
+        assert_eq!(parse_partial_data(" one ,\ttwo "), vec!["one", "two"]);
+        assert_eq!(parse_partial_data("one,,two,"), vec!["one", "two"]);
+        assert_eq!(parse_partial_data("one,two,one"), vec!["one", "two"]);
+        assert_eq!(parse_partial_data("a%2Cb,c%20d"), vec!["a,b", "c d"]);
+        assert!(parse_partial_data("").is_empty());
+        assert!(parse_partial_data(" , ,").is_empty());
+    }
+
+    #[test]
+    fn test_keys() {
+        #[derive(Debug, PartialEq)]
+        enum Prop {
+            Users,
+        }
+
+        impl PropKey for Prop {
+            const KEYS: &'static [&'static str] = &["users"];
+
+            fn from_key(key: &str) -> Option<Self> {
+                (key == "users").then_some(Prop::Users)
+            }
+
+            fn key(&self) -> &'static str {
+                "users"
+            }
+        }
+
+        let partial = |props: &[&str]| Partial {
+            props: props.iter().map(|p| p.to_string()).collect(),
+            component: "Users".to_string(),
+        };
+        assert_eq!(partial(&["users"]).keys::<Prop>(), Ok(vec![Prop::Users]));
+        assert_eq!(
+            partial(&["users", "posts"]).keys::<Prop>(),
+            Err(UnknownPropKeys {
+                component: "Users".to_string(),
+                keys: vec!["posts".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_partial_query() {
+        assert_eq!(
+            parse_partial_query("page=2&partial=users,filters"),
+            Some(vec!["users".to_string(), "filters".to_string()])
+        );
+        assert_eq!(
+            parse_partial_query("partial=users%2Crecent+posts"),
+            Some(vec!["users".to_string(), "recent posts".to_string()])
+        );
+        assert_eq!(parse_partial_query("partial="), Some(vec![]));
+        assert_eq!(parse_partial_query("partials=users"), None);
+    }
+
+    #[test]
+    fn test_parse_partial_data_properties() {
+        // A small xorshift generator, so the test is deterministic.
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let alphabet = [' ', ',', 'a', 'b', 'c', '%', '2', '0', '\t', '_'];
+
+        for _ in 0..1000 {
+            let len = next() % 24;
+            let input: String = (0..len)
+                .map(|_| alphabet[(next() % alphabet.len() as u64) as usize])
+                .collect();
+            let props = parse_partial_data(&input);
+
+            for (i, prop) in props.iter().enumerate() {
+                assert!(!prop.is_empty(), "empty key from {:?}", input);
+                assert_eq!(prop, prop.trim(), "untrimmed key from {:?}", input);
+                assert!(!props[..i].contains(prop), "duplicate key from {:?}", input);
+            }
+
+            // Parsing is idempotent for keys without commas or escapes.
+            if !input.contains('%') {
+                assert_eq!(parse_partial_data(&props.join(",")), props);
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Route-level component prefixes.
+//!
+//! Add a [ComponentPrefix] as an extension to a (nested) router, and
+//! every component rendered by its handlers is prefixed with it. For
+//! instance, with the prefix `Admin`, `i.render("Users/Index", ...)`
+//! renders the `Admin/Users/Index` component:
+//!
+//! ```rust
+//! use axum::{routing::get, Extension, Router};
+//! use axum_inertia::{prefix::ComponentPrefix, vite, Inertia};
+//! use serde_json::json;
+//!
+//! async fn users(i: Inertia) -> impl axum::response::IntoResponse {
+//!     i.render("Users/Index", json!({}))
+//! }
+//!
+//! let admin = Router::new()
+//!     .route("/users", get(users))
+//!     .layer(Extension(ComponentPrefix::new("Admin")));
+//!
+//! let app: Router = Router::new()
+//!     .nest("/admin", admin)
+//!     .with_state(vite::Development::default().into_config());
+//! ```
+//!
+//! An inner router's prefix replaces any prefix set further out.
+
+/// A prefix applied to rendered component names.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComponentPrefix(String);
+
+impl ComponentPrefix {
+    /// Constructs a new prefix. Leading and trailing slashes are
+    /// ignored.
+    pub fn new(prefix: &str) -> ComponentPrefix {
+        ComponentPrefix(prefix.trim_matches('/').to_string())
+    }
+
+    /// Returns `component` with the prefix applied.
+    pub fn apply(&self, component: &str) -> String {
+        if self.0.is_empty() {
+            component.to_string()
+        } else {
+            format!("{}/{}", self.0, component)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(ComponentPrefix::new("Admin").apply("Users"), "Admin/Users");
+        assert_eq!(
+            ComponentPrefix::new("/Admin/").apply("Users"),
+            "Admin/Users"
+        );
+        assert_eq!(ComponentPrefix::new("").apply("Users"), "Users");
+    }
+}
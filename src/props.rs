@@ -7,10 +7,44 @@
 use serde::Serialize;
 use serde_json::Value;
 use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
 
 use crate::partial::Partial;
 
+#[cfg(feature = "binary")]
+mod binary;
+mod cache;
+mod device;
+mod extract;
+pub(crate) mod html;
+mod map;
+mod when;
+
+#[cfg(feature = "binary")]
+pub use binary::{Base64Bytes, DataUrl};
+pub use cache::{Cached, MemoryCache, PropCache};
+pub use device::PerDevice;
+pub use extract::{Extracted, IntoProp};
+pub use html::Html;
+pub use map::Map;
+pub use when::{contains, When};
+
 /// Objects that can be used as Inertia props.
+///
+/// The trait is object safe, so heterogeneous props can be boxed, e.g.
+/// in a registry of prop providers:
+///
+/// ```rust
+/// use axum_inertia::props::Props;
+/// use serde_json::json;
+///
+/// let providers: Vec<Box<dyn Props>> = vec![Box::new(json!({ "a": 1 })), Box::new(vec![1, 2])];
+/// for provider in &providers {
+///     provider.serialize(None).unwrap();
+/// }
+/// ```
 pub trait Props {
     /// Serialize to json, given data about partial reloads.
     ///
@@ -20,7 +54,7 @@ pub trait Props {
     /// information is available in the [inertia docs].
     ///
     /// [inertia docs]: https://inertiajs.com/the-protocol#partial-reloads
-    fn serialize(self, partial: Option<&Partial>) -> Result<Value, impl Error>;
+    fn serialize(&self, partial: Option<&Partial>) -> Result<Value, PropsError>;
 }
 
 /// A naive, blanket implementation for all types that implement
@@ -30,7 +64,336 @@ impl<T> Props for T
 where
     T: Serialize,
 {
-    fn serialize(self, _: Option<&Partial>) -> Result<Value, impl Error> {
-        serde_json::to_value(self)
+    fn serialize(&self, _: Option<&Partial>) -> Result<Value, PropsError> {
+        Ok(serde_json::to_value(self)?)
+    }
+}
+
+impl Props for Box<dyn Props> {
+    fn serialize(&self, partial: Option<&Partial>) -> Result<Value, PropsError> {
+        (**self).serialize(partial)
+    }
+}
+
+impl Props for Box<dyn Props + Send + Sync> {
+    fn serialize(&self, partial: Option<&Partial>) -> Result<Value, PropsError> {
+        (**self).serialize(partial)
+    }
+}
+
+/// An error serializing [Props].
+#[derive(Debug)]
+pub struct PropsError {
+    key: Option<String>,
+    inner: Box<dyn Error + Send + Sync>,
+}
+
+impl PropsError {
+    /// Wraps any error as a props error.
+    pub fn new(err: impl Into<Box<dyn Error + Send + Sync>>) -> PropsError {
+        PropsError {
+            key: None,
+            inner: err.into(),
+        }
+    }
+
+    /// Sets the key of the prop that failed.
+    pub fn with_key(mut self, key: impl Into<String>) -> PropsError {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Returns the key of the prop that failed, if known.
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+}
+
+impl fmt::Display for PropsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.key {
+            Some(key) => write!(f, "failed to serialize prop `{}`: {}", key, self.inner),
+            None => write!(f, "failed to serialize props: {}", self.inner),
+        }
+    }
+}
+
+impl Error for PropsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.inner)
+    }
+}
+
+impl From<serde_json::Error> for PropsError {
+    fn from(err: serde_json::Error) -> PropsError {
+        PropsError::new(err)
+    }
+}
+
+/// A prop computed by a future, bounded by a timeout.
+///
+/// If the future does not complete in time, the `fallback` is used
+/// instead and the prop is marked as deferred, so that the client
+/// fetches the real value with a partial reload. Resolve it with
+/// [Inertia::resolve_timed](crate::Inertia::resolve_timed):
+///
+/// ```rust
+/// use axum_inertia::{props::Timed, Inertia};
+/// use axum::response::IntoResponse;
+/// use serde_json::json;
+/// use std::time::Duration;
+///
+/// async fn count_users() -> u64 {
+///     // ... a slow query ...
+///     # 10
+/// }
+///
+/// async fn dashboard(mut i: Inertia) -> impl IntoResponse {
+///     let timed = Timed::new(Duration::from_millis(100), count_users(), 0);
+///     let user_count = i.resolve_timed("userCount", timed).await;
+///     i.render("Dashboard", json!({ "userCount": user_count }))
+/// }
+/// ```
+pub struct Timed<F: Future> {
+    pub(crate) timeout: Duration,
+    pub(crate) future: F,
+    pub(crate) fallback: F::Output,
+}
+
+impl<F: Future> Timed<F> {
+    pub fn new(timeout: Duration, future: F, fallback: F::Output) -> Timed<F> {
+        Timed {
+            timeout,
+            future,
+            fallback,
+        }
+    }
+}
+
+/// Combinators for building [Props] out of reusable fragments.
+///
+/// The resulting types implement [Props] and forward information
+/// about partial reloads to the underlying props:
+///
+/// ```rust
+/// use axum_inertia::props::PropsExt;
+/// use serde_json::json;
+///
+/// let auth = json!({ "user": "mikey" });
+/// let props = json!({ "posts": ["post one"] })
+///     .rename_key("posts", "items")
+///     .merge(json!({ "count": 1 }))
+///     .nest("auth", auth);
+/// ```
+pub trait PropsExt: Props + Sized {
+    /// Merges the top-level keys of `other` into these props. Keys in
+    /// `other` take precedence.
+    fn merge<P: Props>(self, other: P) -> Merge<Self, P> {
+        Merge {
+            first: self,
+            second: other,
+        }
+    }
+
+    /// Adds `props` under the top-level `key`.
+    ///
+    /// During a partial reload, the nested props are only serialized
+    /// if `key` is requested. Dotted keys such as `auth.user` are
+    /// passed on to the nested props as `user`.
+    fn nest<P: Props>(self, key: &str, props: P) -> Merge<Self, Nested<P>> {
+        self.merge(Nested {
+            key: key.to_string(),
+            props,
+        })
+    }
+
+    /// Renames the top-level key `from` to `to`.
+    ///
+    /// Partial reloads should request the new name, `to`.
+    fn rename_key(self, from: &str, to: &str) -> RenameKey<Self> {
+        RenameKey {
+            props: self,
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+}
+
+impl<T: Props> PropsExt for T {}
+
+/// Props produced by [PropsExt::merge].
+pub struct Merge<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: Props, B: Props> Props for Merge<A, B> {
+    fn serialize(&self, partial: Option<&Partial>) -> Result<Value, PropsError> {
+        let mut merged = into_object(self.first.serialize(partial)?)?;
+        merged.extend(into_object(self.second.serialize(partial)?)?);
+        Ok(Value::Object(merged))
+    }
+}
+
+/// Props produced by [PropsExt::nest].
+pub struct Nested<P> {
+    key: String,
+    props: P,
+}
+
+impl<P: Props> Props for Nested<P> {
+    fn serialize(&self, partial: Option<&Partial>) -> Result<Value, PropsError> {
+        let mut map = serde_json::Map::new();
+        let nested_partial = match partial {
+            None => None,
+            Some(partial) if partial.props.contains(&self.key) => None,
+            Some(partial) => {
+                let prefix = format!("{}.", self.key);
+                let props: Vec<String> = partial
+                    .props
+                    .iter()
+                    .filter_map(|p| p.strip_prefix(&prefix).map(|p| p.to_string()))
+                    .collect();
+                if props.is_empty() {
+                    return Ok(Value::Object(map));
+                }
+                Some(Partial {
+                    props,
+                    component: partial.component.clone(),
+                })
+            }
+        };
+        let value = self.props.serialize(nested_partial.as_ref())?;
+        map.insert(self.key.clone(), value);
+        Ok(Value::Object(map))
+    }
+}
+
+/// Props produced by [PropsExt::rename_key].
+pub struct RenameKey<P> {
+    props: P,
+    from: String,
+    to: String,
+}
+
+impl<P: Props> Props for RenameKey<P> {
+    fn serialize(&self, partial: Option<&Partial>) -> Result<Value, PropsError> {
+        let renamed_partial = partial.map(|partial| Partial {
+            props: partial
+                .props
+                .iter()
+                .map(|p| {
+                    if *p == self.to {
+                        self.from.clone()
+                    } else {
+                        p.clone()
+                    }
+                })
+                .collect(),
+            component: partial.component.clone(),
+        });
+        let mut map = into_object(self.props.serialize(renamed_partial.as_ref())?)?;
+        if let Some(value) = map.remove(&self.from) {
+            map.insert(self.to.clone(), value);
+        }
+        Ok(Value::Object(map))
+    }
+}
+
+fn into_object(value: Value) -> Result<serde_json::Map<String, Value>, PropsError> {
+    match value {
+        Value::Object(map) => Ok(map),
+        _ => Err(PropsError::new("props must serialize to a json object")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn partial(props: &[&str]) -> Partial {
+        Partial {
+            props: props.iter().map(|p| p.to_string()).collect(),
+            component: "Testing".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_boxed_props() {
+        let props: Vec<Box<dyn Props>> = vec![Box::new(json!({ "a": 1 })), Box::new(Inner)];
+        let merged = props
+            .into_iter()
+            .fold(Box::new(json!({})) as Box<dyn Props>, |acc, p| {
+                Box::new(acc.merge(p))
+            });
+        assert_eq!(
+            Props::serialize(&merged, None).unwrap(),
+            json!({ "a": 1, "inner": true })
+        );
+
+        struct Inner;
+        impl Props for Inner {
+            fn serialize(&self, _: Option<&Partial>) -> Result<Value, PropsError> {
+                Ok(json!({ "inner": true }))
+            }
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let props = json!({ "a": 1, "b": 1 }).merge(json!({ "b": 2 }));
+        let value = Props::serialize(&props, None).unwrap();
+        assert_eq!(value, json!({ "a": 1, "b": 2 }));
+    }
+
+    #[test]
+    fn test_merge_rejects_non_objects() {
+        let props = json!({ "a": 1 }).merge(json!([1, 2]));
+        assert!(Props::serialize(&props, None).is_err());
+    }
+
+    #[test]
+    fn test_nest() {
+        let props = json!({ "a": 1 }).nest("auth", json!({ "user": "mikey" }));
+        let value = Props::serialize(&props, None).unwrap();
+        assert_eq!(value, json!({ "a": 1, "auth": { "user": "mikey" } }));
+    }
+
+    #[test]
+    fn test_nest_skips_unrequested_partials() {
+        let props = json!({}).nest("auth", json!({ "user": "mikey" }));
+        let value = Props::serialize(&props, Some(&partial(&["posts"]))).unwrap();
+        assert_eq!(value, json!({}));
+    }
+
+    #[test]
+    fn test_nest_forwards_dotted_partials() {
+        struct Inner;
+        impl Props for Inner {
+            fn serialize(&self, partial: Option<&Partial>) -> Result<Value, PropsError> {
+                assert_eq!(partial.unwrap().props, vec!["user".to_string()]);
+                Ok(json!("ok"))
+            }
+        }
+
+        let props = json!({}).nest("auth", Inner);
+        let value = Props::serialize(&props, Some(&partial(&["auth.user"]))).unwrap();
+        assert_eq!(value, json!({ "auth": "ok" }));
+    }
+
+    #[test]
+    fn test_rename_key() {
+        struct Inner;
+        impl Props for Inner {
+            fn serialize(&self, partial: Option<&Partial>) -> Result<Value, PropsError> {
+                assert_eq!(partial.unwrap().props, vec!["posts".to_string()]);
+                Ok(json!({ "posts": [1] }))
+            }
+        }
+
+        let props = Inner.rename_key("posts", "items");
+        let value = Props::serialize(&props, Some(&partial(&["items"]))).unwrap();
+        assert_eq!(value, json!({ "items": [1] }));
     }
 }
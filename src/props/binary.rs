@@ -0,0 +1,142 @@
+use base64::display::Base64Display;
+use base64::engine::general_purpose::STANDARD;
+use serde::ser::Error;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+/// The default size limit of binary props, before encoding.
+const DEFAULT_MAX_LEN: usize = 64 * 1024;
+
+/// Binary data, serialized as a base64 string, for small inline assets
+/// like QR codes or thumbnails:
+///
+/// ```rust
+/// use axum::response::IntoResponse;
+/// use axum_inertia::{props::Base64Bytes, Inertia};
+/// use serde_json::json;
+///
+/// async fn ticket(i: Inertia) -> impl IntoResponse {
+///     let qr_code: Vec<u8> = vec![0x89, 0x50, 0x4e, 0x47];
+///     i.render("Ticket", json!({ "qrCode": Base64Bytes::new(qr_code) }))
+/// }
+/// ```
+///
+/// Data over the size limit (64 KiB unless set with
+/// [max_len](Base64Bytes::max_len)) fails to serialize, as it bloats
+/// every page object it's in; serve it from a route instead. The data
+/// is encoded straight into the serializer, without an intermediate
+/// string.
+///
+/// Requires the `binary` feature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Base64Bytes<B> {
+    bytes: B,
+    max_len: usize,
+}
+
+impl<B: AsRef<[u8]>> Base64Bytes<B> {
+    pub fn new(bytes: B) -> Base64Bytes<B> {
+        Base64Bytes {
+            bytes,
+            max_len: DEFAULT_MAX_LEN,
+        }
+    }
+
+    /// Sets the size limit, in bytes before encoding.
+    pub fn max_len(mut self, max_len: usize) -> Base64Bytes<B> {
+        self.max_len = max_len;
+        self
+    }
+
+    fn check_len<E: Error>(&self) -> Result<&[u8], E> {
+        let bytes = self.bytes.as_ref();
+        if bytes.len() > self.max_len {
+            return Err(E::custom(format!(
+                "binary prop of {} bytes is over the limit of {} bytes",
+                bytes.len(),
+                self.max_len
+            )));
+        }
+        Ok(bytes)
+    }
+}
+
+impl<B: AsRef<[u8]>> Serialize for Base64Bytes<B> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes = self.check_len()?;
+        serializer.collect_str(&Base64Display::new(bytes, &STANDARD))
+    }
+}
+
+/// Binary data with a mime type, serialized as a `data:` url that can
+/// be used as an image `src` as is:
+///
+/// ```rust
+/// use axum_inertia::props::DataUrl;
+/// use serde_json::json;
+///
+/// let thumbnail = DataUrl::new("image/png", vec![0x89, 0x50, 0x4e, 0x47]);
+/// assert_eq!(
+///     serde_json::to_value(thumbnail).unwrap(),
+///     json!("data:image/png;base64,iVBORw==")
+/// );
+/// ```
+///
+/// It has the same size limit as [Base64Bytes].
+///
+/// Requires the `binary` feature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataUrl<B> {
+    mime: String,
+    bytes: Base64Bytes<B>,
+}
+
+impl<B: AsRef<[u8]>> DataUrl<B> {
+    pub fn new(mime: impl Into<String>, bytes: B) -> DataUrl<B> {
+        DataUrl {
+            mime: mime.into(),
+            bytes: Base64Bytes::new(bytes),
+        }
+    }
+
+    /// Sets the size limit, in bytes before encoding.
+    pub fn max_len(mut self, max_len: usize) -> DataUrl<B> {
+        self.bytes = self.bytes.max_len(max_len);
+        self
+    }
+}
+
+impl<B: AsRef<[u8]>> Serialize for DataUrl<B> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct Url<'a>(&'a str, &'a [u8]);
+
+        impl fmt::Display for Url<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    f,
+                    "data:{};base64,{}",
+                    self.0,
+                    Base64Display::new(self.1, &STANDARD)
+                )
+            }
+        }
+
+        let bytes = self.bytes.check_len()?;
+        serializer.collect_str(&Url(&self.mime, bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_base64_bytes() {
+        let value = serde_json::to_value(Base64Bytes::new(b"hello")).unwrap();
+        assert_eq!(value, json!("aGVsbG8="));
+
+        let err = serde_json::to_value(Base64Bytes::new(b"hello").max_len(4)).unwrap_err();
+        assert!(err.to_string().contains("over the limit of 4 bytes"));
+    }
+}
@@ -0,0 +1,217 @@
+use super::PropsError;
+use crate::clock::{Clock, SystemClock};
+use crate::request::Request;
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+/// A store for serialized props, e.g. in memory or backed by Redis.
+#[async_trait]
+pub trait PropCache: Send + Sync {
+    /// Returns the json stored under `key`, if any and not expired.
+    async fn get(&self, key: &str) -> Option<String>;
+
+    /// Stores `json` under `key` for `ttl`.
+    async fn set(&self, key: &str, json: String, ttl: Duration);
+}
+
+/// An in-memory [PropCache]. Expired entries are removed when they
+/// are next read, or when the cache is full. It holds at most 1000
+/// entries unless set with [max_entries](MemoryCache::max_entries);
+/// once full, and none have expired, new entries aren't cached.
+#[derive(Clone)]
+pub struct MemoryCache {
+    entries: Arc<Mutex<HashMap<String, (Instant, String)>>>,
+    max_entries: usize,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for MemoryCache {
+    fn default() -> Self {
+        MemoryCache {
+            entries: Arc::default(),
+            max_entries: DEFAULT_MAX_ENTRIES,
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl MemoryCache {
+    pub fn new() -> MemoryCache {
+        Self::default()
+    }
+
+    /// Sets the maximum number of cached entries. Defaults to 1000.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Sets the clock entries expire by. See the [crate::clock]
+    /// module.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+}
+
+#[async_trait]
+impl PropCache for MemoryCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((expires, json)) if *expires > self.clock.now() => Some(json.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    async fn set(&self, key: &str, json: String, ttl: Duration) {
+        let now = self.clock.now();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.max_entries && !entries.contains_key(key) {
+            // Only sweep expired entries once full, to keep writes cheap.
+            entries.retain(|_, (expires, _)| *expires > now);
+            if entries.len() >= self.max_entries {
+                tracing::warn!(
+                    max_entries = self.max_entries,
+                    "prop cache is full, not caching the prop"
+                );
+                return;
+            }
+        }
+        entries.insert(key.to_string(), (now + ttl, json));
+    }
+}
+
+/// A prop whose serialized json is cached across requests.
+///
+/// The cache key is computed from the request, and must include
+/// everything the prop depends on. **A prop that differs by user must
+/// have the user or session in its key**: otherwise the first user's
+/// prop is served to everyone requesting the same key. On a cache hit,
+/// the prop's future is dropped without being polled:
+///
+/// ```rust
+/// use axum_inertia::props::{Cached, MemoryCache};
+/// use axum_inertia::Inertia;
+/// use axum::{extract::State, response::IntoResponse};
+/// use serde_json::json;
+/// use std::time::Duration;
+///
+/// async fn expensive_stats() -> Vec<u64> {
+///     // ... a slow query ...
+///     # vec![1, 2, 3]
+/// }
+///
+/// async fn dashboard(i: Inertia, State(cache): State<MemoryCache>) -> impl IntoResponse {
+///     // The same stats are shown to every user.
+///     let key = |request: &axum_inertia::Request| format!("stats:{}", request.url());
+///     let stats = Cached::new(cache, Duration::from_secs(60), key, expensive_stats())
+///         .resolve(i.request())
+///         .await
+///         .unwrap();
+///     i.render("Dashboard", json!({ "stats": stats }))
+/// }
+/// ```
+pub struct Cached<C, F> {
+    cache: C,
+    ttl: Duration,
+    key: Box<dyn Fn(&Request) -> String + Send + Sync>,
+    future: F,
+}
+
+impl<C, F> Cached<C, F>
+where
+    C: PropCache,
+    F: Future,
+    F::Output: Serialize,
+{
+    /// Constructs a cached prop computed by `future`, cached under the
+    /// key `key` computes from the request.
+    pub fn new(
+        cache: C,
+        ttl: Duration,
+        key: impl Fn(&Request) -> String + Send + Sync + 'static,
+        future: F,
+    ) -> Cached<C, F> {
+        Cached {
+            cache,
+            ttl,
+            key: Box::new(key),
+            future,
+        }
+    }
+
+    /// Returns the cached value, or awaits the future and caches its
+    /// output.
+    pub async fn resolve(self, request: &Request) -> Result<Value, PropsError> {
+        let key = (self.key)(request);
+        if let Some(json) = self.cache.get(&key).await {
+            return Ok(serde_json::from_str(&json)?);
+        }
+        let value = serde_json::to_value(self.future.await)?;
+        self.cache.set(&key, value.to_string(), self.ttl).await;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_caches_until_expiry() {
+        let clock = MockClock::new();
+        let cache = MemoryCache::new().clock(clock.clone());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let request = Request::test_request();
+        let ttl = Duration::from_millis(50);
+
+        let resolve = || {
+            let calls = calls.clone();
+            let key = |request: &Request| format!("count:{}", request.url());
+            Cached::new(cache.clone(), ttl, key, async move {
+                calls.fetch_add(1, Ordering::SeqCst) + 1
+            })
+            .resolve(&request)
+        };
+
+        assert_eq!(resolve().await.unwrap(), 1);
+        assert_eq!(resolve().await.unwrap(), 1);
+        assert!(cache.get("count:/foo/bar").await.is_some());
+
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(resolve().await.unwrap(), 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_max_entries() {
+        let clock = MockClock::new();
+        let cache = MemoryCache::new().max_entries(1).clock(clock.clone());
+        let ttl = Duration::from_secs(1);
+
+        cache.set("a", "1".to_string(), ttl).await;
+        cache.set("a", "2".to_string(), ttl).await;
+        cache.set("b", "3".to_string(), ttl).await;
+        assert_eq!(cache.get("a").await.as_deref(), Some("2"));
+        assert_eq!(cache.get("b").await, None);
+
+        clock.advance(Duration::from_secs(2));
+        cache.set("b", "3".to_string(), ttl).await;
+        assert_eq!(cache.entries.lock().unwrap().len(), 1);
+        assert_eq!(cache.get("b").await.as_deref(), Some("3"));
+    }
+}
@@ -0,0 +1,71 @@
+use super::{Props, PropsError};
+use crate::hints::DeviceClass;
+use crate::partial::Partial;
+use serde_json::Value;
+
+/// Props that differ by [DeviceClass], e.g. to leave heavy
+/// desktop-only data out of mobile first loads:
+///
+/// ```rust
+/// use axum::response::IntoResponse;
+/// use axum_inertia::props::{PerDevice, PropsExt};
+/// use axum_inertia::Inertia;
+/// use serde_json::json;
+///
+/// async fn dashboard(i: Inertia) -> impl IntoResponse {
+///     let chart = PerDevice::new(
+///         i.device_class(),
+///         json!({ "chart": { "points": 12 } }),
+///         json!({ "chart": { "points": 365 } }),
+///     );
+///     i.render("Dashboard", json!({ "title": "Dashboard" }).merge(chart))
+/// }
+/// ```
+///
+/// Responses using it should vary on `Sec-CH-UA-Mobile` and
+/// `User-Agent`, so caches don't mix them up.
+pub struct PerDevice<M, D> {
+    class: DeviceClass,
+    mobile: M,
+    desktop: D,
+}
+
+impl<M: Props, D: Props> PerDevice<M, D> {
+    /// Uses `mobile` or `desktop`, depending on `class`.
+    pub fn new(class: DeviceClass, mobile: M, desktop: D) -> PerDevice<M, D> {
+        PerDevice {
+            class,
+            mobile,
+            desktop,
+        }
+    }
+}
+
+impl<M: Props, D: Props> Props for PerDevice<M, D> {
+    fn serialize(&self, partial: Option<&Partial>) -> Result<Value, PropsError> {
+        match self.class {
+            DeviceClass::Mobile => self.mobile.serialize(partial),
+            DeviceClass::Desktop => self.desktop.serialize(partial),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_per_device() {
+        let props =
+            |class| PerDevice::new(class, json!({ "points": 12 }), json!({ "points": 365 }));
+        assert_eq!(
+            props(DeviceClass::Mobile).serialize(None).unwrap(),
+            json!({ "points": 12 })
+        );
+        assert_eq!(
+            props(DeviceClass::Desktop).serialize(None).unwrap(),
+            json!({ "points": 365 })
+        );
+    }
+}
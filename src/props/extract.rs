@@ -0,0 +1,161 @@
+use super::{Props, PropsError};
+use crate::partial::Partial;
+use axum::extract::{Path, Query, State};
+use axum::{Extension, Form, Json};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Extractors whose value can be used as a prop. See [props_from].
+///
+/// Implemented for axum's wrapper extractors, like [Query] and
+/// [Path], when the extracted type implements [Serialize]. Custom
+/// extractors, e.g. for the current user, can implement it too:
+///
+/// ```rust
+/// use axum_inertia::props::{IntoProp, PropsError};
+/// use serde_json::{json, Value};
+///
+/// struct CurrentUser {
+///     name: String,
+/// }
+///
+/// impl IntoProp for CurrentUser {
+///     fn into_prop(self) -> Result<Value, PropsError> {
+///         Ok(json!({ "name": self.name }))
+///     }
+/// }
+/// ```
+///
+/// [props_from]: crate::props_from
+pub trait IntoProp {
+    fn into_prop(self) -> Result<Value, PropsError>;
+}
+
+impl IntoProp for Value {
+    fn into_prop(self) -> Result<Value, PropsError> {
+        Ok(self)
+    }
+}
+
+macro_rules! impl_into_prop {
+    ($($extractor:ident),*) => {
+        $(
+            impl<T: Serialize> IntoProp for $extractor<T> {
+                fn into_prop(self) -> Result<Value, PropsError> {
+                    Ok(serde_json::to_value(self.0)?)
+                }
+            }
+        )*
+    };
+}
+
+impl_into_prop!(Query, Path, Json, Form, Extension, State);
+
+/// Props built from extractors with the [props_from] macro.
+///
+/// During a partial reload, only the requested props are included.
+///
+/// [props_from]: crate::props_from
+#[derive(Default)]
+pub struct Extracted {
+    entries: Vec<(String, Result<Value, PropsError>)>,
+}
+
+impl Extracted {
+    pub fn new() -> Extracted {
+        Self::default()
+    }
+
+    /// Adds the value of `extractor` under `key`.
+    pub fn with(mut self, key: &str, extractor: impl IntoProp) -> Self {
+        self.entries.push((key.to_string(), extractor.into_prop()));
+        self
+    }
+}
+
+impl Props for Extracted {
+    fn serialize(&self, partial: Option<&Partial>) -> Result<Value, PropsError> {
+        let mut props = serde_json::Map::new();
+        for (key, value) in &self.entries {
+            if partial.is_some_and(|partial| !partial.props.contains(key)) {
+                continue;
+            }
+            match value {
+                Ok(value) => {
+                    props.insert(key.clone(), value.clone());
+                }
+                Err(err) => {
+                    return Err(PropsError::new(err.to_string()).with_key(key.clone()));
+                }
+            }
+        }
+        Ok(Value::Object(props))
+    }
+}
+
+/// Builds named props from extractors, in one call.
+///
+/// Each extractor must implement [IntoProp](crate::props::IntoProp).
+/// The result is an [Extracted](crate::props::Extracted), which can be
+/// combined with other props with
+/// [PropsExt](crate::props::PropsExt):
+///
+/// ```rust
+/// use axum::extract::{Path, Query};
+/// use axum::response::IntoResponse;
+/// use axum_inertia::{props::PropsExt, props_from, Inertia};
+/// use serde::{Deserialize, Serialize};
+/// use serde_json::json;
+///
+/// #[derive(Deserialize, Serialize)]
+/// struct Filters {
+///     search: Option<String>,
+/// }
+///
+/// async fn posts(
+///     i: Inertia,
+///     Path(user_id): Path<u64>,
+///     query: Query<Filters>,
+/// ) -> impl IntoResponse {
+///     let props = props_from!(query => "filters", Path(user_id) => "userId")
+///         .merge(json!({ "posts": [] }));
+///     i.render("Posts/Index", props)
+/// }
+/// ```
+#[macro_export]
+macro_rules! props_from {
+    ($($extractor:expr => $key:expr),* $(,)?) => {
+        $crate::props::Extracted::new()$(.with($key, $extractor))*
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Serialize)]
+    struct Filters {
+        search: &'static str,
+    }
+
+    #[test]
+    fn test_props_from() {
+        let props = crate::props_from!(
+            Query(Filters { search: "rust" }) => "filters",
+            Path(7) => "userId",
+        );
+        let value = Props::serialize(&props, None).unwrap();
+        assert_eq!(
+            value,
+            json!({ "filters": { "search": "rust" }, "userId": 7 })
+        );
+
+        let partial = Partial {
+            props: vec!["userId".to_string()],
+            component: "Testing".to_string(),
+        };
+        let value = Props::serialize(&props, Some(&partial)).unwrap();
+        assert_eq!(value, json!({ "userId": 7 }));
+    }
+}
@@ -0,0 +1,83 @@
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+
+/// The key of the object a serialized [Html] prop is wrapped in, until
+/// the page is rendered.
+pub(crate) const HTML_MARKER: &str = "$inertiaHtml";
+
+/// A rich-text prop, passed through the
+/// [html_sanitizer](crate::InertiaConfig::html_sanitizer) before the
+/// page is sent:
+///
+/// ```rust
+/// use axum::response::IntoResponse;
+/// use axum_inertia::{props::Html, Inertia};
+/// use serde_json::json;
+///
+/// async fn post(i: Inertia) -> impl IntoResponse {
+///     let body = "<p>Hello<script>alert(1)</script></p>".to_string();
+///     i.render("Post", json!({ "title": "Hello", "body": Html(body) }))
+/// }
+/// ```
+///
+/// On the client, the prop is a plain string. Without a sanitizer it
+/// is sent unchanged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Html(pub String);
+
+impl Serialize for Html {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(HTML_MARKER, &self.0)?;
+        map.end()
+    }
+}
+
+/// Replaces the serialized [Html] props in `value` with their
+/// sanitized strings.
+pub(crate) fn sanitize(value: &mut Value, sanitizer: &dyn Fn(&str) -> String) {
+    match value {
+        Value::Object(map) => {
+            if map.len() == 1 {
+                if let Some(Value::String(html)) = map.get(HTML_MARKER) {
+                    *value = Value::String(sanitizer(html));
+                    return;
+                }
+            }
+            for value in map.values_mut() {
+                sanitize(value, sanitizer);
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                sanitize(value, sanitizer);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_sanitize() {
+        let mut props = json!({
+            "title": "<b>plain</b>",
+            "body": Html("<p>hi<script></script></p>".to_string()),
+            "comments": [{ "body": Html("<i>ok</i>".to_string()) }],
+        });
+        sanitize(&mut props, &|html| html.replace("<script></script>", ""));
+        assert_eq!(
+            props,
+            json!({
+                "title": "<b>plain</b>",
+                "body": "<p>hi</p>",
+                "comments": [{ "body": "<i>ok</i>" }],
+            })
+        );
+    }
+}
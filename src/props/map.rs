@@ -0,0 +1,221 @@
+use super::PropsError;
+use crate::config::PropErrors;
+use crate::partial::Partial;
+use crate::request::Request;
+use futures_util::future::{join_all, BoxFuture};
+use serde::Serialize;
+use serde_json::Value;
+use std::future::Future;
+
+enum Entry {
+    Value(Result<Value, PropsError>),
+    Lazy(Box<dyn FnOnce() -> Result<Value, PropsError> + Send>),
+    Future(BoxFuture<'static, Result<Value, PropsError>>),
+}
+
+/// Map-style props whose values can be plain values, lazy closures or
+/// futures.
+///
+/// [resolve](Map::resolve) only evaluates the props included in the
+/// response -- during a partial reload, that's only the requested
+/// props -- and awaits all futures concurrently:
+///
+/// ```rust
+/// use axum_inertia::{props, Inertia};
+/// use axum::response::IntoResponse;
+///
+/// async fn load_posts() -> Vec<String> {
+///     vec!["post one".to_string()]
+/// }
+///
+/// async fn load_users() -> Vec<String> {
+///     vec!["mikey".to_string()]
+/// }
+///
+/// async fn dashboard(i: Inertia) -> impl IntoResponse {
+///     let props = props::Map::new()
+///         .value("title", "Dashboard")
+///         .lazy("stats", || vec![1, 2, 3])
+///         .future("posts", load_posts())
+///         .future("users", load_users())
+///         .resolve(i.request())
+///         .await
+///         .unwrap();
+///     i.render("Dashboard", props)
+/// }
+/// ```
+#[derive(Default)]
+pub struct Map {
+    entries: Vec<(String, Entry)>,
+}
+
+impl Map {
+    pub fn new() -> Map {
+        Self::default()
+    }
+
+    /// Adds a plain value.
+    pub fn value(mut self, key: &str, value: impl Serialize) -> Self {
+        let value = serde_json::to_value(value).map_err(PropsError::from);
+        self.entries.push((key.to_string(), Entry::Value(value)));
+        self
+    }
+
+    /// Adds a value computed by `f`, which is only called if the prop
+    /// is included in the response.
+    pub fn lazy<F, T>(mut self, key: &str, f: F) -> Self
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Serialize,
+    {
+        let lazy = Box::new(move || Ok(serde_json::to_value(f())?));
+        self.entries.push((key.to_string(), Entry::Lazy(lazy)));
+        self
+    }
+
+    /// Adds a value computed by `future`, which is only awaited if the
+    /// prop is included in the response.
+    pub fn future<F>(mut self, key: &str, future: F) -> Self
+    where
+        F: Future + Send + 'static,
+        F::Output: Serialize,
+    {
+        let future = Box::pin(async move { Ok(serde_json::to_value(future.await)?) });
+        self.entries.push((key.to_string(), Entry::Future(future)));
+        self
+    }
+
+    /// Resolves the props included in a response to `request`.
+    ///
+    /// Fails if any prop fails to serialize; the error's
+    /// [key](PropsError::key) names the prop. Use
+    /// [Inertia::resolve_map](crate::Inertia::resolve_map) to respect
+    /// the configured [PropErrors] mode.
+    pub async fn resolve(self, request: &Request) -> Result<Value, PropsError> {
+        self.resolve_partial(request.partial(), PropErrors::Strict)
+            .await
+    }
+
+    pub(crate) async fn resolve_partial(
+        self,
+        partial: Option<&Partial>,
+        mode: PropErrors,
+    ) -> Result<Value, PropsError> {
+        let mut props = serde_json::Map::new();
+        let mut resolved = Vec::new();
+        let mut futures = Vec::new();
+        for (key, entry) in self.entries {
+            if partial.is_some_and(|partial| !partial.props.contains(&key)) {
+                continue;
+            }
+            match entry {
+                Entry::Value(value) => resolved.push((key, value)),
+                Entry::Lazy(f) => resolved.push((key, f())),
+                Entry::Future(future) => futures.push(async move { (key, future.await) }),
+            }
+        }
+        resolved.extend(join_all(futures).await);
+
+        for (key, value) in resolved {
+            match (value, mode) {
+                (Ok(value), _) => {
+                    props.insert(key, value);
+                }
+                (Err(err), PropErrors::Strict) => return Err(err.with_key(key)),
+                (Err(err), PropErrors::Lenient) => {
+                    let err = err.with_key(key);
+                    tracing::error!(error = %err, "skipping inertia prop");
+                }
+            }
+        }
+        Ok(Value::Object(props))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn test_resolves_all_entries() {
+        let value = Map::new()
+            .value("a", 1)
+            .lazy("b", || "two")
+            .future("c", async { vec![3] })
+            .resolve_partial(None, PropErrors::Strict)
+            .await
+            .unwrap();
+
+        assert_eq!(value, json!({ "a": 1, "b": "two", "c": [3] }));
+    }
+
+    #[tokio::test]
+    async fn test_resolves_futures_concurrently() {
+        let sleep = || tokio::time::sleep(Duration::from_millis(50));
+        let start = Instant::now();
+        Map::new()
+            .future("a", async move { sleep().await })
+            .future("b", async move { sleep().await })
+            .future("c", async move { sleep().await })
+            .resolve_partial(None, PropErrors::Strict)
+            .await
+            .unwrap();
+
+        assert!(start.elapsed() < Duration::from_millis(140));
+    }
+
+    #[tokio::test]
+    async fn test_skips_unrequested_entries() {
+        let called = Arc::new(AtomicBool::new(false));
+        let lazy_called = called.clone();
+        let future_called = called.clone();
+        let partial = Partial {
+            props: vec!["a".to_string()],
+            component: "Testing".to_string(),
+        };
+
+        let value = Map::new()
+            .value("a", 1)
+            .lazy("b", move || lazy_called.store(true, Ordering::SeqCst))
+            .future(
+                "c",
+                async move { future_called.store(true, Ordering::SeqCst) },
+            )
+            .resolve_partial(Some(&partial), PropErrors::Strict)
+            .await
+            .unwrap();
+
+        assert_eq!(value, json!({ "a": 1 }));
+        assert!(!called.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_reports_failed_keys() {
+        struct Failing;
+
+        impl Serialize for Failing {
+            fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom("boom"))
+            }
+        }
+
+        let map = || Map::new().value("a", 1).future("b", async { Failing });
+
+        let err = map()
+            .resolve_partial(None, PropErrors::Strict)
+            .await
+            .unwrap_err();
+        assert_eq!(err.key(), Some("b"));
+        assert_eq!(err.to_string(), "failed to serialize prop `b`: boom");
+
+        let value = map()
+            .resolve_partial(None, PropErrors::Lenient)
+            .await
+            .unwrap();
+        assert_eq!(value, json!({ "a": 1 }));
+    }
+}
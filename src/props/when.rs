@@ -0,0 +1,116 @@
+use super::{into_object, Props, PropsError};
+use crate::request::Request;
+use http::HeaderName;
+use serde_json::Value;
+
+type HeaderPredicate = dyn Fn(&str) -> bool + Send + Sync;
+
+/// Props that are only included when a request header matches, e.g.
+/// for feature flags sent by the client.
+///
+/// Share them on every page with
+/// [InertiaConfig::share_when](crate::InertiaConfig::share_when), or
+/// resolve them in a handler:
+///
+/// ```rust
+/// use axum::response::IntoResponse;
+/// use axum_inertia::props::{contains, PropsExt, When};
+/// use axum_inertia::Inertia;
+/// use serde_json::json;
+///
+/// async fn dashboard(i: Inertia) -> impl IntoResponse {
+///     let beta = When::header("X-Feature-Flags", contains("beta"), json!({ "charts": [] }))
+///         .resolve(i.request())
+///         .unwrap();
+///     i.render("Dashboard", json!({ "stats": [] }).merge(beta))
+/// }
+/// ```
+pub struct When {
+    header: HeaderName,
+    predicate: Box<HeaderPredicate>,
+    props: Box<dyn Props + Send + Sync>,
+}
+
+impl When {
+    /// Includes `props` when the `name` header is valid text and its
+    /// value satisfies `predicate`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` isn't a valid header name.
+    pub fn header<F, P>(name: &str, predicate: F, props: P) -> When
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+        P: Props + Send + Sync + 'static,
+    {
+        When {
+            header: HeaderName::try_from(name).expect("invalid header name"),
+            predicate: Box::new(predicate),
+            props: Box::new(props),
+        }
+    }
+
+    /// Returns the header the condition reads.
+    pub fn header_name(&self) -> &HeaderName {
+        &self.header
+    }
+
+    /// Returns true if the condition holds for `request`.
+    pub fn matches(&self, request: &Request) -> bool {
+        request
+            .headers()
+            .get_all(&self.header)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .any(|value| (self.predicate)(value))
+    }
+
+    /// Serializes the props for `request`, or returns an empty object
+    /// if the condition doesn't hold.
+    pub fn resolve(&self, request: &Request) -> Result<Value, PropsError> {
+        if !self.matches(request) {
+            return Ok(Value::Object(serde_json::Map::new()));
+        }
+        let props = into_object(self.props.serialize(request.partial())?)?;
+        Ok(Value::Object(props))
+    }
+}
+
+/// Returns a [When] predicate that holds if a comma-separated header
+/// value lists `token`, ignoring ascii case.
+pub fn contains(token: &str) -> impl Fn(&str) -> bool + Send + Sync + 'static {
+    let token = token.to_string();
+    move |value: &str| {
+        value
+            .split(',')
+            .any(|item| item.trim().eq_ignore_ascii_case(&token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_when_header() {
+        let when = When::header(
+            "X-Feature-Flags",
+            contains("beta"),
+            json!({ "charts": [1, 2] }),
+        );
+
+        let mut request = Request::test_request();
+        assert_eq!(when.resolve(&request).unwrap(), json!({}));
+
+        request
+            .headers
+            .insert("x-feature-flags", "dark-mode, Beta".parse().unwrap());
+        assert_eq!(when.resolve(&request).unwrap(), json!({ "charts": [1, 2] }));
+
+        request
+            .headers
+            .insert("x-feature-flags", "betamax".parse().unwrap());
+        assert!(!when.matches(&request));
+    }
+}
@@ -0,0 +1,319 @@
+//! Building blocks of the [inertia.js protocol].
+//!
+//! This module exports the header names, status codes and the page
+//! object used by the rest of the crate. They are public so that
+//! downstream crates (middleware, test helpers, other adapters) can
+//! speak the protocol without hard-coding strings.
+//!
+//! ```rust
+//! use axum_inertia::protocol::{self, X_INERTIA};
+//! use http::{HeaderMap, Method, StatusCode};
+//!
+//! let mut headers = HeaderMap::new();
+//! headers.insert(X_INERTIA, "true".parse().unwrap());
+//!
+//! assert_eq!(protocol::redirect_status(&Method::PUT), StatusCode::SEE_OTHER);
+//! ```
+//!
+//! [inertia.js protocol]: https://inertiajs.com/the-protocol
+
+use http::{HeaderName, HeaderValue, Method, StatusCode};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+
+pub use crate::page::{Modal, Page};
+
+/// Set to `true` on requests made by the Inertia client, and on
+/// Inertia json responses.
+pub const X_INERTIA: HeaderName = HeaderName::from_static("x-inertia");
+
+/// The current asset version, sent by both the client and the server.
+pub const X_INERTIA_VERSION: HeaderName = HeaderName::from_static("x-inertia-version");
+
+/// Comma-separated list of props requested in a partial reload.
+pub const X_INERTIA_PARTIAL_DATA: HeaderName = HeaderName::from_static("x-inertia-partial-data");
+
+/// The component a partial reload was requested for.
+pub const X_INERTIA_PARTIAL_COMPONENT: HeaderName =
+    HeaderName::from_static("x-inertia-partial-component");
+
+/// The protocol version of the Inertia client, e.g. `1` or `2.0.3`.
+/// Only the major version is used. See [ProtocolVersion].
+pub const X_INERTIA_PROTOCOL: HeaderName = HeaderName::from_static("x-inertia-protocol");
+
+/// Where the client should navigate to on a `409 Conflict` response.
+pub const X_INERTIA_LOCATION: HeaderName = HeaderName::from_static("x-inertia-location");
+
+/// The names of the Inertia headers, read from requests and written
+/// to responses. Defaults to the standard names.
+///
+/// Apps behind proxies that strip or rename `X-Inertia-*` headers can
+/// use other names with
+/// [InertiaConfig::header_names](crate::InertiaConfig::header_names).
+/// The client must be set up to send and read the same names.
+///
+/// Change names with the setters, since more headers may be added:
+///
+/// ```rust
+/// use axum_inertia::protocol::HeaderNames;
+/// use http::HeaderName;
+///
+/// let names = HeaderNames::default().version(HeaderName::from_static("x-app-version"));
+/// assert_eq!(names.version, "x-app-version");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HeaderNames {
+    pub inertia: HeaderName,
+    pub version: HeaderName,
+    pub partial_data: HeaderName,
+    pub partial_component: HeaderName,
+    pub protocol: HeaderName,
+    pub location: HeaderName,
+}
+
+impl Default for HeaderNames {
+    fn default() -> Self {
+        HeaderNames {
+            inertia: X_INERTIA,
+            version: X_INERTIA_VERSION,
+            partial_data: X_INERTIA_PARTIAL_DATA,
+            partial_component: X_INERTIA_PARTIAL_COMPONENT,
+            protocol: X_INERTIA_PROTOCOL,
+            location: X_INERTIA_LOCATION,
+        }
+    }
+}
+
+impl HeaderNames {
+    /// Sets the name of the [X_INERTIA] header.
+    pub fn inertia(mut self, name: HeaderName) -> Self {
+        self.inertia = name;
+        self
+    }
+
+    /// Sets the name of the [X_INERTIA_VERSION] header.
+    pub fn version(mut self, name: HeaderName) -> Self {
+        self.version = name;
+        self
+    }
+
+    /// Sets the name of the [X_INERTIA_PARTIAL_DATA] header.
+    pub fn partial_data(mut self, name: HeaderName) -> Self {
+        self.partial_data = name;
+        self
+    }
+
+    /// Sets the name of the [X_INERTIA_PARTIAL_COMPONENT] header.
+    pub fn partial_component(mut self, name: HeaderName) -> Self {
+        self.partial_component = name;
+        self
+    }
+
+    /// Sets the name of the [X_INERTIA_PROTOCOL] header.
+    pub fn protocol(mut self, name: HeaderName) -> Self {
+        self.protocol = name;
+        self
+    }
+
+    /// Sets the name of the [X_INERTIA_LOCATION] header.
+    pub fn location(mut self, name: HeaderName) -> Self {
+        self.location = name;
+        self
+    }
+}
+
+/// Status code used to tell the client to do a full page visit, e.g.
+/// on an asset version mismatch or an external redirect.
+///
+/// More info at: https://inertiajs.com/the-protocol#asset-versioning
+pub const CONFLICT: StatusCode = StatusCode::CONFLICT;
+
+/// The version of the page object sent to the client.
+///
+/// Version 1 clients don't know about fields added in version 2, such
+/// as `deferredProps`, so they are omitted from version 1 page
+/// objects. Clients may announce their version with the
+/// [X_INERTIA_PROTOCOL] header; otherwise, the version configured
+/// with [InertiaConfig::protocol_version] is used.
+///
+/// [InertiaConfig::protocol_version]: crate::config::InertiaConfig::protocol_version
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProtocolVersion {
+    V1,
+    #[default]
+    V2,
+}
+
+impl ProtocolVersion {
+    /// Parses a version hint such as `1`, `v2` or `2.0.3`. Returns
+    /// None for unknown versions.
+    pub fn parse(s: &str) -> Option<ProtocolVersion> {
+        let s = s.trim();
+        let s = s.strip_prefix(['v', 'V']).unwrap_or(s);
+        match s.split('.').next() {
+            Some("1") => Some(ProtocolVersion::V1),
+            Some("2") => Some(ProtocolVersion::V2),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the redirect status code to use after a request with the
+/// given method.
+///
+/// Redirects after `PUT`, `PATCH` or `DELETE` requests must use `303
+/// See Other` so that the follow-up request is a `GET`; everything
+/// else may use a regular `302 Found`.
+///
+/// More info at: https://inertiajs.com/redirects#303-response-code
+pub fn redirect_status(method: &Method) -> StatusCode {
+    if method == Method::PUT || method == Method::PATCH || method == Method::DELETE {
+        StatusCode::SEE_OTHER
+    } else {
+        StatusCode::FOUND
+    }
+}
+
+/// Characters percent-encoded in urls sent as header values, besides
+/// non-ascii characters: the ones that are never valid in urls.
+const URL_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'<')
+    .add(b'>')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Returns `url` as a header value, e.g. for `Location` or
+/// `X-Inertia-Location`, percent-encoding non-ascii characters,
+/// spaces and other characters that aren't valid in urls. Valid urls,
+/// including percent-encoded ones, are unchanged.
+///
+/// ```rust
+/// use axum_inertia::protocol::url_header_value;
+///
+/// assert_eq!(url_header_value("/users/josé martí"), "/users/jos%C3%A9%20mart%C3%AD");
+/// assert_eq!(url_header_value("/search?q=a%20b"), "/search?q=a%20b");
+/// ```
+pub fn url_header_value(url: &str) -> HeaderValue {
+    let encoded = utf8_percent_encode(url, URL_ENCODE_SET).to_string();
+    HeaderValue::try_from(encoded).expect("percent-encoded urls are valid header values")
+}
+
+/// Returns true if the status is a conflict response that the client
+/// will treat as a full page visit.
+pub fn is_conflict(status: StatusCode) -> bool {
+    status == CONFLICT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Map, Value};
+
+    // The page object example of the v1 protocol documentation,
+    // copied verbatim from
+    // https://inertiajs.com/the-protocol#the-page-object.
+    const DOCS_EXAMPLE: &str = r#"{
+        "component": "Event",
+        "props": {
+            "errors": {},
+            "event": {
+                "id": 80,
+                "title": "Birthday party",
+                "start_date": "2019-06-02",
+                "description": "Come out and celebrate Jonathan's 36th birthday party!"
+            }
+        },
+        "url": "/events/80",
+        "version": "c32b8e4965f418ad16eaebba1d4e960f"
+    }"#;
+
+    // Not from the documentation: the example above without an asset
+    // version, written for this crate.
+    const UNVERSIONED_EXAMPLE: &str = r#"{"component":"Event","props":{"event":{"id":80,"title":"Birthday party"}},"url":"/events/80","version":null}"#;
+
+    #[test]
+    fn test_parses_protocol_versions() {
+        assert_eq!(ProtocolVersion::parse("1"), Some(ProtocolVersion::V1));
+        assert_eq!(ProtocolVersion::parse(" v2 "), Some(ProtocolVersion::V2));
+        assert_eq!(ProtocolVersion::parse("2.0.3"), Some(ProtocolVersion::V2));
+        assert_eq!(ProtocolVersion::parse("3"), None);
+        assert_eq!(ProtocolVersion::parse(""), None);
+    }
+
+    #[test]
+    fn test_url_header_value() {
+        assert_eq!(
+            url_header_value("/users/1?tab=posts#top"),
+            "/users/1?tab=posts#top"
+        );
+        assert_eq!(url_header_value("/café"), "/caf%C3%A9");
+        assert_eq!(url_header_value("/a b\n\"c\""), "/a%20b%0A%22c%22");
+        assert_eq!(
+            url_header_value("https://例え.jp/ü"),
+            "https://%E4%BE%8B%E3%81%88.jp/%C3%BC"
+        );
+    }
+
+    #[test]
+    fn test_header_names_match_protocol() {
+        assert_eq!(X_INERTIA, "X-Inertia");
+        assert_eq!(X_INERTIA_VERSION, "X-Inertia-Version");
+        assert_eq!(X_INERTIA_PARTIAL_DATA, "X-Inertia-Partial-Data");
+        assert_eq!(X_INERTIA_PARTIAL_COMPONENT, "X-Inertia-Partial-Component");
+        assert_eq!(X_INERTIA_LOCATION, "X-Inertia-Location");
+    }
+
+    #[test]
+    fn test_page_matches_docs_example() {
+        let page = Page {
+            component: "Event".into(),
+            props: json!({
+                "errors": {},
+                "event": {
+                    "id": 80,
+                    "title": "Birthday party",
+                    "start_date": "2019-06-02",
+                    "description": "Come out and celebrate Jonathan's 36th birthday party!",
+                },
+            }),
+            url: "/events/80".to_string(),
+            version: Some("c32b8e4965f418ad16eaebba1d4e960f".to_string()),
+            modal: None,
+            deferred_props: None,
+            extra: Map::new(),
+        };
+        let expected: Value = serde_json::from_str(DOCS_EXAMPLE).unwrap();
+
+        assert_eq!(serde_json::to_value(&page).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_page_serializes_missing_version_as_null() {
+        let page = Page {
+            component: "Event".into(),
+            props: json!({ "event": { "id": 80, "title": "Birthday party" } }),
+            url: "/events/80".to_string(),
+            version: None,
+            modal: None,
+            deferred_props: None,
+            extra: Map::new(),
+        };
+        let expected: Value = serde_json::from_str(UNVERSIONED_EXAMPLE).unwrap();
+
+        assert_eq!(serde_json::to_value(&page).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_redirect_status() {
+        assert_eq!(redirect_status(&Method::GET), StatusCode::FOUND);
+        assert_eq!(redirect_status(&Method::POST), StatusCode::FOUND);
+        assert_eq!(redirect_status(&Method::PUT), StatusCode::SEE_OTHER);
+        assert_eq!(redirect_status(&Method::PATCH), StatusCode::SEE_OTHER);
+        assert_eq!(redirect_status(&Method::DELETE), StatusCode::SEE_OTHER);
+        assert!(is_conflict(StatusCode::CONFLICT));
+        assert!(!is_conflict(StatusCode::OK));
+    }
+}
@@ -0,0 +1,269 @@
+//! Shared props computed per request, e.g. from a database.
+//!
+//! A [PropProvider] resolves one shared prop from the Inertia
+//! [Request]. Providers are registered on the config and run when the
+//! [Inertia](crate::Inertia) extractor is extracted, after the asset
+//! version check, and concurrently, so their props are ready when the
+//! handler renders:
+//!
+//! ```rust
+//! use async_trait::async_trait;
+//! use axum_inertia::{props::PropsError, provider::PropProvider, vite, Request};
+//! use serde_json::{json, Value};
+//!
+//! struct Locale;
+//!
+//! #[async_trait]
+//! impl PropProvider for Locale {
+//!     fn key(&self) -> Option<&str> {
+//!         Some("locale")
+//!     }
+//!
+//!     async fn provide(&self, request: &Request) -> Result<(String, Value), PropsError> {
+//!         let locale = request
+//!             .headers()
+//!             .get("accept-language")
+//!             .and_then(|value| value.to_str().ok())
+//!             .unwrap_or("en");
+//!         Ok(("locale".to_string(), json!(locale)))
+//!     }
+//! }
+//!
+//! let inertia = vite::Development::default()
+//!     .into_config()
+//!     .prop_provider(Locale);
+//! ```
+//!
+//! Any tower [Service] of Inertia requests that responds with a `(key,
+//! value)` pair is a provider too, through
+//! [InertiaConfig::prop_service](crate::InertiaConfig::prop_service),
+//! so providers can be wrapped in tower middleware like timeouts or
+//! retries, and tested on their own.
+//!
+//! Like other shared props, a provided prop is left out if the handler
+//! renders a prop with the same key, or if a partial reload doesn't ask
+//! for it. Providers that declare their [key](PropProvider::key) aren't
+//! run at all for partial reloads of other props. Failures are handled by the
+//! [PropErrors](crate::config::PropErrors) mode: a strict failure fails
+//! the render.
+
+use crate::props::PropsError;
+use crate::request::Request;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::error::Error;
+use tower_service::Service;
+
+/// Resolves a shared prop for a request. See the [module
+/// documentation](self).
+#[async_trait]
+pub trait PropProvider: Send + Sync {
+    /// Returns the key of the prop, if known before providing it, so
+    /// the provider can be skipped when a partial reload doesn't ask
+    /// for it. Defaults to `None`, which always runs the provider.
+    fn key(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the key and value of the prop.
+    async fn provide(&self, request: &Request) -> Result<(String, Value), PropsError>;
+}
+
+/// A [PropProvider] backed by a tower [Service].
+#[derive(Clone, Debug)]
+pub struct ServiceProvider<S> {
+    service: S,
+}
+
+impl<S> ServiceProvider<S> {
+    pub fn new(service: S) -> ServiceProvider<S> {
+        ServiceProvider { service }
+    }
+}
+
+#[async_trait]
+impl<S> PropProvider for ServiceProvider<S>
+where
+    S: Service<Request, Response = (String, Value)> + Clone + Send + Sync,
+    S::Future: Send,
+    S::Error: Into<Box<dyn Error + Send + Sync>>,
+{
+    async fn provide(&self, request: &Request) -> Result<(String, Value), PropsError> {
+        // Each call gets its own clone, as tower services are driven
+        // to readiness before being called.
+        let mut service = self.service.clone();
+        std::future::poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .map_err(PropsError::new)?;
+        service.call(request.clone()).await.map_err(PropsError::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Inertia, InertiaConfig};
+    use axum::{routing::get, Router};
+    use serde_json::json;
+    use std::convert::Infallible;
+    use std::future::{ready, Ready};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+    use tokio::net::TcpListener;
+    use tokio::sync::Barrier;
+
+    struct Url;
+
+    #[async_trait]
+    impl PropProvider for Url {
+        async fn provide(&self, request: &Request) -> Result<(String, Value), PropsError> {
+            Ok(("url".to_string(), json!(request.url())))
+        }
+    }
+
+    /// Waits for the other provider sharing its barrier, so requests
+    /// only complete if providers run concurrently.
+    struct Waiting {
+        key: &'static str,
+        barrier: Arc<Barrier>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl PropProvider for Waiting {
+        fn key(&self) -> Option<&str> {
+            Some(self.key)
+        }
+
+        async fn provide(&self, _: &Request) -> Result<(String, Value), PropsError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.barrier.wait().await;
+            Ok((self.key.to_string(), json!(true)))
+        }
+    }
+
+    #[derive(Clone)]
+    struct Xhr;
+
+    impl Service<Request> for Xhr {
+        type Response = (String, Value);
+        type Error = Infallible;
+        type Future = Ready<Result<(String, Value), Infallible>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request) -> Self::Future {
+            ready(Ok(("xhr".to_string(), json!(request.is_xhr()))))
+        }
+    }
+
+    #[tokio::test]
+    async fn it_shares_provided_props() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|i: Inertia| async move { i.render("Home", json!({ "url": "mine" })) }),
+            )
+            .route(
+                "/page",
+                get(|i: Inertia| async move { i.render("Page", json!({})) }),
+            )
+            .with_state(
+                InertiaConfig::new(None, Box::new(|props| props.to_string()))
+                    .prop_provider(Url)
+                    .prop_service(Xhr),
+            );
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+        let page = |path: &'static str| {
+            let client = client.clone();
+            async move {
+                let res = client
+                    .get(format!("http://{}{}", &addr, path))
+                    .header("X-Inertia", "true")
+                    .send()
+                    .await
+                    .unwrap();
+                serde_json::from_str::<Value>(&res.text().await.unwrap()).unwrap()
+            }
+        };
+
+        assert_eq!(
+            page("/page").await["props"],
+            json!({ "url": "/page", "xhr": true })
+        );
+        assert_eq!(
+            page("/").await["props"],
+            json!({ "url": "mine", "xhr": true })
+        );
+    }
+
+    #[tokio::test]
+    async fn it_runs_providers_concurrently_and_skips_unrequested_keys() {
+        let barrier = Arc::new(Barrier::new(2));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let waiting = |key| Waiting {
+            key,
+            barrier: barrier.clone(),
+            calls: calls.clone(),
+        };
+        let app = Router::new()
+            .route(
+                "/",
+                get(|i: Inertia| async move { i.render("Home", json!({ "c": 1 })) }),
+            )
+            .with_state(
+                InertiaConfig::new(None, Box::new(|props| props.to_string()))
+                    .prop_provider(waiting("a"))
+                    .prop_provider(waiting("b")),
+            );
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+        let res = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            client
+                .get(format!("http://{}/", &addr))
+                .header("X-Inertia", "true")
+                .send(),
+        )
+        .await
+        .expect("providers ran one after the other")
+        .unwrap();
+        let page: Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["props"], json!({ "a": true, "b": true, "c": 1 }));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let res = client
+            .get(format!("http://{}/", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Inertia-Partial-Component", "Home")
+            .header("X-Inertia-Partial-Data", "c")
+            .send()
+            .await
+            .unwrap();
+        let page: Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["props"], json!({ "c": 1 }));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,160 @@
+//! The rejection of the [Inertia](crate::Inertia) and
+//! [Request](crate::Request) extractors.
+//!
+//! [InertiaRejection] implements [IntoResponse], so handlers can
+//! return it as is. Apps can also match on it to customize the
+//! response, e.g. with
+//! [WithRejection](https://docs.rs/axum-extra/latest/axum_extra/extract/struct.WithRejection.html)
+//! or in middleware:
+//!
+//! ```rust
+//! use axum::response::{IntoResponse, Response};
+//! use axum_inertia::{rejection::InertiaRejection, Inertia};
+//! use serde_json::json;
+//!
+//! async fn home(i: Result<Inertia, InertiaRejection>) -> Response {
+//!     match i {
+//!         Ok(i) => i.render("Home", json!({})).into_response(),
+//!         Err(rejection @ InertiaRejection::VersionConflict { .. }) => {
+//!             tracing::info!(%rejection, "stale client");
+//!             rejection.into_response()
+//!         }
+//!         Err(rejection) => rejection.into_response(),
+//!     }
+//! }
+//! ```
+
+use crate::config::ConflictBody;
+use crate::protocol;
+use axum::response::{Html, IntoResponse, Json, Response};
+use http::{HeaderMap, HeaderName, StatusCode};
+use std::error::Error;
+use std::fmt;
+
+const CONFLICT_MESSAGE: &str = "The asset version has changed. Please reload the page.";
+
+/// Rejection of the Inertia extractors.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum InertiaRejection {
+    /// The client's asset version doesn't match. Responds with `409
+    /// Conflict`, so that the client reloads `location`.
+    VersionConflict {
+        location: String,
+        body: ConflictBody,
+        /// The name of the location header, `X-Inertia-Location` by
+        /// default.
+        header: HeaderName,
+    },
+    /// An Inertia request header has a value that isn't visible
+    /// ascii. Responds with `400 Bad Request` and a json body with
+    /// `message` and `header` fields. See
+    /// [InertiaConfig::invalid_headers](crate::InertiaConfig::invalid_headers).
+    BadHeader { name: HeaderName },
+    /// No [InertiaConfig](crate::InertiaConfig) was provided, as
+    /// router state or with an [InertiaLayer](crate::layer::InertiaLayer).
+    /// Responds with `500 Internal Server Error`.
+    MissingConfig,
+}
+
+impl fmt::Display for InertiaRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InertiaRejection::VersionConflict { location, .. } => {
+                write!(f, "asset version conflict, reload {}", location)
+            }
+            InertiaRejection::BadHeader { name } => write!(f, "invalid `{}` header", name),
+            InertiaRejection::MissingConfig => write!(
+                f,
+                "Missing InertiaConfig. Provide it as router state or with an InertiaLayer."
+            ),
+        }
+    }
+}
+
+impl Error for InertiaRejection {}
+
+impl IntoResponse for InertiaRejection {
+    fn into_response(self) -> Response {
+        match self {
+            InertiaRejection::VersionConflict {
+                location,
+                body,
+                header,
+            } => {
+                #[cfg_attr(not(feature = "otel"), allow(unused_mut))]
+                let mut response = conflict_response(&location, body, header);
+                #[cfg(feature = "otel")]
+                response
+                    .extensions_mut()
+                    .insert(crate::otel::SpanInfo::version_conflict());
+                response
+            }
+            InertiaRejection::BadHeader { ref name } => {
+                let body = serde_json::json!({
+                    "message": self.to_string(),
+                    "header": name.as_str(),
+                });
+                (StatusCode::BAD_REQUEST, Json(body)).into_response()
+            }
+            InertiaRejection::MissingConfig => {
+                (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
+            }
+        }
+    }
+}
+
+/// Builds the `409 Conflict` response for an asset version mismatch.
+fn conflict_response(location: &str, body: ConflictBody, header: HeaderName) -> Response {
+    let mut headers = HeaderMap::new();
+    headers.insert(header, protocol::url_header_value(location));
+
+    match body {
+        ConflictBody::Empty => (protocol::CONFLICT, headers).into_response(),
+        ConflictBody::Html => {
+            let body = maud::html! {
+                p { (CONFLICT_MESSAGE) " " a href=(location) { "Reload" } }
+            };
+            (protocol::CONFLICT, headers, Html(body.into_string())).into_response()
+        }
+        ConflictBody::Json => {
+            let body = serde_json::json!({ "message": CONFLICT_MESSAGE, "location": location });
+            (protocol::CONFLICT, headers, Json(body)).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::InvalidHeaders;
+    use crate::protocol::{HeaderNames, X_INERTIA_VERSION};
+    use crate::Request;
+    use http::HeaderValue;
+    use http_body_util::BodyExt;
+
+    #[tokio::test]
+    async fn test_reports_bad_headers() {
+        let (parts, _) = http::Request::builder()
+            .header(X_INERTIA_VERSION, HeaderValue::from_bytes(b"\xff").unwrap())
+            .body(())
+            .unwrap()
+            .into_parts();
+
+        let rejection = Request::from_parts(&parts).unwrap_err();
+        assert!(
+            matches!(&rejection, InertiaRejection::BadHeader { name } if name == X_INERTIA_VERSION)
+        );
+        let response = rejection.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["header"], "x-inertia-version");
+        assert_eq!(body["message"], "invalid `x-inertia-version` header");
+
+        let request =
+            Request::from_parts_with_names(&parts, &HeaderNames::default(), InvalidHeaders::Strip)
+                .unwrap();
+        assert_eq!(request.version, None);
+    }
+}
@@ -0,0 +1,79 @@
+//! Debug-build checks for [Inertia](crate::Inertia) extractors that
+//! are dropped without responding, or respond more than once.
+//!
+//! Both usually point at a handler bug, e.g. an early return that
+//! sends an empty `200 OK`. The checks log `tracing` warnings, and do
+//! nothing in release builds.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Tracks whether an extractor, or any of its clones, responded.
+#[derive(Clone, Default)]
+pub(crate) struct RenderGuard {
+    state: Option<Arc<State>>,
+}
+
+struct State {
+    url: String,
+    rendered: AtomicBool,
+}
+
+impl RenderGuard {
+    pub(crate) fn new(url: &str) -> RenderGuard {
+        if !cfg!(debug_assertions) {
+            return RenderGuard::default();
+        }
+        RenderGuard {
+            state: Some(Arc::new(State {
+                url: url.to_string(),
+                rendered: AtomicBool::new(false),
+            })),
+        }
+    }
+
+    /// Records a response, warning if one was already sent.
+    pub(crate) fn rendered(&self) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        if state.rendered.swap(true, Ordering::Relaxed) {
+            tracing::warn!(
+                url = state.url,
+                "inertia response rendered more than once for one request"
+            );
+        }
+    }
+
+    #[cfg(test)]
+    fn is_rendered(&self) -> bool {
+        self.state
+            .as_ref()
+            .is_some_and(|state| state.rendered.load(Ordering::Relaxed))
+    }
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        if !*self.rendered.get_mut() {
+            tracing::warn!(
+                url = self.url,
+                "inertia extractor dropped without rendering a response"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_guard() {
+        let guard = RenderGuard::new("/users");
+        let clone = guard.clone();
+        assert!(!guard.is_rendered());
+        clone.rendered();
+        assert_eq!(guard.is_rendered(), cfg!(debug_assertions));
+    }
+}
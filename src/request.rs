@@ -1,21 +1,78 @@
-use crate::partial::Partial;
+use crate::config::{InertiaConfig, InvalidHeaders};
+use crate::partial::{parse_partial_data, Partial};
+use crate::protocol::{HeaderNames, ProtocolVersion};
+use crate::rejection::InertiaRejection;
 use async_trait::async_trait;
-use axum::extract::{FromRequestParts, OriginalUri};
-use http::{request::Parts, HeaderMap, HeaderValue, StatusCode};
+use axum::extract::{FromRequestParts, OriginalUri, RawPathParams};
+use http::{header::HOST, request::Parts, HeaderMap, HeaderName, Method, Uri};
+use serde_json::{Map, Value};
+
+const X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
 
 /// Inertia-related information in the request.
 ///
 /// See more info here: https://inertiajs.com/the-protocol.
 #[derive(Clone, Debug)]
-pub(crate) struct Request {
+pub struct Request {
     pub(crate) is_xhr: bool,
     pub(crate) version: Option<String>,
     /// When using nested services, the `url` will include the full path.
     pub(crate) url: String,
     pub(crate) partial: Option<Partial>,
+    pub(crate) protocol: Option<ProtocolVersion>,
+    /// The original request uri, including the query string.
+    pub(crate) uri: Uri,
+    pub(crate) method: Method,
+    pub(crate) headers: HeaderMap,
+    /// The name of the `X-Inertia` header, also set on responses.
+    pub(crate) inertia_header: HeaderName,
 }
 
 impl Request {
+    /// Returns true if this is an Inertia request, i.e. the
+    /// `X-Inertia` header is set to `true`.
+    pub fn is_xhr(&self) -> bool {
+        self.is_xhr
+    }
+
+    /// Returns the asset version sent by the client, if any.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Returns the original request path.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns partial reload data, if any.
+    pub fn partial(&self) -> Option<&Partial> {
+        self.partial.as_ref()
+    }
+
+    /// Returns the protocol version announced by the client, if any.
+    pub fn protocol(&self) -> Option<ProtocolVersion> {
+        self.protocol
+    }
+
+    /// Returns the original request uri, including the query string.
+    /// Like [url](Request::url), it includes the full path when using
+    /// nested routers.
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    /// Returns the request method.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Returns the request headers, e.g. for computing a locale from
+    /// `Accept-Language` in a [response hook](crate::InertiaConfig::on_response).
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
     #[cfg(test)]
     pub(crate) fn test_request() -> Request {
         Request {
@@ -23,6 +80,11 @@ impl Request {
             version: None,
             url: "/foo/bar".to_string(),
             partial: None,
+            protocol: None,
+            uri: Uri::from_static("/foo/bar"),
+            method: Method::GET,
+            headers: HeaderMap::new(),
+            inertia_header: HeaderNames::default().inertia,
         }
     }
 }
@@ -32,44 +94,65 @@ impl<S> FromRequestParts<S> for Request
 where
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, HeaderMap<HeaderValue>);
+    type Rejection = InertiaRejection;
 
-    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-        let original_uri = OriginalUri::from_request_parts(parts, state)
-            .await
-            .unwrap_or_else(|e| match e {});
-        let url = original_uri.0.path().to_string();
-        let is_xhr = parts
-            .headers
-            .get("X-Inertia")
-            .map(|s| s.to_str().map(|s| s == "true"))
-            .transpose()
-            .map_err(|_err| (StatusCode::BAD_REQUEST, HeaderMap::new()))?
-            .unwrap_or(false);
-        let version = parts
-            .headers
-            .get("X-Inertia-Version")
-            .map(|s| s.to_str().map(|s| s.to_string()))
-            .transpose()
-            .map_err(|_err| (StatusCode::BAD_REQUEST, HeaderMap::new()))?;
-        let partial_data = parts
-            .headers
-            .get("X-Inertia-Partial-Data")
-            .map(|s| s.to_str().map(|s| s.to_string()))
-            .transpose()
-            .map(|s| s.map(|s| s.split(',').map(|s| s.to_owned()).collect::<Vec<_>>()))
-            .map_err(|_err| (StatusCode::BAD_REQUEST, HeaderMap::new()))?;
-        let partial_component = parts
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Request::from_parts(parts)
+    }
+}
+
+impl Request {
+    /// Extracts Inertia request information from request parts
+    /// outside of an extractor, e.g. in middleware.
+    pub(crate) fn from_parts(parts: &Parts) -> Result<Request, InertiaRejection> {
+        Request::from_parts_with_names(parts, &HeaderNames::default(), InvalidHeaders::Reject)
+    }
+
+    /// Like [from_parts](Request::from_parts), reading the Inertia
+    /// headers with the given `names`. Invalid header values are
+    /// rejected, or ignored with [InvalidHeaders::Strip].
+    pub(crate) fn from_parts_with_names(
+        parts: &Parts,
+        names: &HeaderNames,
+        invalid: InvalidHeaders,
+    ) -> Result<Request, InertiaRejection> {
+        let uri = match parts.extensions.get::<OriginalUri>() {
+            Some(original_uri) => original_uri.0.clone(),
+            None => parts.uri.clone(),
+        };
+        let url = uri.path().to_string();
+        let header = |name: &HeaderName| match parts.headers.get(name).map(|s| s.to_str()) {
+            None => Ok(None),
+            Some(Ok(value)) => Ok(Some(value)),
+            Some(Err(_)) if invalid == InvalidHeaders::Strip => {
+                tracing::warn!(header = %name, "ignoring invalid inertia header");
+                Ok(None)
+            }
+            Some(Err(_)) => Err(bad_header(name.clone())),
+        };
+        let is_xhr = header(&names.inertia)?.is_some_and(|s| s == "true");
+        let version = header(&names.version)?.map(str::to_string);
+        let partial_data = header(&names.partial_data)?.map(parse_partial_data);
+        let partial_component = header(&names.partial_component)?.map(str::to_string);
+        // Unknown versions are ignored, since the header is only a hint.
+        let protocol = parts
             .headers
-            .get("X-Inertia-Partial-Component")
-            .map(|s| s.to_str().map(|s| s.to_string()))
-            .transpose()
-            .map_err(|_err| (StatusCode::BAD_REQUEST, HeaderMap::new()))?;
-        // TODO: trace warning if we have one of data/component without the other
-        // TODO: should this enforce is_xhr is true?
+            .get(&names.protocol)
+            .and_then(|s| s.to_str().ok())
+            .and_then(ProtocolVersion::parse);
+        // Incomplete partial reloads are ignored. InertiaGuardLayer
+        // rejects them instead.
         let partial = match (partial_data, partial_component) {
             (Some(props), Some(component)) => Some(Partial { props, component }),
-            _ => None,
+            (None, None) => None,
+            (Some(_), None) => {
+                tracing::warn!("ignoring inertia partial reload data without a component");
+                None
+            }
+            (None, Some(_)) => {
+                tracing::warn!("ignoring inertia partial reload component without data");
+                None
+            }
         };
 
         Ok(Request {
@@ -77,10 +160,75 @@ where
             version,
             url,
             partial,
+            protocol,
+            uri,
+            method: parts.method.clone(),
+            headers: parts.headers.clone(),
+            inertia_header: names.inertia.clone(),
         })
     }
 }
 
+fn bad_header(name: HeaderName) -> InertiaRejection {
+    InertiaRejection::BadHeader { name }
+}
+
+/// Returns the scheme and authority of the request, e.g.
+/// `https://example.com:8080`, or `None` unless the origin is
+/// configured or proxy headers are trusted.
+///
+/// Without a configured origin, the scheme is taken from the request
+/// uri or the `X-Forwarded-Proto` header, and the authority from the
+/// request uri or the `Host` header. Clients can send any of these, so
+/// they're only read behind a proxy that sets them.
+pub(crate) fn base_url(parts: &Parts, config: &InertiaConfig) -> Option<String> {
+    if let Some(origin) = config.origin_ref() {
+        return Some(origin.to_string());
+    }
+    if !config.trusts_proxy() {
+        return None;
+    }
+    let scheme = parts
+        .uri
+        .scheme_str()
+        .or_else(|| {
+            parts
+                .headers
+                .get(X_FORWARDED_PROTO)
+                .and_then(|v| v.to_str().ok())
+        })
+        .unwrap_or("http");
+    let authority = parts
+        .uri
+        .authority()
+        .map(|authority| authority.as_str())
+        .or_else(|| parts.headers.get(HOST).and_then(|v| v.to_str().ok()))?;
+    Some(format!("{}://{}", scheme, authority))
+}
+
+/// Returns the query parameters and matched path parameters of a
+/// request, path parameters taking precedence.
+pub(crate) async fn params(parts: &Parts) -> Map<String, Value> {
+    let mut params: Map<String, Value> = parts
+        .uri
+        .query()
+        .map(|query| {
+            form_urlencoded::parse(query.as_bytes())
+                .map(|(key, value)| (key.into_owned(), Value::String(value.into_owned())))
+                .collect()
+        })
+        .unwrap_or_default();
+    // Path parameters are only reachable through an extractor, which
+    // needs mutable parts.
+    let mut parts = parts.clone();
+    if let Ok(path) = RawPathParams::from_request_parts(&mut parts, &()).await {
+        for (key, value) in &path {
+            params.insert(key.to_string(), Value::String(value.to_string()));
+        }
+    }
+    params
+}
+
 #[cfg(test)]
 mod tests {
     use std::net::SocketAddr;
@@ -268,4 +416,26 @@ mod tests {
             .unwrap();
         assert_eq!(res.status(), StatusCode::OK);
     }
+
+    #[tokio::test]
+    async fn it_extracts_the_uri_method_and_headers() {
+        async fn handler(req: Request) {
+            assert_eq!(req.uri().to_string(), "/outer/test?page=2");
+            assert_eq!(req.method(), Method::POST);
+            assert_eq!(req.headers().get("Accept-Language").unwrap(), "nl");
+        }
+        let inner = Router::new().route("/test", axum::routing::post(handler));
+        let outer = Router::new().nest_service("/outer", inner);
+        let (_, addr) = spawn_test_app(outer).await;
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .post(format!("http://{}/outer/test?page=2", &addr))
+            .header("Accept-Language", "nl")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
 }
@@ -0,0 +1,224 @@
+//! Request ids, for correlating frontend error reports with backend
+//! logs.
+//!
+//! [InertiaRequestIdLayer] reads the request's `X-Request-Id` header,
+//! or generates an id if it's missing or invalid, and:
+//!
+//! - stores it as a [RequestId] in request extensions,
+//! - runs the request in a `request` tracing span with a `request_id`
+//!   field,
+//! - sets the `X-Request-Id` response header.
+//!
+//! Inertia pages rendered within the layer get a `requestId` prop with
+//! the id, so the frontend can attach it to its error reports:
+//!
+//! ```rust
+//! use axum::{response::IntoResponse, routing::get, Router};
+//! use axum_inertia::{request_id::InertiaRequestIdLayer, vite, Inertia};
+//! use serde_json::json;
+//!
+//! async fn home(i: Inertia) -> impl IntoResponse {
+//!     i.render("Home", json!({}))
+//! }
+//!
+//! let app: Router = Router::new()
+//!     .route("/", get(home))
+//!     .layer(InertiaRequestIdLayer::new())
+//!     .with_state(vite::Development::default().into_config());
+//! ```
+//!
+//! Incoming ids are trusted as is, as long as they're at most 128
+//! visible ascii characters; put the layer behind a proxy that sets or
+//! strips the header if clients shouldn't choose their ids.
+
+use crate::clock::{Rng, SystemRng};
+use async_trait::async_trait;
+use axum::extract::FromRequestParts;
+use axum::response::Response;
+use http::request::Parts;
+use http::{HeaderName, HeaderValue, StatusCode};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::Instrument;
+
+/// Header carrying the request id.
+pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+const MAX_LEN: usize = 128;
+
+/// The id of the current request. Also an extractor, for handlers
+/// within an [InertiaRequestIdLayer].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestId(String);
+
+impl RequestId {
+    /// Returns the id of an incoming header value, if it's valid.
+    fn from_header(value: &HeaderValue) -> Option<RequestId> {
+        let id = value.to_str().ok()?;
+        let valid =
+            !id.is_empty() && id.len() <= MAX_LEN && id.bytes().all(|byte| byte.is_ascii_graphic());
+        valid.then(|| RequestId(id.to_string()))
+    }
+
+    fn generate() -> RequestId {
+        RequestId(SystemRng.token())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for RequestId
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<RequestId>().cloned().ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "missing request id; is InertiaRequestIdLayer installed?",
+        ))
+    }
+}
+
+/// Layer that applies [SetRequestId] to a service.
+#[derive(Clone, Debug, Default)]
+pub struct InertiaRequestIdLayer;
+
+impl InertiaRequestIdLayer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for InertiaRequestIdLayer {
+    type Service = SetRequestId<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SetRequestId { inner }
+    }
+}
+
+/// Service that assigns request ids. See the [module
+/// documentation](self) for more.
+#[derive(Clone, Debug)]
+pub struct SetRequestId<S> {
+    inner: S,
+}
+
+impl<S, B> Service<http::Request<B>> for SetRequestId<S>
+where
+    S: Service<http::Request<B>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        // Take the service that was driven to readiness, leaving a
+        // clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let id = req
+            .headers()
+            .get(X_REQUEST_ID)
+            .and_then(RequestId::from_header)
+            .unwrap_or_else(RequestId::generate);
+        let span = tracing::info_span!("request", request_id = %id);
+        let header = HeaderValue::try_from(id.as_str()).expect("request ids are valid headers");
+        req.extensions_mut().insert(id);
+
+        Box::pin(
+            async move {
+                let mut res = inner.call(req).await?;
+                res.headers_mut().insert(X_REQUEST_ID, header);
+                Ok(res)
+            }
+            .instrument(span),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Inertia, InertiaConfig};
+    use axum::{routing::get, Router};
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn it_propagates_request_ids() {
+        let app = Router::new()
+            .route("/", get(|id: RequestId| async move { id.to_string() }))
+            .route(
+                "/page",
+                get(|i: Inertia| async move { i.render("Page", json!({})) }),
+            )
+            .layer(InertiaRequestIdLayer::new())
+            .with_state(InertiaConfig::new(
+                None,
+                Box::new(|props| props.to_string()),
+            ));
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/", &addr))
+            .header("X-Request-Id", "abc-123")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.headers()["x-request-id"], "abc-123");
+        assert_eq!(res.text().await.unwrap(), "abc-123");
+
+        let res = client
+            .get(format!("http://{}/", &addr))
+            .header("X-Request-Id", "has spaces")
+            .send()
+            .await
+            .unwrap();
+        let id = res.headers()["x-request-id"].to_str().unwrap().to_string();
+        assert_eq!(id.len(), 32);
+        assert_eq!(res.text().await.unwrap(), id);
+
+        let res = client
+            .get(format!("http://{}/page", &addr))
+            .header("X-Inertia", "true")
+            .header("X-Request-Id", "abc-123")
+            .send()
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["props"], json!({ "requestId": "abc-123" }));
+    }
+}
@@ -1,7 +1,21 @@
+use crate::bfcache;
 use crate::config::InertiaConfig;
-use crate::{page::Page, request::Request};
+use crate::defer::DeferredStream;
+use crate::error_page::RenderError;
+use crate::page::{Page, RawPage};
+use crate::protocol::{self, ProtocolVersion};
+use crate::request::Request;
+use crate::serializer::SerializeError;
+use crate::server_timing::ServerTiming;
+use crate::shell;
+use axum::body::Bytes;
 use axum::response::{Html, IntoResponse, Json};
-use http::HeaderMap;
+use http::header::{CACHE_CONTROL, CONTENT_TYPE, VARY};
+use http::{HeaderMap, HeaderValue, StatusCode};
+use serde_json::Value;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
 
 /// An Inertia response.
 ///
@@ -11,20 +25,529 @@ pub struct Response<'a> {
     pub(crate) request: Request,
     pub(crate) page: Page<'a>,
     pub(crate) config: InertiaConfig,
+    pub(crate) status: StatusCode,
+    pub(crate) timing: ServerTiming,
+    /// Set when the props failed to serialize.
+    pub(crate) error: Option<RenderError>,
+    /// Set to respond with `409 Conflict`, sending the client to this
+    /// url for a fresh visit. See
+    /// [PartialMismatch](crate::config::PartialMismatch).
+    pub(crate) conflict_location: Option<String>,
+}
+
+impl Response<'_> {
+    /// Adds a custom top-level field to the page object, e.g. for
+    /// client adapters that expect a `meta` field.
+    ///
+    /// Fields named like standard page object fields (`component`,
+    /// `props`, ...) are not allowed, and are ignored.
+    ///
+    /// ```rust
+    /// use axum_inertia::Inertia;
+    /// use axum::response::IntoResponse;
+    /// use serde_json::json;
+    ///
+    /// async fn users(i: Inertia) -> impl IntoResponse {
+    ///     i.render("Users/Index", json!({ "users": [] }))
+    ///         .with_page_field("meta", json!({ "generatedAt": "2024-01-01" }))
+    /// }
+    /// ```
+    pub fn with_page_field(mut self, key: &str, value: impl Into<Value>) -> Self {
+        const RESERVED: &[&str] = &[
+            "component",
+            "props",
+            "url",
+            "version",
+            "modal",
+            "deferredProps",
+            "deferredStream",
+            "layoutProps",
+        ];
+        if RESERVED.contains(&key) {
+            tracing::warn!(key, "ignoring reserved inertia page field");
+        } else {
+            self.page.extra.insert(key.to_string(), value.into());
+        }
+        self
+    }
+
+    /// Streams the groups of `deferred` to the client over server-sent
+    /// events, instead of the client fetching them with partial
+    /// reloads. See the [defer](crate::defer) module.
+    ///
+    /// On partial reloads, the groups are cancelled.
+    pub fn stream_deferred(mut self, deferred: DeferredStream) -> Self {
+        if self.request.partial.is_some() {
+            return self;
+        }
+        let Some((url, groups)) = deferred.register(&self.config) else {
+            return self;
+        };
+        // Streamed groups aren't listed in deferredProps, or the client
+        // would also fetch them with partial reloads.
+        self.page.extra.insert(
+            "deferredStream".to_string(),
+            serde_json::json!({ "url": url, "groups": groups }),
+        );
+        self
+    }
+
+    /// Merges the top-level keys of `props` into the rendered props,
+    /// overriding props with the same key, e.g. for timing data added
+    /// after rendering.
+    ///
+    /// Keys are converted like the rendered props (see
+    /// [InertiaConfig::props_case]), and during partial reloads only
+    /// requested keys are merged. Values that aren't json objects are
+    /// ignored.
+    ///
+    /// ```rust
+    /// use axum_inertia::Inertia;
+    /// use axum::response::IntoResponse;
+    /// use serde_json::json;
+    ///
+    /// async fn users(i: Inertia) -> impl IntoResponse {
+    ///     i.render("Users/Index", json!({ "users": [] }))
+    ///         .merge_props(json!({ "renderedInMs": 12 }))
+    /// }
+    /// ```
+    pub fn merge_props(mut self, props: impl Into<Value>) -> Self {
+        let mut props = props.into();
+        if let Some(case) = self.config.props_case_kind() {
+            props = case.convert_keys(props);
+        }
+        let (Value::Object(merged), Value::Object(page_props)) = (props, &mut self.page.props)
+        else {
+            tracing::warn!("ignoring merged props that aren't a json object");
+            return self;
+        };
+        for (key, value) in merged {
+            let requested = self
+                .request
+                .partial()
+                .is_none_or(|partial| partial.props.contains(&key));
+            if requested {
+                page_props.insert(key, value);
+            }
+        }
+        self
+    }
+}
+
+impl Response<'_> {
+    /// Responds with the props alone, as json, after the response
+    /// hooks and the html sanitizer, like a page. See
+    /// [Inertia::negotiate](crate::Inertia::negotiate).
+    pub(crate) fn into_props_response(mut self) -> axum::response::Response {
+        let mut headers = HeaderMap::new();
+        for hook in self.config.response_hooks() {
+            hook(&self.request, &mut self.page, &mut headers);
+        }
+        self.config.sanitize_html(&mut self.page.props);
+        self.config.prop_order_kind().apply(&mut self.page.props);
+        (headers, Json(self.page.props)).into_response()
+    }
+}
+
+/// Returns the Inertia headers of a response to `request`.
+fn inertia_headers(request: &Request, config: &InertiaConfig) -> HeaderMap {
+    let mut headers = shared_headers(request, config);
+    headers.extend(fingerprint_headers(request, config));
+    headers
+}
+
+/// Returns the Inertia headers that are the same for every client,
+/// which html shells can be cached with.
+fn shared_headers(request: &Request, config: &InertiaConfig) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let names = config.header_names_ref();
+    if let Some(version) = &config.version() {
+        match HeaderValue::try_from(version) {
+            Ok(version) => {
+                headers.insert(names.version.clone(), version);
+            }
+            Err(_) => tracing::warn!(version, "asset version isn't a valid header value"),
+        }
+    }
+    if request.is_xhr {
+        headers.insert(names.inertia.clone(), "true".parse().unwrap());
+    }
+    if config.sends_bfcache_headers() {
+        headers.append(VARY, HeaderValue::from(names.inertia.clone()));
+        headers.insert(CACHE_CONTROL, bfcache::CACHE_CONTROL_VALUE);
+    }
+    headers
+}
+
+/// Returns the [fingerprint](shell::fingerprint) headers of an html
+/// shell sent to `request`, if enabled.
+fn fingerprint_headers(request: &Request, config: &InertiaConfig) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(version) = config.version().filter(|_| config.uses_shell_fingerprint()) {
+        if !request.is_xhr {
+            shell::fingerprint(request, &version, &mut headers);
+        }
+    }
+    headers
+}
+
+/// Renders the initial html page with the layout.
+///
+/// The layout is user-supplied, so panics are caught and logged, and
+/// the configured fallback html is sent with a `500 Internal Server
+/// Error` status instead.
+fn render_html(
+    config: &InertiaConfig,
+    headers: HeaderMap,
+    component: &str,
+    page: String,
+) -> axum::response::Response {
+    match render_layout(config, component, page) {
+        Ok(html) => (headers, Html(html)).into_response(),
+        Err(panic) => panic.into_response(),
+    }
+}
+
+/// A panic of the layout, responding with the fallback html, if any.
+/// See [render_html].
+struct LayoutPanic {
+    fallback: Option<String>,
+}
+
+impl IntoResponse for LayoutPanic {
+    fn into_response(self) -> axum::response::Response {
+        match self.fallback {
+            Some(html) => (StatusCode::INTERNAL_SERVER_ERROR, Html(html)).into_response(),
+            None => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    }
+}
+
+/// Renders the layout, or returns the fallback if it panics. See
+/// [render_html].
+fn render_layout(
+    config: &InertiaConfig,
+    component: &str,
+    page: String,
+) -> Result<String, LayoutPanic> {
+    let render = || config.render_layout(component, page.clone());
+    let panic = match panic::catch_unwind(AssertUnwindSafe(render)) {
+        Ok(html) => return Ok(html),
+        Err(panic) => panic,
+    };
+    tracing::error!(panic = panic_message(&*panic), "inertia layout panicked");
+
+    let fallback = config.layout_fallback_fn().and_then(|fallback| {
+        panic::catch_unwind(AssertUnwindSafe(|| fallback(page)))
+            .inspect_err(|panic| {
+                tracing::error!(
+                    panic = panic_message(&**panic),
+                    "inertia layout fallback panicked"
+                )
+            })
+            .ok()
+    });
+    Err(LayoutPanic { fallback })
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+impl Response<'_> {
+    /// Finalizes the response, running the response hooks and
+    /// serializing the page object, into parts that are cheap to clone.
+    ///
+    /// The parts can be cached and responded with many times, e.g. for
+    /// pages that are identical for all anonymous visitors, without
+    /// serializing the page object again. See
+    /// [Inertia::render_parts](crate::Inertia::render_parts).
+    pub fn into_parts(mut self) -> Result<ResponseParts, SerializeError> {
+        if let Some(error) = self.error {
+            return Err(error.into_inner());
+        }
+        let mut headers = inertia_headers(&self.request, &self.config);
+
+        self.config.add_layout_props(&self.request, &mut self.page);
+        for hook in self.config.response_hooks() {
+            hook(&self.request, &mut self.page, &mut headers);
+        }
+
+        #[cfg(feature = "schema")]
+        if let Some(schemas) = self.config.prop_schemas() {
+            if cfg!(debug_assertions) && self.request.partial.is_none() {
+                schemas.validate(&self.page.component, &self.page.props);
+            }
+        }
+
+        self.config.sanitize_html(&mut self.page.props);
+        self.config.prop_order_kind().apply(&mut self.page.props);
+        self.config.wrap_props(&mut self.page);
+
+        let protocol = self
+            .request
+            .protocol
+            .unwrap_or_else(|| self.config.default_protocol_version());
+        if protocol == ProtocolVersion::V1 {
+            self.page.deferred_props = None;
+        }
+
+        #[cfg(feature = "debug")]
+        if let Some(page_log) = self.config.page_log() {
+            page_log.record(&self.page);
+        }
+        #[cfg(feature = "debug")]
+        if let Some(props_diff) = self.config.props_diff() {
+            props_diff.record(&self.request, &self.page);
+        }
+
+        let json = self.timing.measure("inertia-serialize", || {
+            self.config.page_serializer().serialize(&self.page)
+        })?;
+        if let Some(mode) = self.config.strict_mode() {
+            mode.check_page(
+                json.as_bytes(),
+                &headers,
+                self.config.header_names_ref(),
+                self.request.is_xhr,
+            );
+        }
+        Ok(ResponseParts {
+            config: self.config,
+            headers,
+            json: Bytes::from(json),
+            component: Arc::from(&*self.page.component),
+            status: self.status,
+            is_xhr: self.request.is_xhr,
+            timing: self.timing,
+        })
+    }
+}
+
+impl Response<'_> {
+    /// Like [into_response](IntoResponse::into_response), but
+    /// serializes the page object on a blocking thread when its props
+    /// are larger than the
+    /// [blocking_serialization](crate::InertiaConfig::blocking_serialization)
+    /// threshold, so large pages don't stall the async runtime. See
+    /// [Inertia::render_async](crate::Inertia::render_async).
+    pub async fn into_response_async(self) -> axum::response::Response {
+        let blocking = self
+            .config
+            .blocking_serialization_threshold()
+            .is_some_and(|threshold| estimated_size(&self.page.props) >= threshold);
+        if !blocking || self.conflict_location.is_some() {
+            return self.into_response();
+        }
+        #[cfg(feature = "otel")]
+        let info =
+            crate::otel::SpanInfo::page(&self.page.component, self.request.partial.is_some());
+        let response = Response {
+            request: self.request,
+            page: self.page.into_owned(),
+            config: self.config,
+            status: self.status,
+            timing: self.timing,
+            error: self.error,
+            conflict_location: None,
+        };
+        #[cfg_attr(not(feature = "otel"), allow(unused_mut))]
+        let mut response = match tokio::task::spawn_blocking(move || response.into_parts()).await {
+            Ok(Ok(parts)) => parts.into_response(),
+            Ok(Err(err)) => RenderError::new(err).into_response(),
+            Err(err) => {
+                tracing::error!(error = %err, "inertia page serialization panicked");
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        };
+        #[cfg(feature = "otel")]
+        response.extensions_mut().insert(info);
+        response
+    }
+}
+
+/// Estimates the length of `value` serialized as json, without
+/// serializing it.
+fn estimated_size(value: &Value) -> usize {
+    match value {
+        Value::Null | Value::Bool(_) => 5,
+        Value::Number(_) => 8,
+        Value::String(s) => s.len() + 2,
+        Value::Array(values) => 2 + values.iter().map(|v| estimated_size(v) + 1).sum::<usize>(),
+        Value::Object(map) => {
+            2 + map
+                .iter()
+                .map(|(k, v)| k.len() + 4 + estimated_size(v))
+                .sum::<usize>()
+        }
+    }
 }
 
 impl IntoResponse for Response<'_> {
     fn into_response(self) -> axum::response::Response {
-        let mut headers = HeaderMap::new();
-        if let Some(version) = &self.config.version() {
-            headers.insert("X-Inertia-Version", version.parse().unwrap());
+        #[cfg(feature = "otel")]
+        let info =
+            crate::otel::SpanInfo::page(&self.page.component, self.request.partial.is_some());
+        #[cfg_attr(not(feature = "otel"), allow(unused_mut))]
+        let mut response = self.respond();
+        #[cfg(feature = "otel")]
+        response.extensions_mut().insert(info);
+        response
+    }
+}
+
+impl Response<'_> {
+    fn respond(mut self) -> axum::response::Response {
+        if let Some(error) = self.error.take() {
+            return error.into_response();
+        }
+        if let Some(location) = self.conflict_location.take() {
+            let header = self.config.header_names_ref().location.clone();
+            let location = protocol::url_header_value(&location);
+            return (protocol::CONFLICT, [(header, location)]).into_response();
+        }
+        let shell = self
+            .config
+            .shell_cache_ref()
+            .filter(|_| self.status == StatusCode::OK)
+            .and_then(|cache| {
+                let key = cache.key(&self.request, &self.page.component, self.config.version())?;
+                Some((cache.clone(), key))
+            });
+        let Some((cache, key)) = shell else {
+            return match self.into_parts() {
+                Ok(parts) => parts.into_response(),
+                Err(err) => RenderError::new(err).into_response(),
+            };
+        };
+
+        let if_none_match = shell::if_none_match(&self.request);
+        if let Some(shell) = cache.get(&key) {
+            let mut response = shell.respond(if_none_match.as_ref());
+            response
+                .headers_mut()
+                .extend(fingerprint_headers(&self.request, &self.config));
+            return response;
+        }
+        // Headers of hooks, handlers and the fingerprint may be
+        // specific to this request, so only the shared headers are
+        // cached.
+        let headers = shared_headers(&self.request, &self.config);
+        let parts = match self.into_parts() {
+            Ok(parts) => parts,
+            Err(err) => return RenderError::new(err).into_response(),
+        };
+        let page = String::from_utf8_lossy(&parts.json).into_owned();
+        let mut timing = parts.timing.clone();
+        let html = timing.measure("inertia-layout", || {
+            render_layout(&parts.config, &parts.component, page)
+        });
+        let mut response = match html {
+            Ok(html) => {
+                let mut response = cache
+                    .insert(key, headers, html)
+                    .respond(if_none_match.as_ref());
+                response.headers_mut().extend(parts.headers.clone());
+                response
+            }
+            Err(panic) => panic.into_response(),
+        };
+        timing.apply(response.headers_mut());
+        response
+    }
+}
+
+/// A finalized Inertia response, with the page object serialized.
+/// Cloning is cheap. See [Response::into_parts].
+#[derive(Clone)]
+pub struct ResponseParts {
+    config: InertiaConfig,
+    headers: HeaderMap,
+    json: Bytes,
+    component: Arc<str>,
+    status: StatusCode,
+    is_xhr: bool,
+    timing: ServerTiming,
+}
+
+impl ResponseParts {
+    /// Returns the serialized page object.
+    pub fn json(&self) -> &[u8] {
+        &self.json
+    }
+
+    /// Responds with the json page object for Inertia requests, or
+    /// the html layout otherwise.
+    pub(crate) fn respond(&self, is_xhr: bool) -> axum::response::Response {
+        let mut headers = self.headers.clone();
+        let mut timing = self.timing.clone();
+        let inertia = &self.config.header_names_ref().inertia;
+        if is_xhr {
+            headers.insert(inertia.clone(), HeaderValue::from_static("true"));
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            timing.apply(&mut headers);
+            (self.status, headers, self.json.clone()).into_response()
+        } else {
+            headers.remove(inertia);
+            let page = String::from_utf8_lossy(&self.json).into_owned();
+            let html = timing.measure("inertia-layout", || {
+                render_layout(&self.config, &self.component, page)
+            });
+            timing.apply(&mut headers);
+            let mut response = match html {
+                Ok(html) => (headers, Html(html)).into_response(),
+                Err(panic) => panic.into_response(),
+            };
+            if response.status() == StatusCode::OK {
+                *response.status_mut() = self.status;
+            }
+            response
         }
+    }
+}
+
+impl IntoResponse for ResponseParts {
+    fn into_response(self) -> axum::response::Response {
+        self.respond(self.is_xhr)
+    }
+}
+
+impl IntoResponse for &ResponseParts {
+    fn into_response(self) -> axum::response::Response {
+        self.respond(self.is_xhr)
+    }
+}
+
+/// An Inertia response with pre-serialized props.
+///
+/// Response hooks and the page log are not applied, since there is
+/// no [Page] to pass them.
+pub(crate) struct RawResponse<'a> {
+    pub(crate) request: Request,
+    pub(crate) page: RawPage<'a>,
+    pub(crate) config: InertiaConfig,
+}
+
+impl IntoResponse for RawResponse<'_> {
+    fn into_response(self) -> axum::response::Response {
+        let headers = inertia_headers(&self.request, &self.config);
+        if self.request.is_xhr && !self.config.uses_pretty_json() {
+            return (headers, Json(self.page)).into_response();
+        }
+        let page = if self.config.uses_pretty_json() {
+            serde_json::to_string_pretty(&self.page).unwrap()
+        } else {
+            serde_json::to_string(&self.page).unwrap()
+        };
         if self.request.is_xhr {
-            headers.insert("X-Inertia", "true".parse().unwrap());
-            (headers, Json(self.page)).into_response()
+            let content_type = [(CONTENT_TYPE, HeaderValue::from_static("application/json"))];
+            (headers, content_type, page).into_response()
         } else {
-            let html = (self.config.layout())(serde_json::to_string(&self.page).unwrap());
-            (headers, Html(html)).into_response()
+            render_html(&self.config, headers, &self.page.component, page)
         }
     }
 }
@@ -35,6 +558,9 @@ mod tests {
     use indoc::formatdoc;
 
     use super::*;
+    use crate::config::PropOrder;
+    use crate::protocol::X_INERTIA_VERSION;
+    use serde_json::{json, Map};
 
     #[tokio::test]
     async fn test_into_html_response() {
@@ -43,10 +569,13 @@ mod tests {
             ..Request::test_request()
         };
         let page = Page {
-            component: "Testing",
+            component: "Testing".into(),
             props: serde_json::json!({ "test": "test" }),
             url: "/test".to_string(),
             version: None,
+            modal: None,
+            deferred_props: None,
+            extra: Map::new(),
         };
 
         let layout = |props| {
@@ -69,6 +598,10 @@ mod tests {
             request,
             page,
             config,
+            status: StatusCode::OK,
+            timing: ServerTiming::default(),
+            error: None,
+            conflict_location: None,
         }
         .into_response();
         let body = response.into_body().collect().await.unwrap().to_bytes();
@@ -76,4 +609,600 @@ mod tests {
 
         assert!(body.contains(r#""props":{"test":"test"}"#));
     }
+
+    #[tokio::test]
+    async fn test_response_hooks() {
+        let page = Page {
+            component: "Testing".into(),
+            props: serde_json::json!({ "test": "test" }),
+            url: "/test".to_string(),
+            version: None,
+            modal: None,
+            deferred_props: None,
+            extra: Map::new(),
+        };
+
+        let config = InertiaConfig::new(Some("123".to_string()), Box::new(|props| props))
+            .on_response(|request, page, headers| {
+                assert!(request.is_xhr());
+                page.props["flag"] = serde_json::json!(true);
+                headers.insert("X-Audit", "yes".parse().unwrap());
+            })
+            .on_response(|_, _, headers| {
+                headers.remove(X_INERTIA_VERSION);
+            });
+
+        let response = Response {
+            request: Request::test_request(),
+            page,
+            config,
+            status: StatusCode::OK,
+            timing: ServerTiming::default(),
+            error: None,
+            conflict_location: None,
+        }
+        .into_response();
+
+        assert_eq!(response.headers().get("X-Audit").unwrap(), "yes");
+        assert!(response.headers().get(X_INERTIA_VERSION).is_none());
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let page: Value = serde_json::from_slice(&body).expect("page object json");
+
+        assert_eq!(page["props"], json!({ "flag": true, "test": "test" }));
+    }
+
+    #[test]
+    fn test_caches_shells_without_hook_headers() {
+        let config = InertiaConfig::new(None, Box::new(|props| props))
+            .shell_cache(crate::shell::ShellCache::new().component("Home"))
+            .on_response(|_, _, headers| {
+                headers.insert("X-Request", "first".parse().unwrap());
+            });
+        let response = || Response {
+            request: Request {
+                is_xhr: false,
+                ..Request::test_request()
+            },
+            page: Page {
+                component: "Home".into(),
+                props: serde_json::json!({}),
+                url: "/foo/bar".to_string(),
+                version: None,
+                modal: None,
+                deferred_props: None,
+                extra: Map::new(),
+            },
+            config: config.clone(),
+            status: StatusCode::OK,
+            timing: ServerTiming::default(),
+            error: None,
+            conflict_location: None,
+        };
+
+        let first = response().into_response();
+        assert_eq!(first.headers().get("X-Request").unwrap(), "first");
+        let cached = response().into_response();
+        let etag = http::header::ETAG;
+        assert_eq!(cached.headers().get(&etag), first.headers().get(&etag));
+        assert!(cached.headers().get("X-Request").is_none());
+    }
+
+    #[test]
+    fn test_caches_shells_with_fingerprints() {
+        let config = InertiaConfig::new(Some("1".to_string()), Box::new(|props| props))
+            .shell_cache(crate::shell::ShellCache::new().component("Home"))
+            .shell_fingerprint(true)
+            .on_response(|_, _, headers| {
+                headers.insert("X-Rendered", "yes".parse().unwrap());
+            });
+        let response = |cookie: Option<&str>| {
+            let mut request = Request {
+                is_xhr: false,
+                ..Request::test_request()
+            };
+            if let Some(cookie) = cookie {
+                request
+                    .headers
+                    .insert(http::header::COOKIE, cookie.parse().unwrap());
+            }
+            Response {
+                request,
+                page: Page {
+                    component: "Home".into(),
+                    props: serde_json::json!({}),
+                    url: "/foo/bar".to_string(),
+                    version: Some("1".to_string()),
+                    modal: None,
+                    deferred_props: None,
+                    extra: Map::new(),
+                },
+                config: config.clone(),
+                status: StatusCode::OK,
+                timing: ServerTiming::default(),
+                error: None,
+                conflict_location: None,
+            }
+            .into_response()
+        };
+        let set_cookie = http::header::SET_COOKIE;
+
+        let first = response(None);
+        assert!(first.headers().contains_key("X-Rendered"));
+        assert!(first.headers().contains_key(&set_cookie));
+
+        // Browsers send the fingerprint cookie back, which is still a
+        // cache hit.
+        let cached = response(Some("inertia_version=1"));
+        assert!(!cached.headers().contains_key("X-Rendered"));
+        assert!(cached.headers().contains_key(&set_cookie));
+        assert!(!cached.headers().contains_key(CACHE_CONTROL));
+
+        let stale = response(Some("inertia_version=0"));
+        assert!(!stale.headers().contains_key("X-Rendered"));
+        assert_eq!(
+            stale.headers()[CACHE_CONTROL],
+            "no-cache, no-store, must-revalidate"
+        );
+
+        // Later hits don't replay the cache control of a stale client.
+        let cached = response(Some("inertia_version=1"));
+        assert!(!cached.headers().contains_key(CACHE_CONTROL));
+    }
+
+    #[tokio::test]
+    async fn test_negotiates_protocol_version() {
+        let page = || Page {
+            component: "Testing".into(),
+            props: serde_json::json!({}),
+            url: "/test".to_string(),
+            version: None,
+            modal: None,
+            deferred_props: Some([("default".to_string(), vec!["slow".to_string()])].into()),
+            extra: Map::new(),
+        };
+        let render = |request: Request, config: InertiaConfig| async {
+            let response = Response {
+                request,
+                page: page(),
+                config,
+                status: StatusCode::OK,
+                timing: ServerTiming::default(),
+                error: None,
+                conflict_location: None,
+            }
+            .into_response();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            String::from_utf8(body.into()).expect("decoded string")
+        };
+        let config = InertiaConfig::new(None, Box::new(|props| props));
+        let v1_config = config.clone().protocol_version(ProtocolVersion::V1);
+
+        let body = render(Request::test_request(), config.clone()).await;
+        assert!(body.contains("deferredProps"));
+
+        let body = render(Request::test_request(), v1_config.clone()).await;
+        assert!(!body.contains("deferredProps"));
+
+        let v2_request = Request {
+            protocol: Some(ProtocolVersion::V2),
+            ..Request::test_request()
+        };
+        let body = render(v2_request, v1_config).await;
+        assert!(body.contains("deferredProps"));
+
+        let v1_request = Request {
+            protocol: Some(ProtocolVersion::V1),
+            ..Request::test_request()
+        };
+        let body = render(v1_request, config).await;
+        assert!(!body.contains("deferredProps"));
+    }
+
+    #[tokio::test]
+    async fn test_layout_panics() {
+        let page = || Page {
+            component: "Testing".into(),
+            props: serde_json::json!({}),
+            url: "/test".to_string(),
+            version: None,
+            modal: None,
+            deferred_props: None,
+            extra: Map::new(),
+        };
+        let request = || Request {
+            is_xhr: false,
+            ..Request::test_request()
+        };
+        let config = InertiaConfig::new(None, Box::new(|_| panic!("broken layout")));
+
+        let response = Response {
+            request: request(),
+            page: page(),
+            config: config.clone(),
+            status: StatusCode::OK,
+            timing: ServerTiming::default(),
+            error: None,
+            conflict_location: None,
+        }
+        .into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let response = Response {
+            request: request(),
+            page: page(),
+            config: config.layout_fallback(Box::new(|page| format!("<p>{}</p>", page))),
+            status: StatusCode::OK,
+            timing: ServerTiming::default(),
+            error: None,
+            conflict_location: None,
+        }
+        .into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.into()).expect("decoded string");
+        assert!(body.starts_with(r#"<p>{"component":"Testing""#));
+    }
+
+    #[tokio::test]
+    async fn test_custom_serializer() {
+        struct Uppercase;
+
+        impl crate::serializer::PageSerializer for Uppercase {
+            fn serialize(&self, page: &Page) -> Result<String, crate::serializer::SerializeError> {
+                Ok(serde_json::to_string(page)?.to_uppercase())
+            }
+        }
+
+        let page = Page {
+            component: "Testing".into(),
+            props: serde_json::json!({}),
+            url: "/test".to_string(),
+            version: None,
+            modal: None,
+            deferred_props: None,
+            extra: Map::new(),
+        };
+        let config = InertiaConfig::new(None, Box::new(|props| props)).serializer(Uppercase);
+
+        let response = Response {
+            request: Request::test_request(),
+            page,
+            config,
+            status: StatusCode::OK,
+            timing: ServerTiming::default(),
+            error: None,
+            conflict_location: None,
+        }
+        .into_response();
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.into()).expect("decoded string");
+        assert!(body.starts_with(r#"{"COMPONENT":"TESTING""#));
+    }
+
+    #[tokio::test]
+    async fn test_pretty_json() {
+        let page = Page {
+            component: "Testing".into(),
+            props: serde_json::json!({ "a": 1 }),
+            url: "/test".to_string(),
+            version: None,
+            modal: None,
+            deferred_props: None,
+            extra: Map::new(),
+        };
+        let response = Response {
+            request: Request::test_request(),
+            page,
+            config: InertiaConfig::new(None, Box::new(|props| props)).pretty_json(true),
+            status: StatusCode::OK,
+            timing: ServerTiming::default(),
+            error: None,
+            conflict_location: None,
+        }
+        .into_response();
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.into()).expect("decoded string");
+        assert_eq!(
+            body.starts_with("{\n  \"component\""),
+            cfg!(debug_assertions)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prop_order() {
+        let page = Page {
+            component: "Testing".into(),
+            props: serde_json::json!({ "b": 1, "a": [{ "d": 1, "c": 2 }] }),
+            url: "/test".to_string(),
+            version: None,
+            modal: None,
+            deferred_props: None,
+            extra: Map::new(),
+        };
+        let response = Response {
+            request: Request::test_request(),
+            page,
+            config: InertiaConfig::new(None, Box::new(|props| props)).prop_order(PropOrder::Sorted),
+            status: StatusCode::OK,
+            timing: ServerTiming::default(),
+            error: None,
+            conflict_location: None,
+        }
+        .into_response();
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.into()).expect("decoded string");
+        assert!(body.contains(r#""props":{"a":[{"c":2,"d":1}],"b":1}"#));
+    }
+
+    #[tokio::test]
+    async fn test_page_fields() {
+        let page = Page {
+            component: "Testing".into(),
+            props: serde_json::json!({}),
+            url: "/test".to_string(),
+            version: None,
+            modal: None,
+            deferred_props: None,
+            extra: Map::new(),
+        };
+        let response = Response {
+            request: Request::test_request(),
+            page,
+            config: InertiaConfig::new(None, Box::new(|props| props)),
+            status: StatusCode::OK,
+            timing: ServerTiming::default(),
+            error: None,
+            conflict_location: None,
+        }
+        .with_page_field("meta", serde_json::json!({ "a": 1 }))
+        .with_page_field("component", "Other")
+        .into_response();
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let page: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(page["meta"], serde_json::json!({ "a": 1 }));
+        assert_eq!(page["component"], "Testing");
+    }
+
+    #[test]
+    fn test_merge_props() {
+        let page = Page {
+            component: "Testing".into(),
+            props: serde_json::json!({ "a": 1, "b": 2 }),
+            url: "/test".to_string(),
+            version: None,
+            modal: None,
+            deferred_props: None,
+            extra: Map::new(),
+        };
+        let response = Response {
+            request: Request::test_request(),
+            page,
+            config: InertiaConfig::new(None, Box::new(|props| props)),
+            status: StatusCode::OK,
+            timing: ServerTiming::default(),
+            error: None,
+            conflict_location: None,
+        }
+        .merge_props(serde_json::json!({ "b": 3, "c": 4 }))
+        .merge_props("ignored");
+        assert_eq!(
+            response.page.props,
+            serde_json::json!({ "a": 1, "b": 3, "c": 4 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_props_key_and_envelope() {
+        let render = |config: InertiaConfig| async {
+            let page = Page {
+                component: "Testing".into(),
+                props: serde_json::json!({ "a": 1 }),
+                url: "/test".to_string(),
+                version: None,
+                modal: None,
+                deferred_props: None,
+                extra: Map::new(),
+            };
+            let response = Response {
+                request: Request::test_request(),
+                page,
+                config,
+                status: StatusCode::OK,
+                timing: ServerTiming::default(),
+                error: None,
+                conflict_location: None,
+            }
+            .into_response();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            serde_json::from_slice::<Value>(&body).unwrap()
+        };
+        let config = InertiaConfig::new(None, Box::new(|props| props));
+
+        let page = render(config.clone().props_key("pageProps")).await;
+        assert!(page.get("props").is_none());
+        assert_eq!(page["pageProps"], serde_json::json!({ "a": 1 }));
+
+        let page = render(config.props_envelope("data").on_response(|_, page, _| {
+            page.extra.insert(
+                crate::config::ENVELOPE_META.to_string(),
+                serde_json::json!({ "meta": { "b": 2 } }),
+            );
+        }))
+        .await;
+        assert_eq!(
+            page["props"],
+            serde_json::json!({ "data": { "a": 1 }, "meta": { "b": 2 } })
+        );
+        assert!(page.get("$envelope").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_layout_props() {
+        let render = |request: Request, config: InertiaConfig| async {
+            let page = Page {
+                component: "Testing".into(),
+                props: serde_json::json!({ "a": 1 }),
+                url: "/test".to_string(),
+                version: None,
+                modal: None,
+                deferred_props: None,
+                extra: Map::new(),
+            };
+            let response = Response {
+                request,
+                page,
+                config,
+                status: StatusCode::OK,
+                timing: ServerTiming::default(),
+                error: None,
+                conflict_location: None,
+            }
+            .into_response();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            serde_json::from_slice::<Value>(&body).unwrap()
+        };
+        let config = InertiaConfig::new(None, Box::new(|props| props))
+            .layout_props(|request| serde_json::json!({ "current": request.url() }));
+
+        let page = render(Request::test_request(), config.clone()).await;
+        assert_eq!(page["props"], serde_json::json!({ "a": 1 }));
+        assert_eq!(
+            page["layoutProps"],
+            serde_json::json!({ "current": "/foo/bar" })
+        );
+
+        let config = config.nest_layout_props("layout");
+        let page = render(Request::test_request(), config.clone()).await;
+        assert!(page.get("layoutProps").is_none());
+        assert_eq!(page["props"]["layout"]["current"], "/foo/bar");
+
+        let partial_request = Request {
+            partial: Some(crate::partial::Partial {
+                props: vec!["a".to_string()],
+                component: "Testing".to_string(),
+            }),
+            ..Request::test_request()
+        };
+        let page = render(partial_request, config).await;
+        assert_eq!(page["props"], serde_json::json!({ "a": 1 }));
+    }
+
+    #[tokio::test]
+    async fn test_reuses_response_parts() {
+        let page = Page {
+            component: "Testing".into(),
+            props: serde_json::json!({ "a": 1 }),
+            url: "/test".to_string(),
+            version: None,
+            modal: None,
+            deferred_props: None,
+            extra: Map::new(),
+        };
+        let config = InertiaConfig::new(None, Box::new(|page| format!("<html>{}</html>", page)))
+            .on_response(|_, _, headers| {
+                headers.insert("X-Audit", "yes".parse().unwrap());
+            });
+        let parts = Response {
+            request: Request::test_request(),
+            page,
+            config,
+            status: StatusCode::OK,
+            timing: ServerTiming::default(),
+            error: None,
+            conflict_location: None,
+        }
+        .into_parts()
+        .unwrap();
+        assert_eq!(
+            parts.json(),
+            br#"{"component":"Testing","props":{"a":1},"url":"/test","version":null}"#
+        );
+
+        let response = (&parts).into_response();
+        assert_eq!(response.headers().get("X-Inertia").unwrap(), "true");
+        assert_eq!(response.headers().get("X-Audit").unwrap(), "yes");
+
+        let response = parts.respond(false);
+        assert!(response.headers().get("X-Inertia").is_none());
+        assert_eq!(response.headers().get("X-Audit").unwrap(), "yes");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(body.starts_with(b"<html>{"));
+    }
+
+    #[test]
+    fn test_estimated_size() {
+        let value = serde_json::json!({ "users": [{ "name": "mikey", "admin": true }], "n": 1 });
+        let size = estimated_size(&value);
+        let actual = value.to_string().len();
+        assert!(
+            size >= actual && size < actual * 2,
+            "{} vs {}",
+            size,
+            actual
+        );
+    }
+
+    #[tokio::test]
+    async fn test_into_response_async() {
+        let config = InertiaConfig::new(None, Box::new(|page| page)).blocking_serialization(0);
+        let response = Response {
+            request: Request::test_request(),
+            page: Page {
+                component: "Testing".into(),
+                props: serde_json::json!({ "a": 1 }),
+                url: "/test".to_string(),
+                version: None,
+                modal: None,
+                deferred_props: None,
+                extra: Map::new(),
+            },
+            config,
+            status: StatusCode::OK,
+            timing: ServerTiming::default(),
+            error: None,
+            conflict_location: None,
+        };
+        let response = response.into_response_async().await;
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(
+            body,
+            r#"{"component":"Testing","props":{"a":1},"url":"/test","version":null}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status() {
+        let config = InertiaConfig::new(None, Box::new(|page| page));
+        let response = |is_xhr| {
+            let mut request = Request::test_request();
+            request.is_xhr = is_xhr;
+            Response {
+                request,
+                page: Page {
+                    component: "NotFound".into(),
+                    props: serde_json::json!({}),
+                    url: "/test".to_string(),
+                    version: None,
+                    modal: None,
+                    deferred_props: None,
+                    extra: Map::new(),
+                },
+                config: config.clone(),
+                status: StatusCode::NOT_FOUND,
+                timing: ServerTiming::default(),
+                error: None,
+                conflict_location: None,
+            }
+            .into_response()
+        };
+        assert_eq!(response(true).status(), StatusCode::NOT_FOUND);
+        assert_eq!(response(false).status(), StatusCode::NOT_FOUND);
+    }
 }
@@ -0,0 +1,98 @@
+//! Shorthand routes for pages without a handler.
+//!
+//! Static pages, e.g. an about page, only need to render a component
+//! without props. [InertiaRouterExt::inertia_route] registers a `GET`
+//! route doing just that:
+//!
+//! ```rust
+//! use axum::Router;
+//! use axum_inertia::{routing::InertiaRouterExt, vite};
+//!
+//! let app: Router = Router::new()
+//!     .inertia_route("/about", "Pages/About")
+//!     .inertia_route("/terms", "Pages/Terms")
+//!     .with_state(vite::Development::default().into_config());
+//! ```
+//!
+//! [render_route] returns the same route as a [MethodRouter], e.g. to
+//! add other methods or layers to it.
+
+use crate::Inertia;
+use axum::extract::FromRequestParts;
+use axum::routing::{get, MethodRouter};
+use axum::Router;
+use serde_json::{Map, Value};
+
+/// Returns a `GET` route rendering `component` with empty props.
+pub fn render_route<S>(component: &'static str) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+    Inertia: FromRequestParts<S>,
+{
+    get(move |i: Inertia| async move { i.render(component, Value::Object(Map::new())) })
+}
+
+/// Shorthand routes for [Router]s.
+pub trait InertiaRouterExt {
+    /// Adds a `GET` route at `path` rendering `component` with empty
+    /// props. See [render_route].
+    fn inertia_route(self, path: &str, component: &'static str) -> Self;
+}
+
+impl<S> InertiaRouterExt for Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    Inertia: FromRequestParts<S>,
+{
+    fn inertia_route(self, path: &str, component: &'static str) -> Self {
+        self.route(path, render_route(component))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InertiaConfig;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn it_renders_shorthand_routes() {
+        let layout =
+            Box::new(|props| format!(r#"<html><body><div id="app" data-page='{}'></div>"#, props));
+        let config = InertiaConfig::new(None, layout);
+
+        let app = Router::new()
+            .inertia_route("/about", "Pages/About")
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://{}/about", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+        let page: Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+        assert_eq!(page["component"], "Pages/About");
+        assert_eq!(page["props"], json!({}));
+
+        let res = client
+            .post(format!("http://{}/about", &addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 405);
+    }
+}
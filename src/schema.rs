@@ -0,0 +1,130 @@
+//! Validation of rendered props against JSON Schemas, available with
+//! the `schema` feature.
+//!
+//! [PropSchemas] holds a schema per component. Register it with
+//! [with_prop_schemas](crate::InertiaConfig::with_prop_schemas) to
+//! catch props that drifted from what the frontend expects:
+//!
+//! ```rust
+//! use axum_inertia::{schema::{PropSchemas, SchemaViolation}, vite};
+//! use serde_json::json;
+//!
+//! let schemas = PropSchemas::new()
+//!     .component(
+//!         "Users/Index",
+//!         json!({
+//!             "type": "object",
+//!             "required": ["users"],
+//!             "properties": { "users": { "type": "array" } }
+//!         }),
+//!     )
+//!     .on_violation(SchemaViolation::Panic);
+//! let inertia = vite::Development::default()
+//!     .into_config()
+//!     .with_prop_schemas(schemas);
+//! ```
+//!
+//! Props are only validated in debug builds, i.e. with
+//! `debug_assertions`, and not for partial reloads, since those only
+//! include some of the props. Release builds skip compiling the
+//! schemas altogether.
+
+use jsonschema::Validator;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// What to do when props don't match their schema.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SchemaViolation {
+    /// Log the errors with `tracing`.
+    #[default]
+    Log,
+    /// Panic, e.g. to fail tests.
+    Panic,
+}
+
+/// JSON Schemas for the props of components.
+#[derive(Clone, Default)]
+pub struct PropSchemas {
+    schemas: HashMap<String, Arc<Validator>>,
+    on_violation: SchemaViolation,
+}
+
+impl PropSchemas {
+    pub fn new() -> PropSchemas {
+        Self::default()
+    }
+
+    /// Sets the schema of the props of `component`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `schema` is not a valid JSON Schema, in debug builds.
+    pub fn component(mut self, component: &str, schema: Value) -> PropSchemas {
+        if cfg!(debug_assertions) {
+            let validator = jsonschema::validator_for(&schema)
+                .unwrap_or_else(|err| panic!("invalid schema for `{}`: {}", component, err));
+            self.schemas
+                .insert(component.to_string(), Arc::new(validator));
+        }
+        self
+    }
+
+    /// Sets what to do when props don't match their schema. Defaults
+    /// to [SchemaViolation::Log].
+    pub fn on_violation(mut self, on_violation: SchemaViolation) -> PropSchemas {
+        self.on_violation = on_violation;
+        self
+    }
+
+    /// Validates the props of `component`, if it has a schema.
+    pub(crate) fn validate(&self, component: &str, props: &Value) {
+        let Some(validator) = self.schemas.get(component) else {
+            return;
+        };
+        let errors: Vec<String> = validator
+            .iter_errors(props)
+            .map(|err| format!("{}: {}", err.instance_path, err))
+            .collect();
+        if errors.is_empty() {
+            return;
+        }
+        match self.on_violation {
+            SchemaViolation::Log => {
+                tracing::error!(component, ?errors, "inertia props don't match their schema");
+            }
+            SchemaViolation::Panic => {
+                panic!(
+                    "props of `{}` don't match their schema: {}",
+                    component,
+                    errors.join("; ")
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schemas() -> PropSchemas {
+        PropSchemas::new()
+            .component("Users", json!({ "type": "object", "required": ["users"] }))
+            .on_violation(SchemaViolation::Panic)
+    }
+
+    #[test]
+    fn test_accepts_matching_props() {
+        schemas().validate("Users", &json!({ "users": [] }));
+        schemas().validate("Other", &json!({}));
+    }
+
+    #[test]
+    #[should_panic(expected = "props of `Users` don't match their schema")]
+    fn test_panics_on_violations() {
+        schemas().validate("Users", &json!({ "posts": [] }));
+    }
+}
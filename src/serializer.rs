@@ -0,0 +1,156 @@
+//! Pluggable json serialization of page objects.
+//!
+//! Page objects are serialized with `serde_json` by default. Apps
+//! serving large page objects can plug in a faster serializer. With
+//! the `simd-json` feature, [SimdJson] serializes with `simd-json`:
+//!
+//! ```rust
+//! # #[cfg(feature = "simd-json")] {
+//! use axum_inertia::serializer::SimdJson;
+//! use axum_inertia::vite;
+//!
+//! let inertia = vite::Development::default()
+//!     .into_config()
+//!     .serializer(SimdJson);
+//! # }
+//! ```
+//!
+//! Other serializers, e.g. one backed by `sonic-rs`, implement
+//! [PageSerializer]:
+//!
+//! ```rust
+//! use axum_inertia::protocol::Page;
+//! use axum_inertia::serializer::{PageSerializer, SerializeError};
+//! use axum_inertia::vite;
+//!
+//! struct Compact;
+//!
+//! impl PageSerializer for Compact {
+//!     fn serialize(&self, page: &Page) -> Result<String, SerializeError> {
+//!         // e.g. sonic_rs::to_string(page)
+//!         Ok(serde_json::to_string(page)?)
+//!     }
+//! }
+//!
+//! let inertia = vite::Development::default()
+//!     .into_config()
+//!     .serializer(Compact);
+//! ```
+//!
+//! Responses from [render_raw](crate::Inertia::render_raw) always use
+//! `serde_json`, since their props are already serialized.
+
+use crate::page::Page;
+use std::error::Error;
+
+/// An error serializing a page object.
+pub type SerializeError = Box<dyn Error + Send + Sync>;
+
+/// Serializes page objects to json.
+pub trait PageSerializer: Send + Sync {
+    fn serialize(&self, page: &Page) -> Result<String, SerializeError>;
+}
+
+/// The default serializer, using `serde_json`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SerdeJson;
+
+impl PageSerializer for SerdeJson {
+    fn serialize(&self, page: &Page) -> Result<String, SerializeError> {
+        Ok(serde_json::to_string(page)?)
+    }
+}
+
+/// A `serde_json` serializer that pretty-prints page objects. See
+/// [InertiaConfig::pretty_json](crate::InertiaConfig::pretty_json).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SerdeJsonPretty;
+
+impl PageSerializer for SerdeJsonPretty {
+    fn serialize(&self, page: &Page) -> Result<String, SerializeError> {
+        Ok(serde_json::to_string_pretty(page)?)
+    }
+}
+
+/// A serializer using `simd-json`, for apps serving large page
+/// objects. Requires the `simd-json` feature.
+#[cfg(feature = "simd-json")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SimdJson;
+
+#[cfg(feature = "simd-json")]
+impl PageSerializer for SimdJson {
+    fn serialize(&self, page: &Page) -> Result<String, SerializeError> {
+        Ok(simd_json::to_string(page)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Inertia, InertiaConfig};
+    use axum::{routing::get, Router};
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    async fn fetch_page(config: InertiaConfig) -> (String, String) {
+        let app = Router::new()
+            .route(
+                "/users",
+                get(|i: Inertia| async move { i.render("Users", json!({ "users": ["a", "b"] })) }),
+            )
+            .with_state(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind ephemeral socket");
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server error");
+        });
+
+        let res = reqwest::Client::new()
+            .get(format!("http://{}/users", &addr))
+            .header("X-Inertia", "true")
+            .send()
+            .await
+            .unwrap();
+        let content_type = res.headers()["content-type"].to_str().unwrap().to_string();
+        (content_type, res.text().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn it_renders_pages_with_a_custom_serializer() {
+        struct Tagged;
+
+        impl PageSerializer for Tagged {
+            fn serialize(&self, page: &Page) -> Result<String, SerializeError> {
+                let mut page = serde_json::to_value(page)?;
+                page["serializer"] = json!("tagged");
+                Ok(serde_json::to_string(&page)?)
+            }
+        }
+
+        let config = InertiaConfig::new(None, Box::new(|props| props)).serializer(Tagged);
+        let (content_type, body) = fetch_page(config).await;
+        assert_eq!(content_type, "application/json");
+        let page: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(page["serializer"], "tagged");
+        assert_eq!(page["component"], "Users");
+        assert_eq!(page["props"], json!({ "users": ["a", "b"] }));
+    }
+
+    #[cfg(feature = "simd-json")]
+    #[tokio::test]
+    async fn it_renders_pages_with_simd_json() {
+        let (_, expected) = fetch_page(InertiaConfig::new(None, Box::new(|props| props))).await;
+        let config = InertiaConfig::new(None, Box::new(|props| props)).serializer(SimdJson);
+        let (content_type, body) = fetch_page(config).await;
+        assert_eq!(content_type, "application/json");
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap(),
+            serde_json::from_str::<Value>(&expected).unwrap()
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! `Server-Timing` headers for the stages of an Inertia response.
+//!
+//! With the `server-timing` feature, responses carry a
+//! [Server-Timing] header with the durations of prop computation
+//! (`inertia-props`), page object serialization (`inertia-serialize`)
+//! and, for initial page loads, layout rendering (`inertia-layout`),
+//! e.g.:
+//!
+//! ```text
+//! Server-Timing: inertia-props;dur=0.412, inertia-serialize;dur=0.087
+//! ```
+//!
+//! Browsers show them in the network panel, and frontend monitoring
+//! can read them with the `PerformanceServerTiming` api. Without the
+//! feature, nothing is measured.
+//!
+//! [Server-Timing]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Server-Timing
+
+use http::HeaderMap;
+#[cfg(feature = "server-timing")]
+use http::HeaderName;
+#[cfg(feature = "server-timing")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "server-timing")]
+pub(crate) const SERVER_TIMING: HeaderName = HeaderName::from_static("server-timing");
+
+/// Durations of the stages of a response.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ServerTiming {
+    #[cfg(feature = "server-timing")]
+    entries: Vec<(&'static str, Duration)>,
+}
+
+impl ServerTiming {
+    /// Runs `f`, recording its duration as `name`.
+    #[cfg(feature = "server-timing")]
+    pub(crate) fn measure<T>(&mut self, name: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let output = f();
+        self.entries.push((name, start.elapsed()));
+        output
+    }
+
+    #[cfg(not(feature = "server-timing"))]
+    pub(crate) fn measure<T>(&mut self, _: &'static str, f: impl FnOnce() -> T) -> T {
+        f()
+    }
+
+    /// Adds the recorded durations to `headers`.
+    #[cfg(feature = "server-timing")]
+    pub(crate) fn apply(&self, headers: &mut HeaderMap) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let value = self
+            .entries
+            .iter()
+            .map(|(name, duration)| format!("{};dur={:.3}", name, duration.as_secs_f64() * 1000.0))
+            .collect::<Vec<_>>()
+            .join(", ");
+        headers.append(SERVER_TIMING, value.parse().unwrap());
+    }
+
+    #[cfg(not(feature = "server-timing"))]
+    pub(crate) fn apply(&self, _: &mut HeaderMap) {}
+}
+
+#[cfg(all(test, feature = "server-timing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let mut timing = ServerTiming::default();
+        let mut headers = HeaderMap::new();
+        timing.apply(&mut headers);
+        assert!(headers.get(SERVER_TIMING).is_none());
+
+        assert_eq!(timing.measure("inertia-props", || 1), 1);
+        timing.measure("inertia-serialize", || ());
+        timing.apply(&mut headers);
+        let value = headers.get(SERVER_TIMING).unwrap().to_str().unwrap();
+        assert!(value.starts_with("inertia-props;dur="));
+        assert!(value.contains(", inertia-serialize;dur="));
+    }
+}
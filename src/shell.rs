@@ -0,0 +1,351 @@
+//! Caching of the initial html page of static components.
+//!
+//! Initial page loads run the layout closure and build the whole html
+//! page. For components whose page is the same for every anonymous
+//! visitor, e.g. a landing or pricing page, a [ShellCache] stores the
+//! rendered html and sends it again on later hits, with an `ETag` so
+//! browsers can revalidate with `304 Not Modified`:
+//!
+//! ```rust
+//! use axum_inertia::{shell::ShellCache, vite};
+//!
+//! let inertia = vite::Development::default()
+//!     .into_config()
+//!     .shell_cache(ShellCache::new().component("Home").component("Pricing"));
+//! ```
+//!
+//! Shells are cached by component, url (path and query), asset version
+//! and locale (the first `Accept-Language` tag by default). The page
+//! object of the first render is sent to every later visitor of the
+//! same url, so only opt in components that don't depend on the request
+//! otherwise.
+//!
+//! Requests with an `Authorization` header, or cookies other than the
+//! [fingerprint](crate::InertiaConfig::shell_fingerprint) cookie,
+//! Inertia requests, and responses with a status other than `200 OK`
+//! are never cached. Headers set for the first render, e.g. by
+//! [response hooks](crate::InertiaConfig::on_response), aren't cached
+//! either: only the Inertia headers are sent on later hits, with the
+//! fingerprint headers of each request.
+
+use crate::request::Request;
+use axum::body::Bytes;
+use axum::response::{IntoResponse, Response};
+use http::header::{
+    ACCEPT_LANGUAGE, AUTHORIZATION, CACHE_CONTROL, CONTENT_TYPE, COOKIE, ETAG, EXPIRES,
+    IF_NONE_MATCH, PRAGMA, SET_COOKIE,
+};
+use http::{HeaderMap, HeaderValue, StatusCode};
+use sha1::{Digest, Sha1};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+type LocaleResolver = dyn Fn(&Request) -> Option<String> + Send + Sync;
+
+const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+/// The cookie holding the asset version of the last html shell sent to
+/// the browser. See
+/// [InertiaConfig::shell_fingerprint](crate::InertiaConfig::shell_fingerprint).
+pub const VERSION_COOKIE: &str = "inertia_version";
+
+/// A cache of rendered html pages. See the [module documentation](self).
+#[derive(Clone)]
+pub struct ShellCache {
+    components: HashSet<String>,
+    locale: Arc<LocaleResolver>,
+    max_entries: usize,
+    entries: Arc<Mutex<HashMap<ShellKey, Arc<Shell>>>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct ShellKey {
+    component: String,
+    url: String,
+    version: Option<String>,
+    locale: Option<String>,
+}
+
+/// A cached html page, with the headers it was sent with.
+pub(crate) struct Shell {
+    headers: HeaderMap,
+    html: Bytes,
+    etag: HeaderValue,
+}
+
+impl Default for ShellCache {
+    fn default() -> Self {
+        ShellCache {
+            components: HashSet::new(),
+            locale: Arc::new(accept_language),
+            max_entries: DEFAULT_MAX_ENTRIES,
+            entries: Arc::default(),
+        }
+    }
+}
+
+impl ShellCache {
+    pub fn new() -> ShellCache {
+        Self::default()
+    }
+
+    /// Caches the html page of `component`.
+    pub fn component(mut self, component: &str) -> Self {
+        self.components.insert(component.to_string());
+        self
+    }
+
+    /// Sets how the locale part of the cache key is computed, e.g. from
+    /// a path prefix instead of `Accept-Language`.
+    pub fn locale<F>(mut self, locale: F) -> Self
+    where
+        F: Fn(&Request) -> Option<String> + Send + Sync + 'static,
+    {
+        self.locale = Arc::new(locale);
+        self
+    }
+
+    /// Sets the maximum number of cached pages. Once full, new pages
+    /// are rendered without being cached. Defaults to 1000.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Removes all cached pages.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Returns the cache key of the page of `component` for `request`,
+    /// or `None` if it shouldn't be cached.
+    pub(crate) fn key(
+        &self,
+        request: &Request,
+        component: &str,
+        version: Option<String>,
+    ) -> Option<ShellKey> {
+        let anonymous =
+            !request.headers().contains_key(AUTHORIZATION) && only_version_cookie(request);
+        if request.is_xhr()
+            || request.partial().is_some()
+            || !anonymous
+            || !self.components.contains(component)
+        {
+            return None;
+        }
+        let url = match request.uri().path_and_query() {
+            Some(path_and_query) => path_and_query.as_str().to_string(),
+            None => request.url().to_string(),
+        };
+        Some(ShellKey {
+            component: component.to_string(),
+            url,
+            version,
+            locale: (self.locale)(request),
+        })
+    }
+
+    pub(crate) fn get(&self, key: &ShellKey) -> Option<Arc<Shell>> {
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    /// Stores the html page under `key`, unless the cache is full.
+    /// Cookies set in `headers` are specific to one client, so they're
+    /// never cached.
+    pub(crate) fn insert(&self, key: ShellKey, mut headers: HeaderMap, html: String) -> Arc<Shell> {
+        headers.remove(SET_COOKIE);
+        let etag = format!("\"{}\"", hex::encode(Sha1::digest(html.as_bytes())));
+        let shell = Arc::new(Shell {
+            headers,
+            html: Bytes::from(html),
+            etag: HeaderValue::try_from(etag).unwrap(),
+        });
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() < self.max_entries {
+            entries.insert(key, shell.clone());
+        }
+        shell
+    }
+}
+
+impl Shell {
+    /// Responds with the page, or with `304 Not Modified` if the
+    /// request's `If-None-Match` header lists its etag.
+    pub(crate) fn respond(&self, if_none_match: Option<&HeaderValue>) -> Response {
+        let mut headers = self.headers.clone();
+        headers.insert(ETAG, self.etag.clone());
+        if if_none_match.is_some_and(|value| etag_matches(value, &self.etag)) {
+            return (StatusCode::NOT_MODIFIED, headers).into_response();
+        }
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("text/html; charset=utf-8"),
+        );
+        (headers, self.html.clone()).into_response()
+    }
+}
+
+/// Returns true if the request has no cookies but the version cookie
+/// of [fingerprint], which doesn't identify the visitor.
+fn only_version_cookie(request: &Request) -> bool {
+    request.headers().get_all(COOKIE).iter().all(|value| {
+        value.to_str().is_ok_and(|value| {
+            value
+                .split(';')
+                .map(str::trim)
+                .filter(|pair| !pair.is_empty())
+                .all(|pair| {
+                    pair.split_once('=')
+                        .is_some_and(|(name, _)| name == VERSION_COOKIE)
+                })
+        })
+    })
+}
+
+/// Returns the request's `If-None-Match` header.
+pub(crate) fn if_none_match(request: &Request) -> Option<HeaderValue> {
+    request.headers().get(IF_NONE_MATCH).cloned()
+}
+
+fn etag_matches(if_none_match: &HeaderValue, etag: &HeaderValue) -> bool {
+    let Ok(if_none_match) = if_none_match.to_str() else {
+        return false;
+    };
+    let etag = etag.as_bytes();
+    if_none_match.split(',').any(|tag| {
+        let tag = tag.trim();
+        tag == "*" || tag.trim_start_matches("W/").as_bytes() == etag
+    })
+}
+
+/// Adds the version cookie of an html shell of asset `version` to
+/// `headers`, and headers forbidding caching if the request's cookie
+/// has another version, i.e. the browser last loaded a shell from
+/// before a deploy.
+pub(crate) fn fingerprint(request: &Request, version: &str, headers: &mut HeaderMap) {
+    let encoded =
+        percent_encoding::utf8_percent_encode(version, percent_encoding::NON_ALPHANUMERIC)
+            .to_string();
+    let previous = request
+        .headers()
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == VERSION_COOKIE)
+        .map(|(_, value)| value);
+    if previous.is_some_and(|previous| previous != encoded) {
+        tracing::info!(
+            url = request.url(),
+            previous,
+            version,
+            "sending a fresh html shell after an asset version change"
+        );
+        headers.insert(
+            CACHE_CONTROL,
+            HeaderValue::from_static("no-cache, no-store, must-revalidate"),
+        );
+        headers.insert(PRAGMA, HeaderValue::from_static("no-cache"));
+        headers.insert(EXPIRES, HeaderValue::from_static("0"));
+    }
+    let cookie = format!(
+        "{}={}; Path=/; HttpOnly; SameSite=Lax",
+        VERSION_COOKIE, encoded
+    );
+    headers.append(SET_COOKIE, HeaderValue::try_from(cookie).unwrap());
+}
+
+/// Returns the first language tag of the `Accept-Language` header,
+/// lowercased.
+fn accept_language(request: &Request) -> Option<String> {
+    request
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .map(|tag| {
+            tag.split(';')
+                .next()
+                .unwrap_or("")
+                .trim()
+                .to_ascii_lowercase()
+        })
+        .filter(|tag| !tag.is_empty() && tag != "*")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_cache() {
+        let cache = ShellCache::new().component("Home").max_entries(1);
+        let mut request = Request::test_request();
+        request.is_xhr = false;
+        request
+            .headers
+            .insert(ACCEPT_LANGUAGE, "en-US,en;q=0.9".parse().unwrap());
+
+        assert_eq!(cache.key(&request, "Users", None), None);
+        let key = cache.key(&request, "Home", Some("1".to_string())).unwrap();
+        assert_eq!(key.locale.as_deref(), Some("en-us"));
+        assert!(cache.get(&key).is_none());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(SET_COOKIE, "session=1".parse().unwrap());
+        let shell = cache.insert(key.clone(), headers, "<html>".to_string());
+        assert!(cache.get(&key).is_some());
+        assert!(!shell.respond(None).headers().contains_key(SET_COOKIE));
+        assert_eq!(shell.respond(None).status(), StatusCode::OK);
+        let response = shell.respond(Some(&shell.etag));
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        let other = cache.key(&request, "Home", Some("2".to_string())).unwrap();
+        cache.insert(other.clone(), HeaderMap::new(), "<html>".to_string());
+        assert!(cache.get(&other).is_none());
+
+        request.uri = "/foo/bar?page=2".parse().unwrap();
+        let paged = cache.key(&request, "Home", Some("1".to_string())).unwrap();
+        assert_eq!(paged.url, "/foo/bar?page=2");
+        assert_ne!(paged, key);
+
+        request
+            .headers
+            .insert(COOKIE, "inertia_version=1".parse().unwrap());
+        assert!(cache.key(&request, "Home", None).is_some());
+        request
+            .headers
+            .insert(COOKIE, "inertia_version=1; session=1".parse().unwrap());
+        assert_eq!(cache.key(&request, "Home", None), None);
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let mut request = Request::test_request();
+        request.is_xhr = false;
+
+        let mut headers = HeaderMap::new();
+        fingerprint(&request, "v/1", &mut headers);
+        assert_eq!(
+            headers[SET_COOKIE],
+            "inertia_version=v%2F1; Path=/; HttpOnly; SameSite=Lax"
+        );
+        assert!(!headers.contains_key(CACHE_CONTROL));
+
+        request
+            .headers
+            .insert(COOKIE, "session=1; inertia_version=v%2F1".parse().unwrap());
+        let mut headers = HeaderMap::new();
+        fingerprint(&request, "v/1", &mut headers);
+        assert!(!headers.contains_key(CACHE_CONTROL));
+
+        let mut headers = HeaderMap::new();
+        fingerprint(&request, "v/2", &mut headers);
+        assert_eq!(
+            headers[CACHE_CONTROL],
+            "no-cache, no-store, must-revalidate"
+        );
+    }
+}
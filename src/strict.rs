@@ -0,0 +1,145 @@
+//! Conformance checks of outgoing responses against the Inertia
+//! protocol.
+//!
+//! With [InertiaConfig::strict](crate::InertiaConfig::strict), every
+//! rendered response is checked for:
+//!
+//! - a page object with `component`, `url` and `version` fields,
+//!   whatever the [page serializer](crate::serializer);
+//! - an `X-Inertia: true` header on responses to Inertia requests, and
+//!   an `X-Inertia-Version` header matching the page version;
+//! - `303 See Other` redirects after `PUT`, `PATCH` and `DELETE`
+//!   requests, see [redirect_status](crate::protocol::redirect_status).
+//!
+//! Redirects are only checked for routes behind an
+//! [InertiaLayer](crate::layer::InertiaLayer), since handlers
+//! return them without going through the extractor.
+//!
+//! ```rust
+//! use axum_inertia::{strict::StrictMode, vite};
+//!
+//! let inertia = vite::Development::default()
+//!     .into_config()
+//!     .strict(if cfg!(test) { StrictMode::Panic } else { StrictMode::Log });
+//! ```
+
+use crate::protocol::{redirect_status, HeaderNames};
+use http::{HeaderMap, Method, StatusCode};
+use serde_json::Value;
+
+/// What to do when a response violates the protocol.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StrictMode {
+    /// Log the violations with `tracing`.
+    #[default]
+    Log,
+    /// Panic, e.g. to fail tests.
+    Panic,
+}
+
+impl StrictMode {
+    fn report(self, violations: &[String]) {
+        if violations.is_empty() {
+            return;
+        }
+        match self {
+            StrictMode::Log => {
+                tracing::error!(?violations, "inertia response violates the protocol");
+            }
+            StrictMode::Panic => {
+                panic!(
+                    "inertia response violates the protocol: {}",
+                    violations.join("; ")
+                );
+            }
+        }
+    }
+
+    /// Checks the serialized page object and headers of a response.
+    pub(crate) fn check_page(
+        self,
+        json: &[u8],
+        headers: &HeaderMap,
+        names: &HeaderNames,
+        is_xhr: bool,
+    ) {
+        let mut violations = Vec::new();
+        let page: Value = match serde_json::from_slice(json) {
+            Ok(page) => page,
+            Err(err) => {
+                self.report(&[format!("page object is not json: {}", err)]);
+                return;
+            }
+        };
+        if !page["component"].is_string() {
+            violations.push("page object `component` is not a string".to_string());
+        }
+        if !page["url"].is_string() {
+            violations.push("page object `url` is not a string".to_string());
+        }
+        let version = match page.get("version") {
+            Some(Value::String(version)) => Some(version.as_str()),
+            Some(Value::Null) => None,
+            _ => {
+                violations.push("page object `version` is missing".to_string());
+                None
+            }
+        };
+
+        if is_xhr
+            && headers
+                .get(&names.inertia)
+                .is_none_or(|value| value != "true")
+        {
+            violations.push(format!("`{}` header is not `true`", names.inertia));
+        }
+        let header_version = headers
+            .get(&names.version)
+            .and_then(|value| value.to_str().ok());
+        if version.is_some() && header_version != version {
+            violations.push(format!(
+                "`{}` header doesn't match the page version",
+                names.version
+            ));
+        }
+        self.report(&violations);
+    }
+
+    /// Checks that redirects after `PUT`, `PATCH` and `DELETE` use
+    /// `303 See Other`.
+    pub(crate) fn check_redirect(self, method: &Method, status: StatusCode) {
+        if status == StatusCode::FOUND && redirect_status(method) == StatusCode::SEE_OTHER {
+            self.report(&[format!("redirect after {} is not `303 See Other`", method)]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{X_INERTIA, X_INERTIA_VERSION};
+
+    #[test]
+    fn test_accepts_conforming_pages() {
+        let mut headers = HeaderMap::new();
+        headers.insert(X_INERTIA, "true".parse().unwrap());
+        headers.insert(X_INERTIA_VERSION, "123".parse().unwrap());
+        let json = br#"{"component":"Home","props":{},"url":"/","version":"123"}"#;
+        StrictMode::Panic.check_page(json, &headers, &HeaderNames::default(), true);
+        StrictMode::Panic.check_redirect(&Method::POST, StatusCode::FOUND);
+        StrictMode::Panic.check_redirect(&Method::PUT, StatusCode::SEE_OTHER);
+    }
+
+    #[test]
+    #[should_panic(expected = "page object `version` is missing")]
+    fn test_panics_on_missing_version() {
+        let json = br#"{"component":"Home","props":{},"url":"/"}"#;
+        StrictMode::Panic.check_page(json, &HeaderMap::new(), &HeaderNames::default(), false);
+    }
+
+    #[test]
+    #[should_panic(expected = "redirect after PUT is not `303 See Other`")]
+    fn test_panics_on_found_after_put() {
+        StrictMode::Panic.check_redirect(&Method::PUT, StatusCode::FOUND);
+    }
+}
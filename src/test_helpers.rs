@@ -0,0 +1,289 @@
+//! Helpers for testing Inertia handlers in-process, available with
+//! the `test-helpers` feature.
+//!
+//! [InertiaRequestBuilder] sends requests straight to a router or
+//! service, without binding a socket:
+//!
+//! ```rust
+//! use axum::{response::IntoResponse, routing::get, Router};
+//! use axum_inertia::{test_helpers::InertiaRequestBuilder, vite, Inertia};
+//! use serde_json::json;
+//!
+//! async fn users(i: Inertia) -> impl IntoResponse {
+//!     i.render("Users/Index", json!({ "users": ["mikey"] }))
+//! }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let app: Router = Router::new()
+//!     .route("/users", get(users))
+//!     .with_state(vite::Development::default().into_config());
+//!
+//! let res = InertiaRequestBuilder::get("/users").inertia().send(app).await;
+//!
+//! assert!(res.is_inertia());
+//! assert_eq!(res.page()["component"], "Users/Index");
+//! # }
+//! ```
+
+use crate::protocol::{
+    X_INERTIA, X_INERTIA_PARTIAL_COMPONENT, X_INERTIA_PARTIAL_DATA, X_INERTIA_VERSION,
+};
+use axum::body::{Body, Bytes};
+use axum::response::Response;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, Request, StatusCode};
+use serde_json::Value;
+use std::convert::Infallible;
+use tower::ServiceExt;
+use tower_service::Service;
+
+/// Builds a request with Inertia headers and sends it to a service.
+pub struct InertiaRequestBuilder {
+    method: Method,
+    uri: String,
+    headers: HeaderMap,
+    body: Body,
+}
+
+impl InertiaRequestBuilder {
+    pub fn new(method: Method, uri: &str) -> InertiaRequestBuilder {
+        InertiaRequestBuilder {
+            method,
+            uri: uri.to_string(),
+            headers: HeaderMap::new(),
+            body: Body::empty(),
+        }
+    }
+
+    pub fn get(uri: &str) -> InertiaRequestBuilder {
+        Self::new(Method::GET, uri)
+    }
+
+    pub fn post(uri: &str) -> InertiaRequestBuilder {
+        Self::new(Method::POST, uri)
+    }
+
+    /// Marks the request as an Inertia request.
+    pub fn inertia(self) -> Self {
+        self.header(X_INERTIA, "true")
+    }
+
+    /// Sets the asset version sent by the client.
+    pub fn version(self, version: &str) -> Self {
+        self.header(X_INERTIA_VERSION, version)
+    }
+
+    /// Requests a partial reload of `props` for `component`.
+    pub fn partial(self, component: &str, props: &[&str]) -> Self {
+        self.header(X_INERTIA_PARTIAL_COMPONENT, component)
+            .header(X_INERTIA_PARTIAL_DATA, &props.join(","))
+    }
+
+    /// Sets a request header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is not a valid header value.
+    pub fn header(mut self, name: HeaderName, value: &str) -> Self {
+        let value = HeaderValue::from_str(value).expect("valid header value");
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Sets a json request body.
+    pub fn json(self, body: &Value) -> Self {
+        let mut builder = self.header(http::header::CONTENT_TYPE, "application/json");
+        builder.body = Body::from(body.to_string());
+        builder
+    }
+
+    /// Sends the request to `service`, e.g. an [axum::Router].
+    pub async fn send<S>(self, service: S) -> TestResponse
+    where
+        S: Service<Request<Body>, Response = Response, Error = Infallible>,
+    {
+        let mut request = Request::builder()
+            .method(self.method)
+            .uri(self.uri)
+            .body(self.body)
+            .expect("valid request");
+        *request.headers_mut() = self.headers;
+
+        let response = match service.oneshot(request).await {
+            Ok(response) => response,
+            Err(err) => match err {},
+        };
+        let (parts, body) = response.into_parts();
+        let body = axum::body::to_bytes(body, usize::MAX)
+            .await
+            .expect("readable response body");
+
+        TestResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body,
+        }
+    }
+}
+
+/// A buffered response returned by [InertiaRequestBuilder::send].
+pub struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl TestResponse {
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns true if this is an Inertia json response.
+    pub fn is_inertia(&self) -> bool {
+        self.headers.get(X_INERTIA).is_some_and(|v| v == "true")
+    }
+
+    /// Returns the response body as a string.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Returns the page object, either from an Inertia json response
+    /// or from the `data-page` attribute of an initial html page.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the response contains no page object.
+    pub fn page(&self) -> Value {
+        if self.is_inertia() {
+            return serde_json::from_slice(&self.body).expect("page object json");
+        }
+
+        let text = self.text();
+        let (start, quote) = ["data-page=\"", "data-page='"]
+            .iter()
+            .find_map(|attr| {
+                text.find(attr)
+                    .map(|i| (i + attr.len(), attr.chars().last()))
+            })
+            .expect("data-page attribute");
+        let quote = quote.unwrap();
+        let end = text[start..].find(quote).expect("closing quote") + start;
+        serde_json::from_str(&unescape_html(&text[start..end])).expect("page object json")
+    }
+}
+
+/// Decodes the entities an html attribute value may be escaped with,
+/// named or numeric, in one pass so `&amp;lt;` stays `&lt;`. Unknown
+/// entities are left as is.
+fn unescape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').and_then(|end| {
+            let c = match &rest[1..end] {
+                "quot" => '"',
+                "apos" => '\'',
+                "lt" => '<',
+                "gt" => '>',
+                "amp" => '&',
+                entity => {
+                    let code = entity.strip_prefix('#')?;
+                    let code = match code.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16),
+                        None => code.parse(),
+                    };
+                    char::from_u32(code.ok()?)?
+                }
+            };
+            Some((c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Inertia, InertiaConfig};
+    use axum::{response::IntoResponse, routing::get, Router};
+    use serde_json::json;
+
+    fn app() -> Router {
+        async fn handler(i: Inertia) -> impl IntoResponse {
+            i.render(
+                "Users/Index",
+                json!({ "users": ["<mikey & co>"], "count": 1 }),
+            )
+        }
+
+        let config = InertiaConfig::new(
+            Some("123".to_string()),
+            Box::new(|props| maud::html! { div #app data-page=(props) {} }.into_string()),
+        );
+        Router::new()
+            .route("/users", get(handler))
+            .with_state(config)
+    }
+
+    #[test]
+    fn test_unescape_html() {
+        assert_eq!(
+            unescape_html("&quot;a&#34;b&#x27;c&#X27;d&#39;&lt;&gt;&amp;"),
+            "\"a\"b'c'd'<>&"
+        );
+        assert_eq!(
+            unescape_html("&amp;lt; & &bogus; &#xzz;"),
+            "&lt; & &bogus; &#xzz;"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sends_inertia_requests() {
+        let res = InertiaRequestBuilder::get("/users")
+            .inertia()
+            .version("123")
+            .send(app())
+            .await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.is_inertia());
+        assert_eq!(res.page()["props"]["count"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_reads_page_from_initial_html() {
+        let res = InertiaRequestBuilder::get("/users").send(app()).await;
+
+        assert!(!res.is_inertia());
+        assert_eq!(res.page()["props"]["users"][0], "<mikey & co>");
+    }
+
+    #[tokio::test]
+    async fn test_sends_version_mismatches() {
+        let res = InertiaRequestBuilder::get("/users")
+            .inertia()
+            .version("456")
+            .send(app())
+            .await;
+
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,216 @@
+//! TypeScript type generation for page props, available with the
+//! `typegen` feature.
+//!
+//! Derive [TypeScript] on your prop structs, then export them as
+//! TypeScript interfaces into your frontend source tree, e.g. from a
+//! build script or a small binary:
+//!
+//! ```rust
+//! use axum_inertia::typegen::{Exporter, TypeScript};
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize, TypeScript)]
+//! #[serde(rename_all = "camelCase")]
+//! struct Post {
+//!     id: u64,
+//!     title: String,
+//! }
+//!
+//! #[derive(Serialize, TypeScript)]
+//! #[serde(rename_all = "camelCase")]
+//! struct HomeProps {
+//!     posts: Vec<Post>,
+//!     current_user: Option<String>,
+//! }
+//!
+//! let ts = Exporter::new().add::<Post>().add::<HomeProps>().render();
+//! assert!(ts.contains("export interface HomeProps {\n  posts: Post[];\n  currentUser?: string | null;\n}"));
+//! // Exporter::new().add::<Post>().add::<HomeProps>().write("client/src/types/props.ts")?;
+//! ```
+//!
+//! The derive respects the serde attributes `rename`, `rename_all =
+//! "camelCase"` and `skip`. Fields with `skip_serializing_if`, and
+//! `Option` fields, are optional. `flatten` isn't supported.
+
+pub use axum_inertia_macros::TypeScript;
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Types that have a TypeScript representation.
+pub trait TypeScript {
+    /// The TypeScript type used when referring to this type, e.g.
+    /// `string` or `Post[]`.
+    fn ts_type() -> String;
+
+    /// The declaration of this type, for named types such as derived
+    /// interfaces.
+    fn ts_declaration() -> Option<String> {
+        None
+    }
+}
+
+macro_rules! impl_typescript {
+    ($ts:literal, $($ty:ty),*) => {
+        $(
+            impl TypeScript for $ty {
+                fn ts_type() -> String {
+                    $ts.to_string()
+                }
+            }
+        )*
+    };
+}
+
+impl_typescript!("string", String, str, char);
+impl_typescript!("boolean", bool);
+impl_typescript!("number", u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+impl_typescript!("unknown", serde_json::Value);
+
+impl<T: TypeScript + ?Sized> TypeScript for &T {
+    fn ts_type() -> String {
+        T::ts_type()
+    }
+}
+
+impl<T: TypeScript> TypeScript for Option<T> {
+    fn ts_type() -> String {
+        format!("{} | null", T::ts_type())
+    }
+}
+
+impl<T: TypeScript> TypeScript for Vec<T> {
+    fn ts_type() -> String {
+        array_type(T::ts_type())
+    }
+}
+
+impl<T: TypeScript> TypeScript for [T] {
+    fn ts_type() -> String {
+        array_type(T::ts_type())
+    }
+}
+
+impl<T: TypeScript> TypeScript for HashMap<String, T> {
+    fn ts_type() -> String {
+        format!("Record<string, {}>", T::ts_type())
+    }
+}
+
+impl<T: TypeScript> TypeScript for BTreeMap<String, T> {
+    fn ts_type() -> String {
+        format!("Record<string, {}>", T::ts_type())
+    }
+}
+
+fn array_type(inner: String) -> String {
+    if inner.contains(' ') {
+        format!("({})[]", inner)
+    } else {
+        format!("{}[]", inner)
+    }
+}
+
+/// Collects TypeScript declarations into a single file.
+#[derive(Default)]
+pub struct Exporter {
+    declarations: Vec<String>,
+}
+
+impl Exporter {
+    pub fn new() -> Exporter {
+        Self::default()
+    }
+
+    /// Adds the declaration of `T`.
+    pub fn add<T: TypeScript>(mut self) -> Self {
+        if let Some(declaration) = T::ts_declaration() {
+            self.declarations.push(declaration);
+        }
+        self
+    }
+
+    /// Renders all declarations.
+    pub fn render(&self) -> String {
+        let mut out = String::from("// This file is generated by axum-inertia. Do not edit.\n");
+        for declaration in &self.declarations {
+            out.push('\n');
+            out.push_str(declaration);
+        }
+        out
+    }
+
+    /// Writes all declarations to `path`, creating parent directories
+    /// as needed. The file is left untouched if it is up to date.
+    pub fn write(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        let contents = self.render();
+        if std::fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+
+    #[derive(Serialize, TypeScript)]
+    #[allow(dead_code)]
+    struct User {
+        id: u64,
+        #[serde(rename = "displayName")]
+        name: String,
+        #[serde(skip)]
+        password: String,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        roles: Vec<String>,
+    }
+
+    #[derive(Serialize, TypeScript)]
+    #[serde(rename_all = "camelCase", rename = "Props")]
+    #[allow(dead_code)]
+    struct PageProps {
+        current_user: Option<User>,
+        tags: Vec<Option<String>>,
+        counts: HashMap<String, i32>,
+        extra: serde_json::Value,
+    }
+
+    #[test]
+    fn test_derived_declarations() {
+        assert_eq!(
+            User::ts_declaration().unwrap(),
+            "export interface User {\n  id: number;\n  displayName: string;\n  roles?: string[];\n}\n"
+        );
+        assert_eq!(PageProps::ts_type(), "Props");
+        assert_eq!(
+            PageProps::ts_declaration().unwrap(),
+            "export interface Props {\n  currentUser?: User | null;\n  tags: (string | null)[];\n  counts: Record<string, number>;\n  extra: unknown;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_exporter_writes_file() {
+        let dir = std::env::temp_dir().join(format!("axum-inertia-typegen-{}", std::process::id()));
+        let path = dir.join("types/props.ts");
+
+        let exporter = Exporter::new()
+            .add::<User>()
+            .add::<PageProps>()
+            .add::<String>();
+        exporter.write(&path).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents, exporter.render());
+        assert!(contents.contains("export interface User {"));
+        assert!(contents.contains("export interface Props {"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,91 @@
+//! Asset versions derived from build metadata.
+//!
+//! The [vite::Production](crate::vite::Production) config hashes the
+//! manifest to get the asset version. Apps that deploy the frontend
+//! with the binary can instead derive it from the build, with the
+//! [build_version](crate::build_version) macro:
+//!
+//! ```rust
+//! use axum_inertia::{build_version, vite};
+//!
+//! let inertia = vite::Development::default()
+//!     .version(build_version!())
+//!     .into_config();
+//! ```
+//!
+//! The macro reads the git commit from the `VERGEN_GIT_SHA` (as set by
+//! [vergen]) or `GIT_SHA` environment variables at compile time, and
+//! falls back to `CARGO_PKG_VERSION` alone.
+//!
+//! [vergen]: https://crates.io/crates/vergen
+
+use std::fmt;
+
+/// An asset version, sent in the `X-Inertia-Version` header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Version(String);
+
+impl Version {
+    /// Derives a version from the package version and, if known, the
+    /// git commit, e.g. `1.2.0+3f2a9c1d0b4e`.
+    ///
+    /// Call it through [build_version](crate::build_version), so that
+    /// the metadata of the app is used rather than this crate's.
+    pub fn from_build_info(pkg_version: &str, git_sha: Option<&str>) -> Version {
+        match git_sha.map(str::trim).filter(|sha| !sha.is_empty()) {
+            Some(sha) => Version(format!("{}+{}", pkg_version, sha.get(..12).unwrap_or(sha))),
+            None => Version(pkg_version.to_string()),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<Version> for String {
+    fn from(version: Version) -> String {
+        version.0
+    }
+}
+
+/// Returns the [Version] of the calling crate's build. See the
+/// [version](crate::version) module.
+#[macro_export]
+macro_rules! build_version {
+    () => {
+        $crate::version::Version::from_build_info(
+            env!("CARGO_PKG_VERSION"),
+            option_env!("VERGEN_GIT_SHA").or(option_env!("GIT_SHA")),
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_build_info() {
+        assert_eq!(Version::from_build_info("1.2.0", None).as_str(), "1.2.0");
+        assert_eq!(
+            Version::from_build_info("1.2.0", Some(" ")).as_str(),
+            "1.2.0"
+        );
+        assert_eq!(
+            Version::from_build_info("1.2.0", Some("3f2a9c1d0b4e5f60718293a4b5c6d7e8f9a0b1c2"))
+                .as_str(),
+            "1.2.0+3f2a9c1d0b4e"
+        );
+        assert_eq!(
+            Version::from_build_info("1.2.0", Some("abc")).to_string(),
+            "1.2.0+abc"
+        );
+    }
+}
@@ -26,13 +26,60 @@
 //! };
 //! ```
 //!
+//! Alternatively, [auto] picks the environment by checking whether
+//! the vite dev server is running:
+//!
+//! ```rust,no_run
+//! use axum_inertia::vite;
+//!
+//! let inertia = vite::auto("client/dist/manifest.json", "src/main.ts").unwrap();
+//! ```
+//!
 //! [vitejs]: https://vitejs.dev
 use crate::config::InertiaConfig;
+use crate::html::escape_json;
+use crate::page_store::PageStore;
 use hex::encode;
 use maud::{html, PreEscaped};
 use serde::Deserialize;
 use sha1::{Digest, Sha1};
 use std::collections::HashMap;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Returns a [Development] config if the vite dev server is running
+/// on the default port, and a [Production] config otherwise.
+///
+/// The dev server is probed once, when this is called. Use
+/// [Development::is_running] to customize either config:
+///
+/// ```rust,no_run
+/// use axum_inertia::vite;
+///
+/// let development = vite::Development::default().port(3000).title("My app");
+/// let inertia = if development.is_running() {
+///     development.into_config()
+/// } else {
+///     vite::Production::new("client/dist/manifest.json", "src/main.ts")
+///         .unwrap()
+///         .title("My app")
+///         .into_config()
+/// };
+/// ```
+pub fn auto(
+    manifest_path: &str,
+    main: &'static str,
+) -> Result<InertiaConfig, Box<dyn std::error::Error>> {
+    let development = Development::default().main(main);
+    if development.is_running() {
+        Ok(development.into_config())
+    } else {
+        Ok(Production::new(manifest_path, main)?.into_config())
+    }
+}
 
 pub struct Development {
     base: &'static str,
@@ -42,6 +89,8 @@ pub struct Development {
     title: &'static str,
     react: bool,
     https: bool,
+    public_origin: Option<&'static str>,
+    version: Option<String>,
 }
 
 impl Default for Development {
@@ -54,6 +103,8 @@ impl Default for Development {
             title: "Vite",
             react: false,
             https: false,
+            public_origin: None,
+            version: None,
         }
     }
 }
@@ -93,6 +144,14 @@ impl Development {
         self
     }
 
+    /// Sets the asset version. Defaults to none, since assets are
+    /// served by the dev server. See the [version](crate::version)
+    /// module.
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
     /// Sets up vite for react usage.
     ///
     /// Currently, this will include preamble code for using react-refresh in the html head.
@@ -107,17 +166,53 @@ impl Development {
         self
     }
 
+    /// Returns true if the dev server accepts connections on
+    /// `localhost` at the configured port.
+    pub fn is_running(&self) -> bool {
+        let Ok(addrs) = ("localhost", self.port).to_socket_addrs() else {
+            return false;
+        };
+        addrs
+            .into_iter()
+            .any(|addr| TcpStream::connect_timeout(&addr, Duration::from_millis(100)).is_ok())
+    }
+
+    /// Sets the origin the browser loads dev assets from, e.g.
+    /// `https://app.test` when developing behind a reverse proxy that
+    /// forwards vite requests. Defaults to `localhost` at the
+    /// configured port.
+    ///
+    /// An empty origin loads assets from the same origin as the page.
+    ///
+    /// ```rust
+    /// use axum_inertia::vite;
+    ///
+    /// let inertia = vite::Development::default()
+    ///     .public_origin("https://app.test")
+    ///     .into_config();
+    /// ```
+    pub fn public_origin(mut self, origin: &'static str) -> Self {
+        self.public_origin = Some(origin.trim_end_matches('/'));
+        self
+    }
+
+    /// Returns the origin dev assets are loaded from.
+    fn origin(&self) -> String {
+        match self.public_origin {
+            Some(origin) => origin.to_string(),
+            None => {
+                let http_protocol = if self.https { "https" } else { "http" };
+                format!("{}://localhost:{}", http_protocol, self.port)
+            }
+        }
+    }
+
     pub fn into_config(self) -> InertiaConfig {
-        let layout = Box::new(move |props| {
-            let http_protocol = if self.https { "https" } else { "http" };
-            let vite_src = format!(
-                "{}://localhost:{}{}/@vite/client",
-                http_protocol, self.port, self.base
-            );
-            let main_src = format!(
-                "{}://localhost:{}{}/{}",
-                http_protocol, self.port, self.base, self.main
-            );
+        let version = self.version.clone();
+        let layout = Box::new(move |props: String| {
+            let origin = self.origin();
+            let vite_src = format!("{}{}/@vite/client", origin, self.base);
+            let main_src = format!("{}{}/{}", origin, self.base, self.main);
             let preamble_code = if self.react {
                 Some(PreEscaped(self.build_react_preamble()))
             } else {
@@ -137,49 +232,239 @@ impl Development {
                     }
 
                     body {
-                        div #app data-page=(props) {}
+                        div #app data-page=(escape_json(&props)) {}
                     }
                 }
             }
             .into_string()
         });
 
-        InertiaConfig::new(None, layout)
+        InertiaConfig::new(version, layout)
     }
 
     fn build_react_preamble(&self) -> String {
-        let http_protocol = if self.https { "https" } else { "http" };
         format!(
             r#"
-import RefreshRuntime from "{}://localhost:{}{}/@react-refresh"
+import RefreshRuntime from "{}{}/@react-refresh"
 RefreshRuntime.injectIntoGlobalHook(window)
 window.$RefreshReg$ = () => {{}}
 window.$RefreshSig$ = () => (type) => type
 window.__vite_plugin_react_preamble_installed__ = true
 "#,
-            http_protocol, self.port, self.base
+            self.origin(),
+            self.base
         )
     }
 }
 
+type PageEntry = dyn Fn(&str) -> String + Send + Sync;
+
 pub struct Production {
     main: ManifestEntry,
+    /// The other entries and chunks of the manifest.
+    chunks: HashMap<String, ManifestEntry>,
+    page_entry: Option<Arc<PageEntry>>,
+    #[cfg(feature = "compress")]
+    compress_threshold: Option<usize>,
+    page_store: Option<PageStore>,
     css: Option<String>,
     title: &'static str,
     lang: &'static str,
     /// SHA1 hash of the contents of the manifest file.
     version: String,
+    legacy_assets: LegacyAssets,
+    legacy: bool,
+    /// The build directory, if known from the manifest path.
+    #[cfg_attr(not(feature = "assets"), allow(dead_code))]
+    dist_dir: Option<PathBuf>,
+}
+
+/// Files added to the manifest by `@vitejs/plugin-legacy`.
+#[derive(Debug, Default)]
+struct LegacyAssets {
+    /// Polyfills for modern browsers (`modernPolyfills`).
+    modern_polyfills: Option<String>,
+    /// Polyfills and SystemJS for legacy browsers.
+    polyfills: Option<String>,
+    /// The legacy build of the main entry.
+    entry: Option<String>,
+}
+
+impl LegacyAssets {
+    fn from_manifest(manifest: &HashMap<String, ManifestEntry>, main: &str) -> LegacyAssets {
+        let legacy_main = match main.rsplit_once('.') {
+            Some((name, ext)) => format!("{}-legacy.{}", name, ext),
+            None => format!("{}-legacy", main),
+        };
+        let file = |key: &str| manifest.get(key).map(|entry| format!("/{}", entry.file));
+        LegacyAssets {
+            modern_polyfills: file("vite/legacy-polyfills"),
+            polyfills: file("vite/legacy-polyfills-legacy"),
+            entry: file(&legacy_main),
+        }
+    }
 }
 
+// Inline scripts emitted by `@vitejs/plugin-legacy`. See:
+// https://github.com/vitejs/vite/blob/main/packages/plugin-legacy/src/snippets.ts
+const SAFARI10_NO_MODULE_FIX: &str = r#"!function(){var e=document,t=e.createElement("script");if(!("noModule"in t)&&"onbeforeload"in t){var n=!1;e.addEventListener("beforeload",(function(e){if(e.target===t)n=!0;else if(!e.target.hasAttribute("nomodule")||!n)return;e.preventDefault()}),!0),t.type="module",t.src=".",e.head.appendChild(t),t.remove()}}();"#;
+const SYSTEMJS_INLINE_CODE: &str =
+    r#"System.import(document.getElementById('vite-legacy-entry').getAttribute('data-src'))"#;
+const DETECT_MODERN_BROWSER_CODE: &str = r#"import.meta.url;import("_").catch(()=>1);(async function*(){})().next();if(location.protocol!="file:"){window.__vite_is_modern_browser=true}"#;
+const DYNAMIC_FALLBACK_INLINE_CODE: &str = r#"!function(){if(window.__vite_is_modern_browser)return;console.warn("vite: loading legacy chunks, syntax error above and the same error below should be ignored");var e=document.getElementById("vite-legacy-polyfill"),n=document.createElement("script");n.src=e.src,n.onload=function(){System.import(document.getElementById('vite-legacy-entry').getAttribute('data-src'))},document.body.appendChild(n)}();"#;
+
 impl Production {
     pub fn new(
         manifest_path: &str,
         main: &'static str,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let bytes = std::fs::read(manifest_path)?;
-        let manifest: &'static str = Box::leak(String::from_utf8(bytes)?.into_boxed_str());
+        let mut production = Self::new_from_bytes(&bytes, main)?;
+        production.dist_dir = dist_dir_of(Path::new(manifest_path));
+        Ok(production)
+    }
+
+    /// Constructs a production config from a vite build directory,
+    /// reading `.vite/manifest.json` (vite 5 and later) or
+    /// `manifest.json`.
+    ///
+    /// Every file referenced by the manifest must exist in `dist_dir`,
+    /// so that a partial deploy fails at startup rather than with 404s
+    /// at runtime.
+    ///
+    /// ```rust,no_run
+    /// use axum_inertia::vite;
+    ///
+    /// let inertia = vite::Production::from_dist_dir("client/dist", "src/main.ts")
+    ///     .unwrap()
+    ///     .into_config();
+    /// ```
+    pub fn from_dist_dir(
+        dist_dir: impl AsRef<Path>,
+        main: &'static str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let dist_dir = dist_dir.as_ref();
+        let manifest_path = [".vite/manifest.json", "manifest.json"]
+            .iter()
+            .map(|path| dist_dir.join(path))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                ViteError::ManifestMissing(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("no vite manifest in {}", dist_dir.display()),
+                ))
+            })?;
+        let bytes = std::fs::read(manifest_path).map_err(ViteError::ManifestMissing)?;
+        let mut production = Self::new_from_bytes(&bytes, main)?;
+        production.dist_dir = Some(dist_dir.to_path_buf());
+
+        let mut missing: Vec<String> = production
+            .asset_files()
+            .filter(|file| !dist_dir.join(file).is_file())
+            .map(str::to_string)
+            .collect();
+        if !missing.is_empty() {
+            missing.sort();
+            missing.dedup();
+            return Err(ViteError::AssetsMissing(missing).into());
+        }
+        Ok(production)
+    }
 
-        Self::new_from_string(manifest, main)
+    /// Returns the paths of the files referenced by the manifest,
+    /// relative to the build directory.
+    fn asset_files(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(&self.main)
+            .chain(self.chunks.values())
+            .flat_map(|entry| {
+                std::iter::once(entry.file.as_str())
+                    .chain(entry.css.iter().flatten().map(String::as_str))
+            })
+    }
+
+    /// Checks that every file referenced by the manifest exists in the
+    /// build directory and, for entries with an `integrity` field, that
+    /// the file's hash matches it, so that a stale or partial deploy
+    /// fails at startup instead of shipping integrity attributes that
+    /// browsers reject.
+    ///
+    /// Supports `sha256`, `sha384` and `sha512` integrity values. The
+    /// build directory is found as in [dist_dir](Production::dist_dir).
+    ///
+    /// ```rust,ignore
+    /// use axum_inertia::vite;
+    ///
+    /// let inertia = vite::Production::new("client/dist/.vite/manifest.json", "src/main.ts")
+    ///     .unwrap()
+    ///     .verify()
+    ///     .unwrap()
+    ///     .into_config();
+    /// ```
+    ///
+    /// Requires the `integrity` feature.
+    #[cfg(feature = "integrity")]
+    pub fn verify(self) -> Result<Self, Box<dyn std::error::Error>> {
+        let dist_dir = self.dist_dir.as_deref().ok_or(ViteError::DistDirUnknown)?;
+
+        let mut missing: Vec<String> = self
+            .asset_files()
+            .filter(|file| !dist_dir.join(file).is_file())
+            .map(str::to_string)
+            .collect();
+        if !missing.is_empty() {
+            missing.sort();
+            missing.dedup();
+            return Err(ViteError::AssetsMissing(missing).into());
+        }
+
+        let mut mismatched = Vec::new();
+        for entry in std::iter::once(&self.main).chain(self.chunks.values()) {
+            let Some(integrity) = &entry.integrity else {
+                continue;
+            };
+            let contents = std::fs::read(dist_dir.join(&entry.file))?;
+            if !integrity_matches(integrity, &contents) {
+                mismatched.push(entry.file.clone());
+            }
+        }
+        if !mismatched.is_empty() {
+            mismatched.sort();
+            mismatched.dedup();
+            return Err(ViteError::IntegrityMismatch(mismatched).into());
+        }
+        Ok(self)
+    }
+
+    /// Constructs a production config from the contents of a manifest
+    /// file, e.g. one embedded with `include_bytes!`.
+    ///
+    /// ```rust,ignore
+    /// use axum_inertia::vite;
+    ///
+    /// let manifest = include_bytes!("../client/dist/.vite/manifest.json");
+    /// let inertia = vite::Production::new_from_bytes(manifest, "src/main.ts")
+    ///     .unwrap()
+    ///     .into_config();
+    /// ```
+    pub fn new_from_bytes(
+        manifest: &[u8],
+        main: &'static str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::new_from_string(std::str::from_utf8(manifest)?, main)
+    }
+
+    /// Constructs a production config from a manifest read from
+    /// `reader`, e.g. a file fetched from object storage.
+    pub async fn new_from_reader<R>(
+        mut reader: R,
+        main: &'static str,
+    ) -> Result<Self, Box<dyn std::error::Error>>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Self::new_from_bytes(&bytes, main)
     }
 
     fn new_from_string(
@@ -187,6 +472,7 @@ impl Production {
         main: &'static str,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let mut manifest: HashMap<String, ManifestEntry> = serde_json::from_str(manifest_string)?;
+        let legacy_assets = LegacyAssets::from_manifest(&manifest, main);
         let entry = manifest.remove(main).ok_or(ViteError::EntryMissing(main))?;
         let mut hasher = Sha1::new();
         hasher.update(manifest_string.as_bytes());
@@ -205,13 +491,100 @@ impl Production {
         };
         Ok(Self {
             main: entry,
+            chunks: manifest,
+            page_entry: None,
+            #[cfg(feature = "compress")]
+            compress_threshold: None,
+            page_store: None,
             css,
             title: "Vite",
             lang: "en",
             version,
+            legacy_assets,
+            legacy: false,
+            dist_dir: None,
         })
     }
 
+    /// Sets the vite build directory, served by
+    /// [assets_router](Production::assets_router). It is found from the
+    /// manifest path when using [new](Production::new) or
+    /// [from_dist_dir](Production::from_dist_dir).
+    pub fn dist_dir(mut self, dist_dir: impl Into<PathBuf>) -> Self {
+        self.dist_dir = Some(dist_dir.into());
+        self
+    }
+
+    /// Returns a router serving the `assets` directory of the vite
+    /// build at `/assets`, with gzip and brotli variants built by e.g.
+    /// `vite-plugin-compression` when the client accepts them.
+    ///
+    /// Vite puts a content hash in the names of these files, so they
+    /// are sent with `Cache-Control: public, max-age=31536000,
+    /// immutable`.
+    ///
+    /// ```rust,ignore
+    /// use axum::Router;
+    /// use axum_inertia::vite;
+    ///
+    /// let production = vite::Production::from_dist_dir("client/dist", "src/main.ts").unwrap();
+    /// let app: Router = Router::new().merge(production.assets_router());
+    /// ```
+    ///
+    /// Requires the `assets` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the build directory isn't known, i.e. the config was
+    /// built from manifest contents without calling
+    /// [dist_dir](Production::dist_dir).
+    #[cfg(feature = "assets")]
+    pub fn assets_router<S>(&self) -> axum::Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        use http::header::CACHE_CONTROL;
+        use http::HeaderValue;
+        use tower_http::services::ServeDir;
+        use tower_http::set_header::SetResponseHeaderLayer;
+
+        let dist_dir = self
+            .dist_dir
+            .as_ref()
+            .expect("vite build directory unknown, call Production::dist_dir");
+        let assets = ServeDir::new(dist_dir.join("assets"))
+            .precompressed_gzip()
+            .precompressed_br();
+        let immutable = |response: &axum::response::Response| {
+            response
+                .status()
+                .is_success()
+                .then(|| HeaderValue::from_static("public, max-age=31536000, immutable"))
+        };
+        axum::Router::new()
+            .nest_service("/assets", assets)
+            .layer(SetResponseHeaderLayer::overriding(CACHE_CONTROL, immutable))
+    }
+
+    /// Sets the asset version, instead of the hash of the manifest.
+    /// See the [version](crate::version) module.
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Includes the polyfills and legacy entry built by
+    /// [@vitejs/plugin-legacy], for browsers without native ES module
+    /// support.
+    ///
+    /// Legacy assets missing from the manifest are skipped.
+    ///
+    /// [@vitejs/plugin-legacy]: https://www.npmjs.com/package/@vitejs/plugin-legacy
+    pub fn legacy(mut self) -> Self {
+        self.legacy = true;
+        self
+    }
+
     pub fn lang(mut self, lang: &'static str) -> Self {
         self.lang = lang;
         self
@@ -222,41 +595,288 @@ impl Production {
         self
     }
 
-    pub fn into_config(self) -> InertiaConfig {
-        let layout = Box::new(move |props| {
-            let css = self.css.clone().unwrap_or("".to_string());
-            let main_path = format!("/{}", self.main.file);
-            let main_integrity = self.main.integrity.clone();
+    /// Maps components to their manifest entry, so the initial html
+    /// preloads the chunk and css of the rendered page, instead of
+    /// waiting for the main entry to import them.
+    ///
+    /// Pages must be built as entries, e.g. with vite's
+    /// `build.rollupOptions.input`, or imported with
+    /// `import.meta.glob`. Components missing from the manifest only
+    /// get the main entry.
+    ///
+    /// ```rust,no_run
+    /// use axum_inertia::vite;
+    ///
+    /// let inertia = vite::Production::new("client/dist/manifest.json", "src/main.ts")
+    ///     .unwrap()
+    ///     .page_entry(|component| format!("src/Pages/{}.vue", component))
+    ///     .into_config();
+    /// ```
+    pub fn page_entry<F>(mut self, entry: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        self.page_entry = Some(Arc::new(entry));
+        self
+    }
 
-            html! {
-                html lang=(self.lang) {
-                    head {
-                        title { (self.title) }
-                        meta charset="utf-8";
-                        meta name="viewport" content="width=device-width, initial-scale=1.0";
-                        @if let Some(integrity) = main_integrity {
-                            script type="module" src=(main_path) integrity=(integrity) {}
-                        } else {
-                            script type="module" src=(main_path) {}
+    /// Returns the js files and stylesheets of the entry of
+    /// `component`, including the chunks it imports, but not the
+    /// main entry.
+    fn page_assets(&self, component: &str) -> (Vec<&str>, Vec<&str>) {
+        let mut files = Vec::new();
+        let mut css = Vec::new();
+        let Some(page_entry) = &self.page_entry else {
+            return (files, css);
+        };
+        let mut keys = vec![page_entry(component)];
+        let mut seen = Vec::new();
+        while let Some(key) = keys.pop() {
+            if seen.contains(&key) {
+                continue;
+            }
+            let Some(entry) = self.chunks.get(&key) else {
+                continue;
+            };
+            files.push(entry.file.as_str());
+            for file in entry.css.iter().flatten() {
+                if !css.contains(&file.as_str()) {
+                    css.push(file.as_str());
+                }
+            }
+            keys.extend(entry.imports.iter().flatten().cloned());
+            seen.push(key);
+        }
+        (files, css)
+    }
+
+    /// Embeds large page objects gzipped and base64-encoded, with an
+    /// inline script that decodes them before loading the main entry.
+    /// Available with the `compress` feature.
+    ///
+    /// Page objects shorter than `threshold` bytes are embedded as
+    /// usual. The script relies on `DecompressionStream`, so pages
+    /// aren't compressed for [legacy](Production::legacy) builds. The
+    /// main entry is then loaded with a dynamic import, and its
+    /// integrity is only checked by a `modulepreload` link.
+    ///
+    /// ```rust,ignore
+    /// use axum_inertia::vite;
+    ///
+    /// let inertia = vite::Production::new("client/dist/manifest.json", "src/main.ts")
+    ///     .unwrap()
+    ///     .compress_page(64 * 1024)
+    ///     .into_config();
+    /// ```
+    #[cfg(feature = "compress")]
+    pub fn compress_page(mut self, threshold: usize) -> Self {
+        self.compress_threshold = Some(threshold);
+        self
+    }
+
+    /// Returns the gzipped and base64-encoded page object, if it
+    /// should be compressed.
+    #[cfg(feature = "compress")]
+    fn compressed_page(&self, page: &str) -> Option<String> {
+        use base64::Engine;
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let threshold = self.compress_threshold?;
+        if self.legacy || page.len() < threshold {
+            return None;
+        }
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(page.as_bytes()).ok()?;
+        let gzipped = encoder.finish().ok()?;
+        Some(base64::engine::general_purpose::STANDARD.encode(gzipped))
+    }
+
+    #[cfg(not(feature = "compress"))]
+    fn compressed_page(&self, _page: &str) -> Option<String> {
+        None
+    }
+
+    /// Serves page objects from `store` instead of embedding them in
+    /// the html, with an inline script that fetches them before loading
+    /// the main entry. See the [page_store](crate::page_store) module.
+    ///
+    /// Like [compressed](Production::compress_page) pages, it doesn't
+    /// apply to [legacy](Production::legacy) builds, and takes
+    /// precedence over compression.
+    pub fn external_page(mut self, store: PageStore) -> Self {
+        self.page_store = Some(store);
+        self
+    }
+
+    /// Renders the layout, with the assets of `component` if given.
+    fn render(&self, component: Option<&str>, props: String) -> String {
+        let css = self.css.clone().unwrap_or("".to_string());
+        let main_path = format!("/{}", self.main.file);
+        let main_integrity = self.main.integrity.clone();
+        let legacy = self.legacy.then_some(&self.legacy_assets);
+        let (page_files, page_css) = match component {
+            Some(component) => self.page_assets(component),
+            None => (Vec::new(), Vec::new()),
+        };
+        let page_files = page_files.into_iter().map(|file| format!("/{}", file));
+        let page_css = page_css.into_iter().map(|file| format!("/{}", file));
+        let page_src = match &self.page_store {
+            Some(store) if !self.legacy => store.insert(&props),
+            _ => None,
+        };
+        let compressed = match page_src {
+            Some(_) => None,
+            None => self.compressed_page(&props),
+        };
+
+        html! {
+            html lang=(self.lang) {
+                head {
+                    title { (self.title) }
+                    meta charset="utf-8";
+                    meta name="viewport" content="width=device-width, initial-scale=1.0";
+                    @if let Some(src) = legacy.and_then(|l| l.modern_polyfills.as_ref()) {
+                        script type="module" crossorigin src=(src) {}
+                    }
+                    @if compressed.is_some() || page_src.is_some() {
+                        @if let Some(integrity) = &main_integrity {
+                            link rel="modulepreload" href=(main_path) integrity=(integrity);
+                        } @else {
+                            link rel="modulepreload" href=(main_path);
                         }
-                        (PreEscaped(css))
+                    } @else if let Some(integrity) = main_integrity {
+                        script type="module" src=(main_path) integrity=(integrity) {}
+                    } @else {
+                        script type="module" src=(main_path) {}
                     }
-                    body {
-                        div #app data-page=(props) {}
+                    @for file in page_files {
+                        link rel="modulepreload" href=(file);
+                    }
+                    (PreEscaped(css))
+                    @for file in page_css {
+                        link rel="stylesheet" href=(file);
+                    }
+                    @if legacy.is_some() {
+                        script type="module" { (PreEscaped(DETECT_MODERN_BROWSER_CODE)) }
+                        script type="module" { (PreEscaped(DYNAMIC_FALLBACK_INLINE_CODE)) }
+                    }
+                }
+                body {
+                    @if let Some(src) = &page_src {
+                        div #app {}
+                        script type="module" {
+                            (PreEscaped(fetch_page_script(src, &main_path)))
+                        }
+                    } @else if let Some(payload) = &compressed {
+                        div #app {}
+                        script type="module" {
+                            (PreEscaped(decompress_script(payload, &main_path)))
+                        }
+                    } @else {
+                        div #app data-page=(escape_json(&props)) {}
+                    }
+                    @if let Some(legacy) = legacy {
+                        script nomodule { (PreEscaped(SAFARI10_NO_MODULE_FIX)) }
+                        @if let Some(src) = &legacy.polyfills {
+                            script nomodule crossorigin id="vite-legacy-polyfill" src=(src) {}
+                        }
+                        @if let Some(src) = &legacy.entry {
+                            script nomodule crossorigin id="vite-legacy-entry" data-src=(src) {
+                                (PreEscaped(SYSTEMJS_INLINE_CODE))
+                            }
+                        }
                     }
                 }
             }
-            .into_string()
-        });
+        }
+        .into_string()
+    }
+
+    pub fn into_config(self) -> InertiaConfig {
+        let version = self.version.clone();
+        let has_page_entry = self.page_entry.is_some();
+        let production = Arc::new(self);
+        let layout = {
+            let production = production.clone();
+            Box::new(move |props| production.render(None, props))
+        };
+        let config = InertiaConfig::new(Some(version), layout);
+        if has_page_entry {
+            config
+                .component_layout(move |component, props| production.render(Some(component), props))
+        } else {
+            config
+        }
+    }
+}
 
-        InertiaConfig::new(Some(self.version), layout)
+/// Returns an inline module script that decompresses the base64
+/// `payload` into the `data-page` attribute of the app element, then
+/// imports the main entry.
+fn decompress_script(payload: &str, main_src: &str) -> String {
+    format!(
+        r#"const b=Uint8Array.from(atob("{}"),c=>c.charCodeAt(0));const s=new Blob([b]).stream().pipeThrough(new DecompressionStream("gzip"));document.getElementById("app").dataset.page=await new Response(s).text();import({});"#,
+        payload,
+        serde_json::to_string(main_src).unwrap()
+    )
+}
+
+/// Returns an inline module script that fetches the page object at
+/// `src` into the `data-page` attribute of the app element, then
+/// imports the main entry.
+fn fetch_page_script(src: &str, main_src: &str) -> String {
+    format!(
+        r#"const r=await fetch({});document.getElementById("app").dataset.page=await r.text();import({});"#,
+        serde_json::to_string(src).unwrap(),
+        serde_json::to_string(main_src).unwrap()
+    )
+}
+
+/// Returns the build directory of the manifest at `manifest_path`,
+/// i.e. its directory, or the parent of its `.vite` directory.
+fn dist_dir_of(manifest_path: &Path) -> Option<PathBuf> {
+    let dir = manifest_path.parent()?;
+    match dir.file_name() {
+        Some(name) if name == ".vite" => dir.parent().map(Path::to_path_buf),
+        _ => Some(dir.to_path_buf()),
     }
 }
 
+/// Returns true if any of the space-separated hashes of an
+/// `integrity` value matches `contents`.
+#[cfg(feature = "integrity")]
+fn integrity_matches(integrity: &str, contents: &[u8]) -> bool {
+    use base64::Engine;
+    use sha2::{Sha256, Sha384, Sha512};
+
+    integrity.split_whitespace().any(|hash| {
+        // Options after a `?` are reserved, and ignored by browsers.
+        let hash = hash.split('?').next().unwrap_or(hash);
+        let Some((algorithm, expected)) = hash.split_once('-') else {
+            return false;
+        };
+        let digest = match algorithm {
+            "sha256" => Sha256::digest(contents).to_vec(),
+            "sha384" => Sha384::digest(contents).to_vec(),
+            "sha512" => Sha512::digest(contents).to_vec(),
+            _ => return false,
+        };
+        base64::engine::general_purpose::STANDARD.encode(digest) == expected
+    })
+}
+
 #[derive(Debug)]
 pub enum ViteError {
     ManifestMissing(std::io::Error),
     EntryMissing(&'static str),
+    /// Files referenced by the manifest that are missing from the build
+    /// directory.
+    AssetsMissing(Vec<String>),
+    /// Files whose contents don't match their manifest `integrity`.
+    IntegrityMismatch(Vec<String>),
+    /// The build directory isn't known, so the build can't be checked.
+    DistDirUnknown,
 }
 
 impl std::fmt::Display for ViteError {
@@ -264,6 +884,17 @@ impl std::fmt::Display for ViteError {
         match self {
             Self::ManifestMissing(_) => write!(f, "couldn't open manifest file"),
             Self::EntryMissing(entry) => write!(f, "manifest missing entry for {}", entry),
+            Self::AssetsMissing(files) => {
+                write!(f, "vite build missing assets: {}", files.join(", "))
+            }
+            Self::IntegrityMismatch(files) => {
+                write!(
+                    f,
+                    "vite build assets don't match their integrity: {}",
+                    files.join(", ")
+                )
+            }
+            Self::DistDirUnknown => write!(f, "vite build directory unknown"),
         }
     }
 }
@@ -282,6 +913,7 @@ struct ManifestEntry {
     file: String,
     integrity: Option<String>,
     css: Option<Vec<String>>,
+    imports: Option<Vec<String>>,
 }
 
 #[cfg(test)]
@@ -331,6 +963,23 @@ mod tests {
         assert!(rendered_layout.contains(r#"https://localhost:5173/app/src/main.ts"#));
     }
 
+    #[test]
+    fn test_development_public_origin() {
+        let layout = |development: Development| development.into_config().layout()("{}".into());
+
+        let rendered = layout(
+            Development::default()
+                .public_origin("https://app.test/")
+                .react(),
+        );
+        assert!(rendered.contains(r#"src="https://app.test/@vite/client""#));
+        assert!(rendered.contains(r#"src="https://app.test/src/main.ts""#));
+        assert!(rendered.contains(r#"from "https://app.test/@react-refresh""#));
+
+        let rendered = layout(Development::default().public_origin("").base("/app"));
+        assert!(rendered.contains(r#"src="/app/@vite/client""#));
+    }
+
     #[test]
     fn test_development_into_config() {
         let main_script = "src/index.ts";
@@ -354,11 +1003,111 @@ mod tests {
         assert!(rendered_layout.contains(r#"<title>app-title-here</title>"#));
         assert!(rendered_layout.contains(r#"{&quot;someprops&quot;: &quot;somevalues&quot;}"#));
         assert!(rendered_layout.contains(r#"http://localhost:8080/app/@vite/client"#));
+
+        let binding = config_layout(r#"{"bio": "</div><script>"}"#.to_string());
+        assert!(binding.contains(r#"&quot;\u003c/div\u003e\u003cscript\u003e&quot;"#));
         assert!(
             rendered_layout.contains(r#"window.__vite_plugin_react_preamble_installed__ = true"#)
         );
     }
 
+    #[test]
+    fn test_development_is_running() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let development = Development::default().port(port);
+
+        assert!(development.is_running());
+
+        drop(listener);
+        assert!(!development.is_running());
+    }
+
+    #[test]
+    fn test_production_from_dist_dir() {
+        let dir = std::env::temp_dir().join(format!("axum-inertia-dist-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join(".vite")).unwrap();
+        std::fs::create_dir_all(dir.join("assets")).unwrap();
+        assert!(matches!(
+            *Production::from_dist_dir(&dir, "main.js")
+                .err()
+                .unwrap()
+                .downcast::<ViteError>()
+                .unwrap(),
+            ViteError::ManifestMissing(_)
+        ));
+
+        std::fs::write(
+            dir.join(".vite/manifest.json"),
+            r#"{"main.js": {"file": "assets/main.js", "css": ["assets/main.css"]}}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("assets/main.js"), "").unwrap();
+        let err = Production::from_dist_dir(&dir, "main.js").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "vite build missing assets: assets/main.css"
+        );
+
+        std::fs::write(dir.join("assets/main.css"), "").unwrap();
+        let production = Production::from_dist_dir(&dir, "main.js").unwrap();
+        assert_eq!(production.main.file, "assets/main.js");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "integrity")]
+    #[test]
+    fn test_production_verify() {
+        let dir = std::env::temp_dir().join(format!("axum-inertia-sri-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join(".vite")).unwrap();
+        std::fs::create_dir_all(dir.join("assets")).unwrap();
+        // sha256 of "main", base64-encoded.
+        std::fs::write(
+            dir.join(".vite/manifest.json"),
+            r#"{"main.js": {"file": "assets/main.js", "integrity": "sha256-DW5AeeNnA+vTfAByL1iR0osOKBHcEUsSkhUSOtzONgU="}}"#,
+        )
+        .unwrap();
+        let manifest = dir.join(".vite/manifest.json");
+        let manifest = manifest.to_str().unwrap();
+
+        let err = Production::new(manifest, "main.js")
+            .unwrap()
+            .verify()
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "vite build missing assets: assets/main.js");
+
+        std::fs::write(dir.join("assets/main.js"), "stale").unwrap();
+        let err = Production::new(manifest, "main.js")
+            .unwrap()
+            .verify()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "vite build assets don't match their integrity: assets/main.js"
+        );
+
+        std::fs::write(dir.join("assets/main.js"), "main").unwrap();
+        assert!(Production::new(manifest, "main.js")
+            .unwrap()
+            .verify()
+            .is_ok());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_dist_dir_of() {
+        assert_eq!(
+            dist_dir_of(Path::new("client/dist/.vite/manifest.json")),
+            Some(PathBuf::from("client/dist"))
+        );
+        assert_eq!(
+            dist_dir_of(Path::new("client/dist/manifest.json")),
+            Some(PathBuf::from("client/dist"))
+        );
+    }
+
     #[test]
     fn test_production_new_entry_missing() {
         let manifest_content = r#"{"main.js": {}}"#;
@@ -386,6 +1135,48 @@ mod tests {
         assert_eq!(production.version, content_hash);
     }
 
+    #[tokio::test]
+    async fn test_production_new_from_bytes_and_reader() {
+        let manifest_content: &[u8] = br#"{"main.js": {"file": "main.hash-id-here.js"}}"#;
+
+        let production = Production::new_from_bytes(manifest_content, "main.js").unwrap();
+        assert_eq!(production.main.file, "main.hash-id-here.js");
+        assert_eq!(production.version, encode(Sha1::digest(manifest_content)));
+
+        let production = Production::new_from_reader(manifest_content, "main.js")
+            .await
+            .unwrap();
+        assert_eq!(production.main.file, "main.hash-id-here.js");
+
+        assert!(Production::new_from_bytes(b"\xff", "main.js").is_err());
+    }
+
+    #[test]
+    fn test_production_legacy() {
+        let manifest_content = r#"{
+            "src/main.ts": {"file": "assets/main-1.js"},
+            "src/main-legacy.ts": {"file": "assets/main-legacy-2.js"},
+            "vite/legacy-polyfills": {"file": "assets/polyfills-3.js"},
+            "vite/legacy-polyfills-legacy": {"file": "assets/polyfills-legacy-4.js"}
+        }"#;
+        let production = Production::new_from_string(manifest_content, "src/main.ts").unwrap();
+        let layout = production.into_config().layout()("{}".to_string());
+        assert!(!layout.contains("nomodule"));
+
+        let production = Production::new_from_string(manifest_content, "src/main.ts").unwrap();
+        let layout = production.legacy().into_config().layout()("{}".to_string());
+        assert!(layout.contains(
+            r#"<script type="module" crossorigin src="/assets/polyfills-3.js"></script>"#
+        ));
+        assert!(layout.contains(
+            r#"<script nomodule crossorigin id="vite-legacy-polyfill" src="/assets/polyfills-legacy-4.js"></script>"#
+        ));
+        assert!(layout.contains(
+            r#"<script nomodule crossorigin id="vite-legacy-entry" data-src="/assets/main-legacy-2.js">System.import("#
+        ));
+        assert!(layout.contains("window.__vite_is_modern_browser=true"));
+    }
+
     #[test]
     fn test_production_builder_methods() {
         let manifest_content =
@@ -421,6 +1212,64 @@ mod tests {
         assert!(rendered_layout.contains(r#"{&quot;someprops&quot;: &quot;somevalues&quot;}"#));
     }
 
+    #[cfg(feature = "compress")]
+    #[test]
+    fn test_production_compress_page() {
+        let manifest_content = r#"{"main.js": {"file": "main-1.js"}}"#;
+        let config = Production::new_from_string(manifest_content, "main.js")
+            .unwrap()
+            .compress_page(16)
+            .into_config();
+
+        let layout = config.layout()("{}".to_string());
+        assert!(layout.contains(r#"<div id="app" data-page="{}"></div>"#));
+
+        let layout = config.layout()(r#"{"component":"Home","props":{}}"#.to_string());
+        assert!(layout.contains(r#"<link rel="modulepreload" href="/main-1.js">"#));
+        assert!(layout.contains(r#"<div id="app"></div>"#));
+        assert!(layout.contains(r#"import("/main-1.js")"#));
+        assert!(!layout.contains(r#"<script type="module" src="/main-1.js">"#));
+    }
+
+    #[test]
+    fn test_production_external_page() {
+        let manifest_content = r#"{"main.js": {"file": "main-1.js"}}"#;
+        let config = Production::new_from_string(manifest_content, "main.js")
+            .unwrap()
+            .external_page(PageStore::new())
+            .into_config();
+
+        let layout = config.layout()(r#"{"component":"Home","props":{}}"#.to_string());
+        assert!(layout.contains(r#"<link rel="modulepreload" href="/main-1.js">"#));
+        assert!(layout.contains(r#"<div id="app"></div>"#));
+        assert!(layout.contains(r#"await fetch("/_inertia/page/"#));
+        assert!(layout.contains(r#"import("/main-1.js")"#));
+        assert!(!layout.contains("data-page="));
+        assert!(!layout.contains(r#"<script type="module" src="/main-1.js">"#));
+    }
+
+    #[test]
+    fn test_production_page_entry() {
+        let manifest_content = r#"{
+            "src/main.ts": {"file": "assets/main-1.js"},
+            "src/Pages/Users.vue": {"file": "assets/Users-2.js", "css": ["assets/Users-3.css"], "imports": ["_shared.js"]},
+            "_shared.js": {"file": "assets/shared-4.js", "imports": ["src/Pages/Users.vue"]}
+        }"#;
+        let config = Production::new_from_string(manifest_content, "src/main.ts")
+            .unwrap()
+            .page_entry(|component| format!("src/Pages/{}.vue", component))
+            .into_config();
+
+        let layout = config.render_layout("Users", "{}".to_string());
+        assert!(layout.contains(r#"<link rel="modulepreload" href="/assets/Users-2.js">"#));
+        assert!(layout.contains(r#"<link rel="modulepreload" href="/assets/shared-4.js">"#));
+        assert!(layout.contains(r#"<link rel="stylesheet" href="/assets/Users-3.css">"#));
+
+        let layout = config.render_layout("Missing", "{}".to_string());
+        assert!(layout.contains(r#"<script type="module" src="/assets/main-1.js"></script>"#));
+        assert!(!layout.contains("modulepreload"));
+    }
+
     #[test]
     fn test_production_into_config_with_integrity() {
         let manifest_content = r#"{"main.js": {"file": "main.hash-id-here.js", "integrity": "sha000-shaHashHere1234", "css": ["style.css"]}}"#;